@@ -1,3 +1,77 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// Splits CSV text into records, honouring quoted fields that contain commas,
+// doubled quotes or line breaks (the IEEE export quotes most organization
+// names and addresses)
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    records
+}
+
+// data/oui.csv is the IEEE MA-L registry export
+// (https://standards-oui.ieee.org/oui/oui.csv); replacing the file is all a
+// vendor refresh takes. oui.rs binary searches the generated table, so it
+// is written sorted by prefix.
+fn generate_oui_table() {
+    println!("cargo:rerun-if-changed=data/oui.csv");
+
+    let csv = fs::read_to_string("data/oui.csv").expect("Failed to read data/oui.csv");
+
+    let mut entries: Vec<(String, String)> = parse_csv(&csv)
+        .into_iter()
+        .skip(1)
+        .filter_map(|record| {
+            let prefix = record.get(1)?.trim().to_ascii_uppercase();
+            let vendor = record.get(2)?.trim().to_string();
+            (prefix.len() == 6
+                && prefix.chars().all(|c| c.is_ascii_hexdigit())
+                && !vendor.is_empty())
+            .then_some((prefix, vendor))
+        })
+        .collect();
+    entries.sort();
+    entries.dedup_by(|a, b| a.0 == b.0);
+
+    let mut table = String::from("&[\n");
+    for (prefix, vendor) in &entries {
+        table.push_str(&format!("    ({:?}, {:?}),\n", prefix, vendor));
+    }
+    table.push(']');
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is not set");
+    fs::write(Path::new(&out_dir).join("oui_table.rs"), table)
+        .expect("Failed to write the OUI table");
+}
+
 fn main() {
+    generate_oui_table();
     tauri_build::build()
 }
@@ -0,0 +1,371 @@
+use crate::certificates::fetch_certificates;
+use crate::dashboard::fetch_gateway_status;
+use crate::db::{AlertMonitorSettings, ApiInfo, Database};
+use crate::error::ApiError;
+use crate::system_resources::fetch_system_disk;
+use crate::update_checker::check_updates;
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::Notify;
+
+// Filesystems this full are critical rather than a warning
+const DISK_CRITICAL_PCT: u8 = 98;
+
+// Every scan runs a firmware check, so don't poll more often than this
+const MIN_MONITOR_INTERVAL: u64 = 300;
+const MAX_MONITOR_INTERVAL: u64 = 86400;
+
+/// Wakes the background monitor when its settings change so a new
+/// interval or an enable takes effect without waiting out the old one
+#[derive(Default)]
+pub struct AlertMonitor {
+    wake: Notify,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    GatewayDown,
+    GatewayDegraded,
+    DiskFull,
+    CertificateExpiring,
+    CertificateExpired,
+    UpdateAvailable,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub severity: AlertSeverity,
+    /// What the alert is about: a gateway name, mountpoint or certificate
+    pub subject: String,
+    pub message: String,
+}
+
+/// Alerts from every check that could run. A check that failed is listed
+/// under its name in `errors` and doesn't affect the others.
+#[derive(Serialize, Debug)]
+pub struct AlertScan {
+    pub alerts: Vec<Alert>,
+    pub errors: HashMap<String, String>,
+}
+
+async fn gateway_alerts(api_info: &ApiInfo) -> Result<Vec<Alert>, String> {
+    let status = fetch_gateway_status(api_info).await?;
+
+    Ok(status
+        .items
+        .iter()
+        .filter_map(|gateway| {
+            // dpinger reports "none" for a healthy gateway
+            let (kind, severity) = match gateway.status.as_str() {
+                "none" | "" => return None,
+                "down" | "force_down" => (AlertKind::GatewayDown, AlertSeverity::Critical),
+                _ => (AlertKind::GatewayDegraded, AlertSeverity::Warning),
+            };
+            Some(Alert {
+                kind,
+                severity,
+                subject: gateway.name.clone(),
+                message: format!("Gateway {}: {}", gateway.name, gateway.status_translated),
+            })
+        })
+        .collect())
+}
+
+async fn disk_alerts(api_info: &ApiInfo) -> Result<Vec<Alert>, String> {
+    let disk = fetch_system_disk(api_info).await?;
+
+    Ok(disk
+        .devices
+        .iter()
+        .filter(|device| device.nearly_full)
+        .map(|device| {
+            let inodes_pct = device.inodes_used_pct.unwrap_or(0);
+            let severity = if device.used_pct.max(inodes_pct) >= DISK_CRITICAL_PCT {
+                AlertSeverity::Critical
+            } else {
+                AlertSeverity::Warning
+            };
+            let message = if inodes_pct > device.used_pct {
+                format!(
+                    "{} is using {}% of its inodes",
+                    device.mountpoint, inodes_pct
+                )
+            } else {
+                format!("{} is {}% full", device.mountpoint, device.used_pct)
+            };
+            Alert {
+                kind: AlertKind::DiskFull,
+                severity,
+                subject: device.mountpoint.clone(),
+                message,
+            }
+        })
+        .collect())
+}
+
+async fn certificate_alerts(api_info: &ApiInfo) -> Result<Vec<Alert>, String> {
+    let certificates = fetch_certificates(api_info).await?;
+
+    Ok(certificates
+        .iter()
+        .filter(|cert| cert.expiring_soon)
+        .filter_map(|cert| {
+            let days = cert.days_until_expiry?;
+            let name = if cert.description.is_empty() {
+                cert.common_name.clone()
+            } else {
+                cert.description.clone()
+            };
+            let alert = if days < 0 {
+                Alert {
+                    kind: AlertKind::CertificateExpired,
+                    severity: AlertSeverity::Critical,
+                    message: format!("Certificate {} expired {} days ago", name, -days),
+                    subject: name,
+                }
+            } else {
+                Alert {
+                    kind: AlertKind::CertificateExpiring,
+                    severity: AlertSeverity::Warning,
+                    message: format!("Certificate {} expires in {} days", name, days),
+                    subject: name,
+                }
+            };
+            Some(alert)
+        })
+        .collect())
+}
+
+async fn update_alerts(api_info: &ApiInfo) -> Result<Vec<Alert>, String> {
+    // The firmware check polls until the firewall finishes, so bound it
+    let timeout = Duration::from_secs(api_info.long_timeout().unwrap_or(300));
+    let status = tokio::time::timeout(timeout, check_updates(api_info))
+        .await
+        .map_err(|_| "Firmware check timed out".to_string())??;
+
+    let target = status["major_upgrade_version"]
+        .as_str()
+        .filter(|_| status["has_major_upgrade"] == true)
+        .or_else(|| {
+            status["target_version"]
+                .as_str()
+                .filter(|_| status["has_minor_upgrade"] == true)
+        })
+        .or_else(|| {
+            // Package-only updates have no new OPNsense version
+            matches!(status["status"].as_str(), Some("update" | "upgrade")).then_some("")
+        });
+
+    Ok(target
+        .map(|version| Alert {
+            kind: AlertKind::UpdateAvailable,
+            severity: AlertSeverity::Info,
+            subject: version.to_string(),
+            message: if version.is_empty() {
+                "Package updates are available".to_string()
+            } else {
+                format!("OPNsense {} is available", version)
+            },
+        })
+        .into_iter()
+        .collect())
+}
+
+fn collect(
+    alerts: &mut Vec<Alert>,
+    errors: &mut HashMap<String, String>,
+    name: &str,
+    result: Result<Vec<Alert>, String>,
+) {
+    match result {
+        Ok(found) => alerts.extend(found),
+        Err(e) => {
+            errors.insert(name.to_string(), e);
+        }
+    }
+}
+
+impl Alert {
+    // Alerts worth interrupting the user for when the app is in the background
+    fn should_notify(&self) -> bool {
+        self.severity == AlertSeverity::Critical || self.kind == AlertKind::UpdateAvailable
+    }
+}
+
+pub(crate) async fn scan(api_info: &ApiInfo) -> AlertScan {
+    // join! rather than try_join! so one failing check does not discard the rest
+    let (gateways, disk, certificates, updates) = tokio::join!(
+        gateway_alerts(api_info),
+        disk_alerts(api_info),
+        certificate_alerts(api_info),
+        update_alerts(api_info),
+    );
+
+    let mut alerts = Vec::new();
+    let mut errors = HashMap::new();
+    collect(&mut alerts, &mut errors, "gateways", gateways);
+    collect(&mut alerts, &mut errors, "disk", disk);
+    collect(&mut alerts, &mut errors, "certificates", certificates);
+    collect(&mut alerts, &mut errors, "updates", updates);
+
+    // Most severe first
+    alerts.sort_by_key(|alert| std::cmp::Reverse(alert.severity));
+
+    AlertScan { alerts, errors }
+}
+
+#[tauri::command]
+pub async fn scan_for_alerts(
+    database: State<'_, Database>,
+    profile_id: Option<i64>,
+) -> Result<AlertScan, ApiError> {
+    let api_info = database
+        .get_profile_api_info(profile_id)
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    Ok(scan(&api_info).await)
+}
+
+#[tauri::command]
+pub fn get_alert_monitor_settings(
+    database: State<'_, Database>,
+) -> Result<AlertMonitorSettings, String> {
+    database
+        .get_alert_monitor_settings()
+        .map_err(|e| format!("Failed to get alert monitor settings: {}", e))
+}
+
+#[tauri::command]
+pub fn save_alert_monitor_settings(
+    database: State<'_, Database>,
+    monitor: State<'_, AlertMonitor>,
+    enabled: bool,
+    interval_seconds: u64,
+) -> Result<(), String> {
+    if !(MIN_MONITOR_INTERVAL..=MAX_MONITOR_INTERVAL).contains(&interval_seconds) {
+        return Err(format!(
+            "Interval must be between {} and {} seconds",
+            MIN_MONITOR_INTERVAL, MAX_MONITOR_INTERVAL
+        ));
+    }
+
+    database
+        .save_alert_monitor_settings(&AlertMonitorSettings {
+            enabled,
+            interval_seconds,
+        })
+        .map_err(|e| format!("Failed to save alert monitor settings: {}", e))?;
+
+    monitor.wake.notify_one();
+    Ok(())
+}
+
+fn notify(app: &AppHandle, alert: &Alert) {
+    let title = match alert.kind {
+        AlertKind::UpdateAvailable => "Update available",
+        AlertKind::GatewayDown => "Gateway down",
+        AlertKind::CertificateExpired => "Certificate expired",
+        AlertKind::DiskFull => "Disk almost full",
+        _ => "Firewall alert",
+    };
+
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title(title)
+        .body(&alert.message)
+        .show()
+    {
+        warn!("Failed to show notification: {}", e);
+    }
+}
+
+async fn run_alert_monitor(app: AppHandle) {
+    // Alerts already notified for the current profile, by kind and subject
+    let mut active: HashSet<(AlertKind, String)> = HashSet::new();
+    let mut active_profile = None;
+
+    loop {
+        let database = app.state::<Database>();
+        let settings = database.get_alert_monitor_settings().unwrap_or_else(|e| {
+            warn!("Failed to read alert monitor settings: {}", e);
+            AlertMonitorSettings::default()
+        });
+
+        // Credentials are only readable while the PIN is cached
+        let api_info = match database.get_default_api_info() {
+            Ok(Some(api_info)) if !api_info.api_key.is_empty() => Some(api_info),
+            _ => None,
+        };
+
+        if let (true, Some(api_info)) = (settings.enabled, api_info) {
+            if active_profile != Some(api_info.id) {
+                active.clear();
+                active_profile = Some(api_info.id);
+            }
+
+            let result = scan(&api_info).await;
+            for (check, e) in &result.errors {
+                warn!("Alert check '{}' failed: {}", check, e);
+            }
+
+            let current: HashSet<(AlertKind, String)> = result
+                .alerts
+                .iter()
+                .filter(|alert| alert.should_notify())
+                .map(|alert| {
+                    let key = (alert.kind, alert.subject.clone());
+                    if !active.contains(&key) {
+                        info!("New alert: {}", alert.message);
+                        notify(&app, alert);
+                    }
+                    key
+                })
+                .collect();
+
+            // A check that failed this round keeps its previous alerts so
+            // they don't notify again once it succeeds
+            active = current
+                .into_iter()
+                .chain(active.into_iter().filter(|(kind, _)| {
+                    let check = match kind {
+                        AlertKind::GatewayDown | AlertKind::GatewayDegraded => "gateways",
+                        AlertKind::DiskFull => "disk",
+                        AlertKind::CertificateExpiring | AlertKind::CertificateExpired => {
+                            "certificates"
+                        }
+                        AlertKind::UpdateAvailable => "updates",
+                    };
+                    result.errors.contains_key(check)
+                }))
+                .collect();
+        }
+
+        let monitor = app.state::<AlertMonitor>();
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(settings.interval_seconds)) => {}
+            _ = monitor.wake.notified() => {}
+        }
+    }
+}
+
+pub fn register_alert_monitor(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(AlertMonitor::default());
+    tauri::async_runtime::spawn(run_alert_monitor(app.handle().clone()));
+    Ok(())
+}
@@ -1,15 +1,123 @@
 use crate::db::Database;
+use crate::error::ApiError;
 use crate::http_client::make_http_request;
+use crate::pending::{PendingChanges, Subsystem};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use serde_json::Value;
+use std::collections::HashMap;
+use std::net::IpAddr;
 use tauri::State;
 
+#[derive(Serialize, Debug)]
+pub struct RejectedEntry {
+    entry: String,
+    reason: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BulkAliasResult {
+    changed: Vec<String>,
+    rejected: Vec<RejectedEntry>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BlockIpResult {
+    alias_name: String,
+    ip: String,
+    /// The IP was already in the alias, so nothing was changed
+    already_present: bool,
+    /// Whether the alias lists the IP after the update
+    contained: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AliasReference {
+    /// "filter_rule", "nat_rule" or "alias"
+    kind: String,
+    uuid: String,
+    description: String,
+    /// The field that mentions the alias, e.g. "source_net"
+    field: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AliasSearchResponse {
+    total: u32,
+    #[serde(rename = "rowCount")]
+    row_count: i64,
+    current: u32,
+    rows: Vec<Value>,
+}
+
+/// Where an alias gets its entries from
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum AliasSource {
+    /// Entries typed into the alias itself
+    Static,
+    /// Entries downloaded from a URL, such as threat feeds
+    Url,
+}
+
+impl AliasSource {
+    fn alias_types(self) -> &'static [&'static str] {
+        match self {
+            AliasSource::Static => &["host", "network", "port", "networkgroup", "mac"],
+            AliasSource::Url => &["url", "urltable", "urljson"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AliasType {
+    Host,
+    Network,
+    Port,
+    /// URL tables, GeoIP, MAC and the other types checked by the firewall only
+    Other,
+}
+
+impl AliasType {
+    // searchItem may report the type by key ("host") or by label ("Host(s)")
+    fn parse(alias_type: &str) -> Self {
+        match alias_type.trim().to_ascii_lowercase().as_str() {
+            "host" | "host(s)" => AliasType::Host,
+            "network" | "network(s)" | "networkgroup" | "network group" => AliasType::Network,
+            "port" | "port(s)" => AliasType::Port,
+            _ => AliasType::Other,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            AliasType::Host => "host",
+            AliasType::Network => "network",
+            AliasType::Port => "port",
+            AliasType::Other => "non-address",
+        }
+    }
+}
+
+// Rule fields that can hold an alias, flattened and legacy names included
+const RULE_ALIAS_FIELDS: &[&str] = &[
+    "source_net",
+    "source_port",
+    "destination_net",
+    "destination_port",
+    "source.network",
+    "source.port",
+    "destination.network",
+    "destination.port",
+    "target",
+];
+
 fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
     format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
 }
 
 #[tauri::command]
-pub async fn list_network_aliases(database: State<'_, Database>) -> Result<Value, String> {
+pub async fn list_network_aliases(database: State<'_, Database>) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -22,42 +130,68 @@ pub async fn list_network_aliases(database: State<'_, Database>) -> Result<Value
         &url,
         None,
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
 
 #[tauri::command]
-pub async fn get_alias(database: State<'_, Database>, alias_name: String) -> Result<Value, String> {
-    let aliases = search_alias_items(database).await?;
-
-    let alias = aliases["rows"]
-        .as_array()
-        .and_then(|rows| {
-            rows.iter()
-                .find(|row| row["name"].as_str() == Some(&alias_name))
-        })
-        .ok_or_else(|| format!("Alias '{}' not found", alias_name))?;
-
-    Ok(alias.clone())
+pub async fn get_alias(
+    database: State<'_, Database>,
+    alias_name: String,
+) -> Result<Value, ApiError> {
+    all_aliases(database)
+        .await?
+        .into_iter()
+        .find(|row| row["name"].as_str() == Some(&alias_name))
+        .ok_or_else(|| ApiError::NotFound(format!("Alias '{}' not found", alias_name)))
 }
 
 #[tauri::command]
 pub async fn add_alias(
     database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    name: String,
+    alias_type: String,
+    content: String,
+    description: String,
+    enabled: bool,
+) -> Result<Value, ApiError> {
+    let target = name.clone();
+    crate::audit::audited(
+        &database,
+        "add_alias",
+        &target,
+        add_alias_request(
+            database.clone(),
+            pending,
+            name,
+            alias_type,
+            content,
+            description,
+            enabled,
+        ),
+    )
+    .await
+}
+
+async fn add_alias_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
     name: String,
     alias_type: String,
     content: String,
     description: String,
     enabled: bool,
-) -> Result<Value, String> {
+) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -93,9 +227,10 @@ pub async fn add_alias(
         &url,
         Some(payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -105,7 +240,7 @@ pub async fn add_alias(
         .map_err(|e| format!("Failed to parse response: {}", e))?;
 
     if result["result"].as_str() == Some("saved") {
-        apply_alias_changes(database).await?;
+        apply_alias_changes(database, pending).await?;
     }
 
     Ok(result)
@@ -114,10 +249,49 @@ pub async fn add_alias(
 #[tauri::command]
 pub async fn add_ip_to_alias(
     database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+    current_content: String,
+    new_ip: String,
+) -> Result<(), ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "add_ip_to_alias",
+        &target,
+        add_ip_to_alias_request(database.clone(), pending, uuid, current_content, new_ip),
+    )
+    .await
+}
+
+async fn add_ip_to_alias_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
     uuid: String,
     current_content: String,
-    _new_ip: String,
-) -> Result<(), String> {
+    new_ip: String,
+) -> Result<(), ApiError> {
+    let aliases = all_aliases(database.clone()).await?;
+    let alias = aliases
+        .iter()
+        .find(|row| row["uuid"].as_str() == Some(&uuid))
+        .ok_or_else(|| format!("Alias '{}' not found", uuid))?;
+    let alias_type = AliasType::parse(alias["type"].as_str().unwrap_or_default());
+    let alias_name = alias["name"].as_str().unwrap_or(&uuid);
+
+    // Only entries new to the alias are checked, so it can still be saved
+    // when it already holds something unexpected
+    let existing = content_entries(alias["content"].as_str().unwrap_or_default());
+    let added = content_entries(&current_content)
+        .into_iter()
+        .chain(content_entries(&new_ip))
+        .filter(|entry| !existing.contains(entry));
+    for entry in added {
+        if let Some(reason) = type_mismatch(&entry, alias_type, alias_name) {
+            return Err(reason.into());
+        }
+    }
+
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -140,26 +314,44 @@ pub async fn add_ip_to_alias(
         &url,
         Some(payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     if response.status().is_success() {
-        apply_alias_changes(database).await?;
+        apply_alias_changes(database, pending).await?;
         Ok(())
     } else {
-        Err(format!("Failed to add IP to alias: {}", response.status()))
+        Err(format!("Failed to add IP to alias: {}", response.status()).into())
     }
 }
 
 #[tauri::command]
 pub async fn remove_ip_from_alias(
     database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+    current_content: String,
+) -> Result<(), ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "remove_ip_from_alias",
+        &target,
+        remove_ip_from_alias_request(database.clone(), pending, uuid, current_content),
+    )
+    .await
+}
+
+async fn remove_ip_from_alias_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
     uuid: String,
     current_content: String,
-) -> Result<(), String> {
+) -> Result<(), ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -182,25 +374,42 @@ pub async fn remove_ip_from_alias(
         &url,
         Some(payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     if response.status().is_success() {
-        apply_alias_changes(database).await?;
+        apply_alias_changes(database, pending).await?;
         Ok(())
     } else {
-        Err(format!(
-            "Failed to remove IP from alias: {}",
-            response.status()
-        ))
+        Err(format!("Failed to remove IP from alias: {}", response.status()).into())
     }
 }
 
 #[tauri::command]
-pub async fn toggle_alias(database: State<'_, Database>, uuid: String) -> Result<Value, String> {
+pub async fn toggle_alias(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+) -> Result<Value, ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "toggle_alias",
+        &target,
+        toggle_alias_request(database.clone(), pending, uuid),
+    )
+    .await
+}
+
+async fn toggle_alias_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -216,9 +425,10 @@ pub async fn toggle_alias(database: State<'_, Database>, uuid: String) -> Result
         &url,
         Some(json!({})),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -228,14 +438,68 @@ pub async fn toggle_alias(database: State<'_, Database>, uuid: String) -> Result
         .map_err(|e| format!("Failed to parse response: {}", e))?;
 
     if result["changed"].as_bool().unwrap_or(false) {
-        apply_alias_changes(database).await?;
+        apply_alias_changes(database, pending).await?;
     }
 
     Ok(result)
 }
 
+/// Deletes an alias. Unless `force` is set, an alias still referenced by rules
+/// or other aliases is refused with the list of references.
 #[tauri::command]
-pub async fn delete_alias(database: State<'_, Database>, uuid: String) -> Result<Value, String> {
+pub async fn delete_alias(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+    force: Option<bool>,
+) -> Result<Value, ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "delete_alias",
+        &target,
+        delete_alias_request(database.clone(), pending, uuid, force.unwrap_or(false)),
+    )
+    .await
+}
+
+async fn delete_alias_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+    force: bool,
+) -> Result<Value, ApiError> {
+    if !force {
+        let aliases = all_aliases(database.clone()).await?;
+        let alias_name = aliases
+            .iter()
+            .find(|row| row["uuid"].as_str() == Some(&uuid))
+            .and_then(|row| row["name"].as_str())
+            .map(str::to_string);
+
+        if let Some(alias_name) = alias_name {
+            let references = get_alias_references(database.clone(), alias_name.clone()).await?;
+            if !references.is_empty() {
+                let used_by: Vec<String> = references
+                    .iter()
+                    .map(|reference| {
+                        if reference.description.is_empty() {
+                            format!("{} {}", reference.kind, reference.uuid)
+                        } else {
+                            format!("{} '{}'", reference.kind, reference.description)
+                        }
+                    })
+                    .collect();
+                return Err(format!(
+                    "Cannot delete alias '{}' because it is in use by: {}",
+                    alias_name,
+                    used_by.join(", ")
+                )
+                .into());
+            }
+        }
+    }
+
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -248,9 +512,10 @@ pub async fn delete_alias(database: State<'_, Database>, uuid: String) -> Result
         &url,
         Some(json!({})),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await;
 
@@ -262,7 +527,7 @@ pub async fn delete_alias(database: State<'_, Database>, uuid: String) -> Result
                     .await
                     .map_err(|e| format!("Failed to parse response: {}", e))?;
 
-                apply_alias_changes(database).await?;
+                apply_alias_changes(database, pending).await?;
                 Ok(result)
             } else {
                 let error_text = response
@@ -271,9 +536,9 @@ pub async fn delete_alias(database: State<'_, Database>, uuid: String) -> Result
                     .unwrap_or_else(|_| "Unknown error".to_string());
                 if error_text.contains("Alias in use") || error_text.contains("Currently in use by")
                 {
-                    Err("Cannot delete this alias because it is currently in use by firewall rules. Please remove references to this alias in your firewall rules first.".to_string())
+                    Err("Cannot delete this alias because it is currently in use by firewall rules. Please remove references to this alias in your firewall rules first.".to_string().into())
                 } else {
-                    Err(format!("Server returned error: {}", error_text))
+                    Err(format!("Server returned error: {}", error_text).into())
                 }
             }
         }
@@ -282,12 +547,31 @@ pub async fn delete_alias(database: State<'_, Database>, uuid: String) -> Result
 }
 
 #[tauri::command]
-pub async fn apply_alias_changes(database: State<'_, Database>) -> Result<Value, String> {
+pub async fn apply_alias_changes(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+) -> Result<Value, ApiError> {
+    crate::audit::audited(
+        &database,
+        "apply_alias_changes",
+        "",
+        apply_alias_changes_request(database.clone(), pending),
+    )
+    .await
+}
+
+async fn apply_alias_changes_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
         .ok_or_else(|| "API info not found".to_string())?;
 
+    // Stays pending unless the reconfigure succeeds
+    pending.mark(api_info.id, Subsystem::Aliases);
+
     // Step 1: Call the set API
     let set_url = build_api_url(&api_info, "/api/firewall/alias/set");
     let set_payload = json!({
@@ -303,9 +587,10 @@ pub async fn apply_alias_changes(database: State<'_, Database>) -> Result<Value,
         &set_url,
         Some(set_payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -323,12 +608,15 @@ pub async fn apply_alias_changes(database: State<'_, Database>) -> Result<Value,
         &reconfigure_url,
         Some(reconfigure_payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
+    pending.clear(api_info.id, Subsystem::Aliases);
+
     // Return the result of the reconfigure call, or the set result if reconfigure fails
     match reconfigure_response.json::<Value>().await {
         Ok(reconfigure_result) => Ok(reconfigure_result),
@@ -339,7 +627,7 @@ pub async fn apply_alias_changes(database: State<'_, Database>) -> Result<Value,
     }
 }
 
-async fn get_alias_info(api_info: &crate::db::ApiInfo, uuid: &str) -> Result<Value, String> {
+async fn get_alias_info(api_info: &crate::db::ApiInfo, uuid: &str) -> Result<Value, ApiError> {
     let url = build_api_url(api_info, &format!("/api/firewall/alias/getItem/{}", uuid));
 
     let response = make_http_request(
@@ -347,20 +635,29 @@ async fn get_alias_info(api_info: &crate::db::ApiInfo, uuid: &str) -> Result<Val
         &url,
         None,
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
 
+/// One page of aliases. Omitting the paging arguments returns every alias
+/// in a single page; `source` limits the page to static or URL-fed aliases.
 #[tauri::command]
-pub async fn search_alias_items(database: State<'_, Database>) -> Result<Value, String> {
+pub async fn search_alias_items(
+    database: State<'_, Database>,
+    current_page: Option<u32>,
+    rows_per_page: Option<u32>,
+    search_phrase: Option<String>,
+    source: Option<AliasSource>,
+) -> Result<AliasSearchResponse, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -368,19 +665,452 @@ pub async fn search_alias_items(database: State<'_, Database>) -> Result<Value,
 
     let url = build_api_url(&api_info, "/api/firewall/alias/searchItem");
 
+    let mut payload = json!({
+        "current": current_page.unwrap_or(1),
+        "rowCount": rows_per_page.map(i64::from).unwrap_or(-1),
+        "sort": {},
+        "searchPhrase": search_phrase.unwrap_or_default()
+    });
+    // The alias grid filters on a list of alias types
+    if let Some(source) = source {
+        payload["type"] = json!(source.alias_types());
+    }
+
     let response = make_http_request(
-        "GET",
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await
+    .map_err(|e| e.missing_privilege("aliases"))?;
+
+    response
+        .json::<AliasSearchResponse>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
+}
+
+async fn all_aliases(database: State<'_, Database>) -> Result<Vec<Value>, ApiError> {
+    Ok(search_alias_items(database, None, None, None, None)
+        .await?
+        .rows)
+}
+
+/// Entries of every port alias, keyed by alias name
+pub(crate) async fn port_alias_entries(
+    database: State<'_, Database>,
+) -> Result<HashMap<String, Vec<String>>, ApiError> {
+    Ok(all_aliases(database)
+        .await?
+        .iter()
+        .filter(|row| AliasType::parse(row["type"].as_str().unwrap_or_default()) == AliasType::Port)
+        .filter_map(|row| {
+            Some((
+                row["name"].as_str()?.to_string(),
+                content_entries(row["content"].as_str().unwrap_or_default()),
+            ))
+        })
+        .collect())
+}
+
+// Accepts an IP address, a CIDR network or a hostname, normalizing the case
+fn validate_alias_entry(entry: &str) -> Result<String, String> {
+    if entry.parse::<IpAddr>().is_ok() {
+        return Ok(entry.to_string());
+    }
+
+    if let Some((address, prefix)) = entry.split_once('/') {
+        let address = address
+            .parse::<IpAddr>()
+            .map_err(|_| format!("'{}' is not a valid network address", address))?;
+        let max_prefix = if address.is_ipv4() { 32 } else { 128 };
+        return match prefix.parse::<u8>() {
+            Ok(bits) if bits <= max_prefix => Ok(entry.to_string()),
+            _ => Err(format!(
+                "Prefix length '{}' must be between 0 and {}",
+                prefix, max_prefix
+            )),
+        };
+    }
+
+    let hostname = entry.trim_end_matches('.').to_ascii_lowercase();
+    let labels: Vec<&str> = hostname.split('.').collect();
+    let valid_labels = labels.iter().all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    });
+
+    // An all-numeric name is a mistyped address rather than a hostname
+    if hostname.len() <= 253
+        && valid_labels
+        && !labels
+            .iter()
+            .all(|label| label.chars().all(|c| c.is_ascii_digit()))
+    {
+        Ok(hostname)
+    } else {
+        Err("Not a valid IP address, network or hostname".to_string())
+    }
+}
+
+// A port or a "from:to" port range
+fn is_port_entry(entry: &str) -> bool {
+    let parts: Vec<&str> = entry.split(':').collect();
+    parts.len() <= 2 && parts.iter().all(|part| part.parse::<u16>().is_ok())
+}
+
+/// Explains why `entry` doesn't belong in an alias of `alias_type`, e.g. a
+/// port in a host alias. Anything else is left to the firewall to validate.
+fn type_mismatch(entry: &str, alias_type: AliasType, alias_name: &str) -> Option<String> {
+    let is_address = entry.parse::<IpAddr>().is_ok()
+        || entry
+            .split_once('/')
+            .is_some_and(|(address, _)| address.parse::<IpAddr>().is_ok());
+
+    match alias_type {
+        AliasType::Host | AliasType::Network if is_port_entry(entry) => Some(format!(
+            "'{}' is a port, but '{}' is a {} alias",
+            entry,
+            alias_name,
+            alias_type.label()
+        )),
+        AliasType::Port if is_address => Some(format!(
+            "'{}' is an IP address, but '{}' is a port alias",
+            entry, alias_name
+        )),
+        AliasType::Port if entry.contains('.') => Some(format!(
+            "'{}' is a hostname, but only host and network aliases accept hostnames",
+            entry
+        )),
+        _ => None,
+    }
+}
+
+fn content_entries(content: &str) -> Vec<String> {
+    content
+        .split(['\n', ','])
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+// Adds or removes many entries with a single setItem call and one apply
+async fn update_alias_entries(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    alias_name: String,
+    entries: Vec<String>,
+    add: bool,
+) -> Result<BulkAliasResult, ApiError> {
+    let aliases = all_aliases(database.clone()).await?;
+    let alias = aliases
+        .iter()
+        .find(|row| row["name"].as_str() == Some(&alias_name))
+        .ok_or_else(|| format!("Alias '{}' not found", alias_name))?;
+
+    let uuid = alias["uuid"]
+        .as_str()
+        .ok_or_else(|| format!("Alias '{}' has no uuid", alias_name))?
+        .to_string();
+
+    let alias_type = AliasType::parse(alias["type"].as_str().unwrap_or_default());
+    let mut content = content_entries(alias["content"].as_str().unwrap_or_default());
+
+    let mut changed = Vec::new();
+    let mut rejected = Vec::new();
+
+    for raw in entries {
+        let entry = raw.trim();
+        // Feed files commonly contain blank lines and # comments
+        if entry.is_empty() || entry.starts_with('#') {
+            continue;
+        }
+
+        let reject = |reason: &str| RejectedEntry {
+            entry: entry.to_string(),
+            reason: reason.to_string(),
+        };
+
+        if let Some(reason) = type_mismatch(entry, alias_type, &alias_name) {
+            rejected.push(reject(&reason));
+            continue;
+        }
+
+        // Port aliases hold ports and nested port aliases rather than addresses
+        let validated = if alias_type == AliasType::Port {
+            Ok(entry.to_string())
+        } else {
+            validate_alias_entry(entry)
+        };
+        let normalized = match validated {
+            Ok(normalized) => normalized,
+            Err(reason) => {
+                rejected.push(reject(&reason));
+                continue;
+            }
+        };
+
+        let position = content.iter().position(|item| *item == normalized);
+        match (add, position) {
+            (true, None) => {
+                content.push(normalized.clone());
+                changed.push(normalized);
+            }
+            (true, Some(_)) => rejected.push(reject("Already in the alias")),
+            (false, Some(index)) => {
+                content.remove(index);
+                changed.push(normalized);
+            }
+            (false, None) => rejected.push(reject("Not in the alias")),
+        }
+    }
+
+    if changed.is_empty() {
+        return Ok(BulkAliasResult { changed, rejected });
+    }
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, &format!("/api/firewall/alias/setItem/{}", uuid));
+
+    let payload = json!({
+        "alias": {
+            "name": alias_name,
+            "content": content.join("\n"),
+        }
+    });
+
+    let response = make_http_request(
+        "POST",
         &url,
+        Some(payload),
         None,
+        Some(60),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to update alias: {}", response.status()).into());
+    }
+
+    apply_alias_changes(database, pending).await?;
+
+    Ok(BulkAliasResult { changed, rejected })
+}
+
+/// Adds a list of IPs, networks or hostnames to an alias in one update.
+/// Invalid or duplicate entries are reported instead of aborting the import.
+#[tauri::command]
+pub async fn bulk_add_ips_to_alias(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    alias_name: String,
+    entries: Vec<String>,
+) -> Result<BulkAliasResult, ApiError> {
+    let target = alias_name.clone();
+    crate::audit::audited(
+        &database,
+        "bulk_add_ips_to_alias",
+        &target,
+        update_alias_entries(database.clone(), pending, alias_name, entries, true),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn bulk_remove_ips_from_alias(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    alias_name: String,
+    entries: Vec<String>,
+) -> Result<BulkAliasResult, ApiError> {
+    let target = alias_name.clone();
+    crate::audit::audited(
+        &database,
+        "bulk_remove_ips_from_alias",
+        &target,
+        update_alias_entries(database.clone(), pending, alias_name, entries, false),
+    )
+    .await
+}
+
+fn alias_contains(alias: &Value, ip: &str) -> bool {
+    alias["content"]
+        .as_str()
+        .unwrap_or_default()
+        .split(['\n', ','])
+        .any(|item| item.trim() == ip)
+}
+
+/// Adds an address seen in the firewall log to a block alias and applies it.
+/// The alias has to exist already, typically one referenced by a block rule.
+#[tauri::command]
+pub async fn block_ip_from_log(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    ip: String,
+    alias_name: String,
+) -> Result<BlockIpResult, ApiError> {
+    let ip = ip
+        .trim()
+        .parse::<IpAddr>()
+        .map_err(|_| format!("'{}' is not a valid IP address", ip.trim()))?
+        .to_string();
+
+    let alias = get_alias(database.clone(), alias_name.clone())
+        .await
+        .map_err(|_| {
+            format!(
+                "Alias '{}' not found. Create a host alias with that name and a block rule using it first.",
+                alias_name
+            )
+        })?;
+
+    if alias_contains(&alias, &ip) {
+        return Ok(BlockIpResult {
+            alias_name,
+            ip,
+            already_present: true,
+            contained: true,
+        });
+    }
+
+    let uuid = alias["uuid"]
+        .as_str()
+        .ok_or_else(|| format!("Alias '{}' has no uuid", alias_name))?
+        .to_string();
+    let current = alias["content"].as_str().unwrap_or_default().trim();
+    let content = if current.is_empty() {
+        ip.clone()
+    } else {
+        format!("{}\n{}", current, ip)
+    };
+
+    // add_ip_to_alias applies the alias change itself
+    add_ip_to_alias(database.clone(), pending, uuid, content, ip.clone()).await?;
+
+    let alias = get_alias(database, alias_name.clone()).await?;
+
+    Ok(BlockIpResult {
+        contained: alias_contains(&alias, &ip),
+        alias_name,
+        ip,
+        already_present: false,
+    })
+}
+
+pub(crate) async fn search_rule_rows(
+    api_info: &crate::db::ApiInfo,
+    endpoint: &str,
+) -> Result<Vec<Value>, ApiError> {
+    let url = build_api_url(api_info, endpoint);
+
+    let payload = json!({
+        "current": 1,
+        "rowCount": -1,
+        "sort": {},
+        "searchPhrase": ""
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
-    response
+    let result = response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))?;
+
+    Ok(result["rows"].as_array().cloned().unwrap_or_default())
+}
+
+// Fields hold comma separated lists and negate aliases with a leading "!"
+fn mentions_alias(value: &Value, alias_name: &str) -> bool {
+    value.as_str().is_some_and(|text| {
+        text.split([',', '\n'])
+            .map(|item| item.trim().trim_start_matches('!'))
+            .any(|item| item == alias_name)
+    })
+}
+
+fn rule_references(rows: &[Value], kind: &str, alias_name: &str) -> Vec<AliasReference> {
+    rows.iter()
+        .filter_map(|row| {
+            let field = RULE_ALIAS_FIELDS
+                .iter()
+                .find(|field| mentions_alias(&row[**field], alias_name))?;
+
+            Some(AliasReference {
+                kind: kind.to_string(),
+                uuid: row["uuid"].as_str().unwrap_or_default().to_string(),
+                description: row["description"]
+                    .as_str()
+                    .or_else(|| row["descr"].as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                field: field.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Lists the filter rules, port forwards and nested aliases that reference an
+/// alias. Only rules managed through the automation (MVC) API are searched.
+#[tauri::command]
+pub async fn get_alias_references(
+    database: State<'_, Database>,
+    alias_name: String,
+) -> Result<Vec<AliasReference>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let filter_rows = search_rule_rows(&api_info, "/api/firewall/filter/search_rule").await?;
+    let mut references = rule_references(&filter_rows, "filter_rule", &alias_name);
+
+    // The port-forward API only exists on newer firmware
+    match search_rule_rows(&api_info, "/api/firewall/d_nat/search_rule").await {
+        Ok(nat_rows) => references.extend(rule_references(&nat_rows, "nat_rule", &alias_name)),
+        Err(ApiError::NotFound(_)) => {}
+        Err(e) => return Err(e),
+    }
+
+    let aliases = all_aliases(database).await?;
+    references.extend(
+        aliases
+            .iter()
+            .filter(|row| row["name"].as_str() != Some(&alias_name))
+            .filter(|row| mentions_alias(&row["content"], &alias_name))
+            .map(|row| AliasReference {
+                kind: "alias".to_string(),
+                uuid: row["uuid"].as_str().unwrap_or_default().to_string(),
+                description: row["name"].as_str().unwrap_or_default().to_string(),
+                field: "content".to_string(),
+            }),
+    );
+
+    Ok(references)
 }
@@ -0,0 +1,98 @@
+use crate::db::Database;
+use log::LevelFilter;
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+const DEFAULT_LOG_LINES: usize = 200;
+const MAX_LOG_LINES: usize = 5000;
+
+#[derive(Serialize, Debug)]
+pub struct LogLevelSetting {
+    level: String,
+    default_level: String,
+}
+
+// Debug builds keep the verbose output developers expect
+fn default_level() -> LevelFilter {
+    if cfg!(debug_assertions) {
+        LevelFilter::Debug
+    } else {
+        LevelFilter::Warn
+    }
+}
+
+fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    level
+        .parse::<LevelFilter>()
+        .map_err(|_| format!("Unknown log level: {}", level))
+}
+
+fn stored_level(database: &Database) -> LevelFilter {
+    match database.get_log_level() {
+        Ok(Some(level)) => parse_level(&level).unwrap_or_else(|e| {
+            log::warn!("{}", e);
+            default_level()
+        }),
+        Ok(None) => default_level(),
+        Err(e) => {
+            log::warn!("Failed to read log level: {}", e);
+            default_level()
+        }
+    }
+}
+
+/// The log plugin is built before the database opens, so it passes
+/// everything through and the stored level is applied here during setup
+pub fn apply_log_level(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    let level = stored_level(&app.state::<Database>());
+    log::set_max_level(level);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_log_level(database: State<'_, Database>) -> LogLevelSetting {
+    LogLevelSetting {
+        level: stored_level(&database).to_string().to_lowercase(),
+        default_level: default_level().to_string().to_lowercase(),
+    }
+}
+
+/// Takes effect immediately and is kept across restarts
+#[tauri::command]
+pub fn set_log_level(database: State<'_, Database>, level: String) -> Result<(), String> {
+    let level = parse_level(&level)?;
+
+    database
+        .save_log_level(&level.to_string().to_lowercase())
+        .map_err(|e| format!("Failed to save log level: {}", e))?;
+
+    log::set_max_level(level);
+    Ok(())
+}
+
+/// The most recent lines of the app's own log file, oldest first
+#[tauri::command]
+pub fn get_app_logs(app: AppHandle, limit: Option<usize>) -> Result<Vec<String>, String> {
+    let limit = limit.unwrap_or(DEFAULT_LOG_LINES).min(MAX_LOG_LINES);
+
+    // tauri_plugin_log writes <app name>.log to the app log directory
+    let path = app
+        .path()
+        .app_log_dir()
+        .map_err(|e| format!("Failed to find log directory: {}", e))?
+        .join(format!("{}.log", app.package_info().name));
+
+    let contents = match std::fs::read(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+
+    let contents = String::from_utf8_lossy(&contents);
+    let lines: Vec<&str> = contents.lines().collect();
+
+    Ok(lines[lines.len().saturating_sub(limit)..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect())
+}
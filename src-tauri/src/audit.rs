@@ -0,0 +1,47 @@
+use crate::db::{AuditEntry, Database};
+use log::error;
+use std::fmt::Display;
+use std::future::Future;
+use tauri::State;
+
+const DEFAULT_AUDIT_LIMIT: u32 = 200;
+
+/// Records the outcome of a mutating command. A failure to write the entry is
+/// logged but never fails the command itself.
+pub fn record<T, E: Display>(
+    database: &Database,
+    command: &str,
+    target: &str,
+    result: &Result<T, E>,
+) {
+    let (outcome, error) = match result {
+        Ok(_) => ("success", None),
+        Err(e) => ("failure", Some(e.to_string())),
+    };
+
+    if let Err(e) = database.insert_audit_entry(command, target, outcome, error.as_deref()) {
+        error!("Failed to write audit entry for {}: {}", command, e);
+    }
+}
+
+/// Runs a mutating request and records its outcome in the audit log
+pub async fn audited<T, E: Display>(
+    database: &Database,
+    command: &str,
+    target: &str,
+    action: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let result = action.await;
+    record(database, command, target, &result);
+    result
+}
+
+#[tauri::command]
+pub fn get_audit_log(
+    database: State<'_, Database>,
+    limit: Option<u32>,
+) -> Result<Vec<AuditEntry>, String> {
+    database
+        .get_audit_log(limit.unwrap_or(DEFAULT_AUDIT_LIMIT))
+        .map_err(|e| format!("Failed to read audit log: {}", e))
+}
@@ -0,0 +1,215 @@
+use crate::db::Database;
+use crate::error::ApiError;
+use crate::http_client::make_http_request;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager, State};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BackupEntry {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    time: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    filesize: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BackupListResponse {
+    #[serde(default)]
+    items: Vec<BackupEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RestoreResponse {
+    status: u16,
+    result: Value,
+    rebooting: bool,
+}
+
+fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
+    format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
+}
+
+/// Downloads config.xml and writes it untouched to `path`, or to a timestamped
+/// file in the user's download directory when no path is given
+#[tauri::command]
+pub async fn download_config_backup(
+    app_handle: AppHandle,
+    database: State<'_, Database>,
+    path: Option<String>,
+) -> Result<String, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, "/api/core/backup/download/this");
+
+    let response = make_http_request(
+        "GET",
+        &url,
+        None,
+        None,
+        api_info.long_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    // Keep the raw bytes so the file stays restorable
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read backup: {}", e))?;
+
+    let target = match path {
+        Some(path) if !path.trim().is_empty() => PathBuf::from(path),
+        _ => {
+            let download_dir = app_handle
+                .path()
+                .download_dir()
+                .map_err(|e| format!("Failed to resolve download directory: {}", e))?;
+            let timestamp = chrono::Local::now().format("%Y%m%d%H%M%S");
+            download_dir.join(format!("config-opnsense-{}.xml", timestamp))
+        }
+    };
+
+    std::fs::write(&target, &bytes)
+        .map_err(|e| format!("Failed to write backup to {}: {}", target.display(), e))?;
+
+    Ok(target.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub async fn list_backups(database: State<'_, Database>) -> Result<Vec<BackupEntry>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, "/api/core/backup/backups/this");
+
+    let response = make_http_request(
+        "GET",
+        &url,
+        None,
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await
+    .map_err(|e| e.plugin_missing("Backup history is not available on this firewall version"))?;
+
+    let backups = response
+        .json::<BackupListResponse>()
+        .await
+        .map_err(|e| format!("Failed to parse backups: {}", e))?;
+
+    Ok(backups.items)
+}
+
+// Ids come from list_backups, e.g. "config-1700000000.1234.xml"
+fn is_valid_backup_id(backup_id: &str) -> bool {
+    !backup_id.is_empty()
+        && backup_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Reverts the firewall to a backup from its own history, see `list_backups`.
+/// The API has no endpoint for uploading a config.xml, so a downloaded file
+/// can only be restored through the web interface. Requires `confirm` since
+/// the running configuration is replaced
+#[tauri::command]
+pub async fn restore_config_backup(
+    database: State<'_, Database>,
+    backup_id: String,
+    confirm: bool,
+    reboot: bool,
+) -> Result<RestoreResponse, ApiError> {
+    let target = backup_id.clone();
+    crate::audit::audited(
+        &database,
+        "restore_config_backup",
+        &target,
+        restore_config_backup_request(database.clone(), backup_id, confirm, reboot),
+    )
+    .await
+}
+
+async fn restore_config_backup_request(
+    database: State<'_, Database>,
+    backup_id: String,
+    confirm: bool,
+    reboot: bool,
+) -> Result<RestoreResponse, ApiError> {
+    if !confirm {
+        return Err(
+            "Restoring a backup replaces the running configuration; confirmation is required"
+                .to_string()
+                .into(),
+        );
+    }
+
+    if !is_valid_backup_id(&backup_id) {
+        return Err(format!("Invalid backup id: {}", backup_id).into());
+    }
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(
+        &api_info,
+        &format!("/api/core/backup/revertBackup/{}", backup_id),
+    );
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(json!({})),
+        None,
+        api_info.long_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await
+    .map_err(|e| e.plugin_missing("Backup history is not available on this firewall version"))?;
+
+    let status = response.status().as_u16();
+    let response_text = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read restore response: {}", e))?;
+    let result = serde_json::from_str::<Value>(&response_text)
+        .unwrap_or(Value::String(response_text));
+
+    // Only an explicit "reverted" counts; anything else could mean the old
+    // configuration is still running, so there is nothing to reboot into
+    if result["status"].as_str() != Some("reverted") {
+        return Err(format!("The firewall did not confirm the restore: {}", result).into());
+    }
+
+    let rebooting = if reboot {
+        crate::power::reboot_firewall(database).await?;
+        true
+    } else {
+        false
+    };
+
+    Ok(RestoreResponse {
+        status,
+        result,
+        rebooting,
+    })
+}
@@ -0,0 +1,259 @@
+use crate::db::{ApiInfo, Database};
+use crate::error::ApiError;
+use crate::http_client::make_http_request;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::State;
+
+#[derive(Serialize, Debug)]
+pub struct PortalZone {
+    zone_id: String,
+    description: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PortalSession {
+    session_id: String,
+    username: String,
+    mac_address: String,
+    ip_address: String,
+    /// Unix timestamp in seconds
+    started_at: Option<i64>,
+    last_accessed: Option<i64>,
+    bytes_in: u64,
+    bytes_out: u64,
+}
+
+fn build_api_url(api_info: &ApiInfo, endpoint: &str) -> String {
+    format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
+}
+
+fn portal_error(e: ApiError) -> ApiError {
+    e.plugin_missing("Captive portal is not available on this firewall")
+}
+
+fn value_as_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+// Timestamps and counters arrive as numbers, floats or strings
+fn value_as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn parse_session(row: &Value) -> PortalSession {
+    PortalSession {
+        session_id: value_as_string(&row["sessionId"]),
+        username: value_as_string(&row["userName"]),
+        mac_address: value_as_string(&row["macAddress"]),
+        ip_address: value_as_string(&row["ipAddress"]),
+        started_at: value_as_f64(&row["startTime"]).map(|time| time as i64),
+        last_accessed: value_as_f64(&row["last_accessed"]).map(|time| time as i64),
+        bytes_in: value_as_f64(&row["bytes_in"]).unwrap_or(0.0) as u64,
+        bytes_out: value_as_f64(&row["bytes_out"]).unwrap_or(0.0) as u64,
+    }
+}
+
+// Zone ids are numeric in OPNsense and end up in the URL path
+fn validate_zone(zone: &str) -> Result<(), ApiError> {
+    if zone.is_empty() || !zone.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("Invalid captive portal zone: {}", zone).into());
+    }
+    Ok(())
+}
+
+async fn fetch_sessions(api_info: &ApiInfo, zone: &str) -> Result<Value, ApiError> {
+    let payload = json!({
+        "current": 1,
+        "rowCount": -1,
+        "sort": {},
+        "searchPhrase": "",
+        "selected_zones": [zone]
+    });
+
+    let search = make_http_request(
+        "POST",
+        &build_api_url(api_info, "/api/captiveportal/session/search/"),
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await;
+
+    // Firmware before the search endpoint only offers a plain list per zone
+    let response = match search {
+        Err(ApiError::NotFound(_)) => {
+            make_http_request(
+                "GET",
+                &build_api_url(
+                    api_info,
+                    &format!("/api/captiveportal/session/list/{}", zone),
+                ),
+                None,
+                None,
+                api_info.request_timeout(),
+                Some(&api_info.api_key),
+                Some(&api_info.api_secret),
+                Some(&api_info.client_settings()),
+            )
+            .await?
+        }
+        other => other?,
+    };
+
+    response
+        .json::<Value>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse portal sessions: {}", e)))
+}
+
+#[tauri::command]
+pub async fn list_portal_zones(database: State<'_, Database>) -> Result<Vec<PortalZone>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let response = make_http_request(
+        "GET",
+        &build_api_url(&api_info, "/api/captiveportal/session/zones"),
+        None,
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await
+    .map_err(portal_error)?;
+
+    let body = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse portal zones: {}", e))?;
+
+    // Zone ids mapped to their descriptions; an empty list when none exist
+    let mut zones: Vec<PortalZone> = body
+        .as_object()
+        .map(|zones| {
+            zones
+                .iter()
+                .map(|(zone_id, description)| PortalZone {
+                    zone_id: zone_id.clone(),
+                    description: value_as_string(description),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if zones.is_empty() {
+        return Err("Captive portal is not enabled: no zones are configured"
+            .to_string()
+            .into());
+    }
+
+    zones.sort_by(|a, b| a.zone_id.cmp(&b.zone_id));
+    Ok(zones)
+}
+
+/// Clients currently logged in to a captive portal zone
+#[tauri::command]
+pub async fn list_portal_sessions(
+    database: State<'_, Database>,
+    zone: String,
+) -> Result<Vec<PortalSession>, ApiError> {
+    validate_zone(&zone)?;
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let body = fetch_sessions(&api_info, &zone)
+        .await
+        .map_err(portal_error)?;
+
+    // search wraps the sessions in "rows", list returns them bare
+    let rows = body["rows"].as_array().or_else(|| body.as_array());
+
+    // Rows carry their zone, so sessions of other zones are dropped in case
+    // the firewall ignored the zone filter
+    Ok(rows
+        .map(|rows| {
+            rows.iter()
+                .filter(|row| row["zoneid"].is_null() || value_as_string(&row["zoneid"]) == zone)
+                .map(parse_session)
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Logs a client out of the portal; it has to authenticate again
+#[tauri::command]
+pub async fn disconnect_portal_session(
+    database: State<'_, Database>,
+    zone: String,
+    session_id: String,
+) -> Result<Value, ApiError> {
+    validate_zone(&zone)?;
+
+    crate::audit::audited(
+        &database,
+        "disconnect_portal_session",
+        &session_id,
+        disconnect_portal_session_request(database.clone(), zone.clone(), session_id.clone()),
+    )
+    .await
+}
+
+async fn disconnect_portal_session_request(
+    database: State<'_, Database>,
+    zone: String,
+    session_id: String,
+) -> Result<Value, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(
+        &api_info,
+        &format!("/api/captiveportal/session/disconnect/{}", zone),
+    );
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(json!({ "sessionId": session_id })),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await
+    .map_err(portal_error)?;
+
+    let result = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    // An unknown session comes back as an empty object
+    if result.as_object().is_some_and(|result| result.is_empty()) {
+        return Err(format!("Session {} not found in zone {}", session_id, zone).into());
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,134 @@
+use crate::db::Database;
+use crate::error::ApiError;
+use crate::http_client::make_http_request;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::State;
+
+const EXPIRY_WARNING_DAYS: i64 = 30;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Certificate {
+    uuid: String,
+    pub(crate) description: String,
+    pub(crate) common_name: String,
+    issuer: String,
+    valid_from: Option<String>,
+    valid_to: Option<String>,
+    pub(crate) days_until_expiry: Option<i64>,
+    pub(crate) expiring_soon: bool,
+}
+
+fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
+    format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
+}
+
+fn first_field(row: &Value, keys: &[&str]) -> String {
+    keys.iter()
+        .filter_map(|key| match row.get(*key) {
+            Some(Value::String(s)) => Some(s.clone()),
+            Some(Value::Number(n)) => Some(n.to_string()),
+            _ => None,
+        })
+        .find(|v| !v.is_empty())
+        .unwrap_or_default()
+}
+
+// The trust API reports validity either as a unix timestamp or in the
+// openssl notAfter format ("Jan  1 00:00:00 2025 GMT")
+fn parse_cert_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+
+    if let Ok(timestamp) = value.parse::<i64>() {
+        return DateTime::from_timestamp(timestamp, 0);
+    }
+
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date.with_timezone(&Utc));
+    }
+
+    let without_zone = value.trim_end_matches("GMT").trim();
+    let normalized = without_zone
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    NaiveDateTime::parse_from_str(&normalized, "%b %d %H:%M:%S %Y")
+        .ok()
+        .map(|date| date.and_utc())
+}
+
+fn parse_certificate(row: &Value, now: DateTime<Utc>) -> Certificate {
+    let valid_from = parse_cert_date(&first_field(row, &["valid_from", "notBefore"]));
+    let valid_to = parse_cert_date(&first_field(row, &["valid_to", "notAfter"]));
+
+    let days_until_expiry = valid_to.map(|expiry| (expiry - now).num_days());
+
+    Certificate {
+        uuid: first_field(row, &["uuid", "refid"]),
+        description: first_field(row, &["descr", "description"]),
+        common_name: first_field(row, &["commonname", "common_name", "name"]),
+        issuer: first_field(row, &["issuer", "caref_descr", "caref"]),
+        valid_from: valid_from.map(|date| date.to_rfc3339()),
+        valid_to: valid_to.map(|date| date.to_rfc3339()),
+        days_until_expiry,
+        expiring_soon: days_until_expiry.is_some_and(|days| days <= EXPIRY_WARNING_DAYS),
+    }
+}
+
+#[tauri::command]
+pub async fn list_certificates(
+    database: State<'_, Database>,
+) -> Result<Vec<Certificate>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    fetch_certificates(&api_info).await
+}
+
+pub(crate) async fn fetch_certificates(
+    api_info: &crate::db::ApiInfo,
+) -> Result<Vec<Certificate>, ApiError> {
+    let url = build_api_url(api_info, "/api/trust/cert/search/");
+
+    let payload = json!({
+        "current": 1,
+        "rowCount": -1,
+        "sort": {},
+        "searchPhrase": ""
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let result = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse certificates: {}", e))?;
+
+    let now = Utc::now();
+    let mut certificates: Vec<Certificate> = result["rows"]
+        .as_array()
+        .map(|rows| rows.iter().map(|row| parse_certificate(row, now)).collect())
+        .unwrap_or_default();
+
+    // Soonest expiry first, certificates without a parsable date last
+    certificates.sort_by_key(|cert| cert.days_until_expiry.unwrap_or(i64::MAX));
+
+    Ok(certificates)
+}
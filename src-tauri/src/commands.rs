@@ -1,18 +1,23 @@
 use crate::db::{self, ApiInfo, Database};
-use crate::http_client::make_http_request;
+use crate::error::ApiError;
+use crate::http_client::{
+    make_http_request, parse_extra_header, ClientSettings, DEFAULT_MAX_RETRIES,
+};
 use crate::pin_cache::PinCache;
+use argon2::password_hash::{rand_core::OsRng, SaltString};
+use base64::{engine::general_purpose, Engine as _};
 use log::{error, info};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use tauri::State;
 
 #[tauri::command]
-pub async fn get_vendor_info(mac: String) -> Result<String, String> {
+pub async fn get_vendor_info(mac: String) -> Result<String, ApiError> {
     let formatted_mac = mac.replace(":", "-");
     let url = format!("https://api.macvendors.com/{}", formatted_mac);
 
-    match make_http_request("GET", &url, None, None, Some(30), None, None).await {
+    match make_http_request("GET", &url, None, None, Some(30), None, None, None).await {
         Ok(response) => {
             if response.status().is_success() {
                 Ok(response
@@ -22,16 +27,26 @@ pub async fn get_vendor_info(mac: String) -> Result<String, String> {
             } else if response.status().as_u16() == 404 {
                 Ok("Unknown".to_string())
             } else {
-                Err(format!("Failed to get vendor info: {}", response.status()))
+                Err(format!("Failed to get vendor info: {}", response.status()).into())
             }
         }
-        Err(e) => Err(format!("Failed to get vendor info: {}", e)),
+        Err(e) => Err(format!("Failed to get vendor info: {}", e).into()),
     }
 }
 
 #[tauri::command]
-pub fn check_first_run(database: State<Database>) -> Result<bool, String> {
-    database.is_first_run().map_err(|e| e.to_string())
+pub fn check_first_run(database: State<Database>) -> Result<bool, ApiError> {
+    database
+        .is_first_run()
+        .map_err(|e| ApiError::Other(e.to_string()))
+}
+
+fn default_verify_tls() -> bool {
+    true
+}
+
+fn default_max_retries() -> u32 {
+    DEFAULT_MAX_RETRIES
 }
 
 #[derive(Deserialize)]
@@ -42,6 +57,12 @@ pub struct InitialConfig {
     api_url: String,
     port: u16,
     pin: String,
+    #[serde(default = "default_verify_tls")]
+    verify_tls: bool,
+    #[serde(default)]
+    ca_cert_path: Option<String>,
+    #[serde(default = "default_max_retries")]
+    max_retries: u32,
 }
 
 #[tauri::command]
@@ -49,7 +70,7 @@ pub async fn save_initial_config(
     config: InitialConfig,
     database: State<'_, Database>,
     pin_cache: State<'_, PinCache>,
-) -> Result<(), String> {
+) -> Result<(), ApiError> {
     info!("Starting save_initial_config");
 
     info!("Hashing password");
@@ -75,6 +96,11 @@ pub async fn save_initial_config(
         api_url: config.api_url,
         port: config.port,
         is_default: true,
+        verify_tls: config.verify_tls,
+        ca_cert_path: config.ca_cert_path,
+        max_retries: config.max_retries,
+        timeouts: HashMap::new(),
+        extra_headers: HashMap::new(),
     };
 
     info!("Saving API info");
@@ -85,6 +111,14 @@ pub async fn save_initial_config(
 
     info!("API info saved successfully");
 
+    // Unlocking runs the encryption migration, so the credentials are not left
+    // in plaintext until the next PIN entry
+    info!("Encrypting API credentials");
+    database.verify_pin(&config.pin).map_err(|e| {
+        error!("Failed to encrypt API credentials: {}", e);
+        format!("Failed to encrypt API credentials: {}", e)
+    })?;
+
     info!("Setting has_run flag");
     database.set_has_run().map_err(|e| {
         error!("Failed to set has_run flag: {}", e);
@@ -97,18 +131,21 @@ pub async fn save_initial_config(
 }
 
 #[tauri::command]
-pub fn get_api_info(database: State<Database>) -> Result<Option<ApiInfo>, String> {
-    database.get_default_api_info().map_err(|e| e.to_string())
+pub fn get_api_info(database: State<Database>) -> Result<Option<ApiInfo>, ApiError> {
+    database
+        .get_default_api_info()
+        .map_err(|e| ApiError::Other(e.to_string()))
 }
 
 #[tauri::command]
-pub fn get_api_profiles(database: State<Database>) -> Result<Vec<ApiInfo>, String> {
+pub fn get_api_profiles(database: State<Database>) -> Result<Vec<ApiInfo>, ApiError> {
     database
         .list_api_profiles()
-        .map_err(|e| format!("Failed to get API profiles: {}", e))
+        .map_err(|e| ApiError::Other(format!("Failed to get API profiles: {}", e)))
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn update_api_info(
     profile_name: String,
     api_key: String,
@@ -116,32 +153,79 @@ pub fn update_api_info(
     api_url: String,
     port: u16,
     is_default: bool,
+    verify_tls: Option<bool>,
+    ca_cert_path: Option<String>,
+    max_retries: Option<u32>,
     database: State<Database>,
-) -> Result<(), String> {
+) -> Result<(), ApiError> {
+    update_profile(
+        &database,
+        &profile_name,
+        ProfileUpdate {
+            api_key,
+            api_secret,
+            api_url,
+            port,
+            is_default,
+            verify_tls,
+            ca_cert_path,
+            max_retries,
+        },
+    )
+}
+
+struct ProfileUpdate {
+    api_key: String,
+    api_secret: String,
+    api_url: String,
+    port: u16,
+    is_default: bool,
+    verify_tls: Option<bool>,
+    ca_cert_path: Option<String>,
+    max_retries: Option<u32>,
+}
+
+fn update_profile(
+    database: &Database,
+    profile_name: &str,
+    update: ProfileUpdate,
+) -> Result<(), ApiError> {
+    // Credentials read while locked are blank, and saving them would
+    // overwrite the stored ones. A cloned profile has blank credentials
+    // even when unlocked, so they're not a sign of being locked.
+    if database.is_locked() {
+        return Err(
+            "PIN authentication required. Please login again before editing profiles."
+                .to_string()
+                .into(),
+        );
+    }
+
     let result = database
-        .get_api_info(Some(&profile_name))
+        .get_api_info(Some(profile_name))
         .map_err(|e| e.to_string())?;
 
     if result.is_none() {
-        return Err(format!("API profile '{}' not found", profile_name));
+        return Err(format!("API profile '{}' not found", profile_name).into());
     }
 
     // Get the existing profile info
     let mut api_info = result.unwrap();
-
-    // Check if credentials are empty (which happens when PIN cache is expired)
-    if api_info.api_key.is_empty() && api_info.api_secret.is_empty() {
-        return Err(
-            "PIN authentication required. Please login again before editing profiles.".to_string(),
-        );
-    }
+    let previous = api_info.clone();
 
     // Update the profile info
-    api_info.api_key = api_key;
-    api_info.api_secret = api_secret;
-    api_info.api_url = api_url;
-    api_info.port = port;
-    api_info.is_default = is_default;
+    api_info.api_key = update.api_key;
+    api_info.api_secret = update.api_secret;
+    api_info.api_url = update.api_url;
+    api_info.port = update.port;
+    api_info.is_default = update.is_default;
+    if let Some(verify_tls) = update.verify_tls {
+        api_info.verify_tls = verify_tls;
+        api_info.ca_cert_path = update.ca_cert_path.filter(|path| !path.is_empty());
+    }
+    if let Some(max_retries) = update.max_retries {
+        api_info.max_retries = max_retries;
+    }
 
     // Save the updated profile
     database.save_api_info(&api_info).map_err(|e| {
@@ -152,10 +236,12 @@ pub fn update_api_info(
         }
     })?;
 
+    resave_or_restore(database, &api_info, Some(&previous))?;
+
     // Update default status if needed
-    if is_default {
+    if update.is_default {
         database
-            .set_default_profile(&profile_name)
+            .set_default_profile(profile_name)
             .map_err(|e| format!("Failed to set default profile: {}", e))?;
     }
 
@@ -163,6 +249,41 @@ pub fn update_api_info(
     Ok(())
 }
 
+// Saving replaces the profile row, so its headers and timeouts are stored
+// again afterwards. If that fails the previous row is put back, or a new
+// profile removed, rather than leaving a profile without its settings.
+fn resave_or_restore(
+    database: &Database,
+    api_info: &ApiInfo,
+    previous: Option<&ApiInfo>,
+) -> Result<(), ApiError> {
+    let error = match database.resave_profile_settings(api_info) {
+        Ok(()) => return Ok(()),
+        Err(e) => e,
+    };
+
+    let restored = match previous {
+        Some(previous) => database
+            .save_api_info(previous)
+            .map_err(|e| e.to_string())
+            .and_then(|_| database.resave_profile_settings(previous)),
+        None => database
+            .delete_api_profile(&api_info.profile_name)
+            .map_err(|e| e.to_string()),
+    };
+    if let Err(e) = restored {
+        error!(
+            "Failed to roll back profile '{}': {}",
+            api_info.profile_name, e
+        );
+    }
+
+    Err(ApiError::Other(format!(
+        "Failed to save settings for profile '{}': {}",
+        api_info.profile_name, error
+    )))
+}
+
 #[tauri::command]
 pub fn update_pin(
     current_pin: String,
@@ -170,9 +291,9 @@ pub fn update_pin(
     confirm_new_pin: String,
     database: State<Database>,
     pin_cache: State<PinCache>,
-) -> Result<(), String> {
+) -> Result<(), ApiError> {
     if new_pin != confirm_new_pin {
-        return Err("New PIN and confirmation do not match".to_string());
+        return Err("New PIN and confirmation do not match".to_string().into());
     }
 
     log::info!("Updating PIN in database and re-encrypting API keys");
@@ -193,14 +314,33 @@ pub struct NewApiProfile {
     api_secret: String,
     api_url: String,
     port: u16,
+    #[serde(default = "default_verify_tls")]
+    verify_tls: bool,
+    #[serde(default)]
+    ca_cert_path: Option<String>,
+    #[serde(default = "default_max_retries")]
+    max_retries: u32,
+    #[serde(default)]
+    extra_headers: HashMap<String, String>,
+}
+
+fn validate_extra_headers(headers: &HashMap<String, String>) -> Result<(), String> {
+    headers
+        .iter()
+        .try_for_each(|(name, value)| parse_extra_header(name, value).map(|_| ()))
 }
 
 #[tauri::command]
 pub async fn add_api_profile(
     profile: NewApiProfile,
     database: State<'_, Database>,
-) -> Result<(), String> {
+) -> Result<(), ApiError> {
+    save_new_profile(&database, profile)
+}
+
+fn save_new_profile(database: &Database, profile: NewApiProfile) -> Result<(), ApiError> {
     info!("Starting add_api_profile");
+    validate_extra_headers(&profile.extra_headers)?;
 
     let api_info = ApiInfo {
         id: 0,
@@ -210,6 +350,11 @@ pub async fn add_api_profile(
         api_url: profile.api_url,
         port: profile.port,
         is_default: false,
+        verify_tls: profile.verify_tls,
+        ca_cert_path: profile.ca_cert_path,
+        max_retries: profile.max_retries,
+        timeouts: HashMap::new(),
+        extra_headers: profile.extra_headers,
     };
 
     info!("Saving new API profile");
@@ -218,12 +363,355 @@ pub async fn add_api_profile(
         format!("Failed to save API profile: {}", e)
     })?;
 
+    if !api_info.extra_headers.is_empty() {
+        let saved = database
+            .get_api_info(Some(&api_info.profile_name))
+            .map_err(|e| format!("Failed to load saved profile: {}", e))?
+            .ok_or_else(|| "Saved profile not found".to_string())?;
+        database.save_profile_headers(saved.id, &api_info.extra_headers)?;
+    }
+
     info!("New API profile saved successfully");
     Ok(())
 }
 
+/// Copies a profile's connection settings into a new profile under
+/// `new_name`. The key and secret are left blank to be filled in by
+/// editing the clone, and custom headers aren't copied since they often
+/// carry credentials too.
+#[tauri::command]
+pub async fn clone_api_profile(
+    source_id: i64,
+    new_name: String,
+    database: State<'_, Database>,
+) -> Result<(), ApiError> {
+    clone_profile(&database, source_id, new_name)
+}
+
+fn clone_profile(database: &Database, source_id: i64, new_name: String) -> Result<(), ApiError> {
+    let new_name = new_name.trim().to_string();
+    if new_name.is_empty() {
+        return Err("Profile name cannot be empty".to_string().into());
+    }
+
+    let profiles = database
+        .list_api_profiles()
+        .map_err(|e| format!("Failed to get API profiles: {}", e))?;
+
+    // Saving under an existing name would overwrite that profile
+    if profiles
+        .iter()
+        .any(|profile| profile.profile_name == new_name)
+    {
+        return Err(format!("API profile '{}' already exists", new_name).into());
+    }
+
+    let source = profiles
+        .into_iter()
+        .find(|profile| profile.id == source_id)
+        .ok_or_else(|| format!("API profile {} not found", source_id))?;
+
+    save_new_profile(
+        database,
+        NewApiProfile {
+            profile_name: new_name,
+            api_key: String::new(),
+            api_secret: String::new(),
+            api_url: source.api_url,
+            port: source.port,
+            verify_tls: source.verify_tls,
+            ca_cert_path: source.ca_cert_path,
+            max_retries: source.max_retries,
+            extra_headers: HashMap::new(),
+        },
+    )
+}
+
+const EXPORT_FORMAT_VERSION: u32 = 1;
+
+// Written in place of the secret when it isn't exported
+const SECRET_PLACEHOLDER: &str = "REPLACE_WITH_API_SECRET";
+
+#[derive(Serialize, Deserialize)]
+struct EncryptedValue {
+    ciphertext: String,
+    nonce: String,
+}
+
+/// Secrets and header values are encrypted with a key derived from the PIN
+/// and this salt; the PIN is needed again to import them
+#[derive(Serialize, Deserialize)]
+struct ExportEncryption {
+    salt: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExportedProfile {
+    profile_name: String,
+    api_url: String,
+    port: u16,
+    verify_tls: bool,
+    #[serde(default)]
+    ca_cert_path: Option<String>,
+    #[serde(default = "default_max_retries")]
+    max_retries: u32,
+    #[serde(default)]
+    timeouts: HashMap<String, u64>,
+    api_key: String,
+    /// Plaintext secret or the placeholder, when not encrypted
+    #[serde(default)]
+    api_secret: Option<String>,
+    #[serde(default)]
+    encrypted_secret: Option<EncryptedValue>,
+    /// JSON object of the custom headers, only exported with secrets
+    #[serde(default)]
+    encrypted_headers: Option<EncryptedValue>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProfileExport {
+    version: u32,
+    exported_at: String,
+    #[serde(default)]
+    encryption: Option<ExportEncryption>,
+    profiles: Vec<ExportedProfile>,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct ProfileImportResult {
+    imported: Vec<String>,
+    /// Profiles whose name already exists are left untouched
+    skipped: Vec<String>,
+    /// Imported without a secret; it has to be entered before connecting
+    needs_secret: Vec<String>,
+}
+
+fn seal(database: &Database, key: &[u8], plaintext: &str) -> Result<EncryptedValue, ApiError> {
+    let (ciphertext, nonce) = database.encrypt_string(plaintext, key)?;
+    Ok(EncryptedValue {
+        ciphertext: general_purpose::STANDARD.encode(ciphertext),
+        nonce: general_purpose::STANDARD.encode(nonce),
+    })
+}
+
+fn unseal(database: &Database, key: &[u8], value: &EncryptedValue) -> Result<String, String> {
+    let decode = |data: &str| {
+        general_purpose::STANDARD
+            .decode(data)
+            .map_err(|e| format!("Invalid encrypted value: {}", e))
+    };
+    database
+        .decrypt_string(&decode(&value.ciphertext)?, &decode(&value.nonce)?, key)
+        .map_err(|_| {
+            "Failed to decrypt the export. Check the PIN it was exported with.".to_string()
+        })
+}
+
+/// Writes every profile to a JSON file. With `include_secrets` the secrets
+/// and custom headers are encrypted with the current PIN; without, the
+/// secret is a placeholder and custom headers are left out.
 #[tauri::command]
-pub fn delete_api_profile(profile_name: String, database: State<Database>) -> Result<(), String> {
+pub fn export_profiles(
+    path: String,
+    include_secrets: bool,
+    database: State<Database>,
+    pin_cache: State<PinCache>,
+) -> Result<usize, ApiError> {
+    let pin = if include_secrets {
+        Some(
+            pin_cache
+                .get_pin()
+                .ok_or_else(|| "PIN authentication required. Please login again.".to_string())?,
+        )
+    } else {
+        None
+    };
+
+    write_profile_export(&database, &path, pin.as_deref())
+}
+
+// Secrets are encrypted with `pin` when given and left out otherwise
+fn write_profile_export(
+    database: &Database,
+    path: &str,
+    pin: Option<&str>,
+) -> Result<usize, ApiError> {
+    // Every profile would be exported with blank credentials
+    if database.is_locked() {
+        return Err("PIN authentication required. Please login again."
+            .to_string()
+            .into());
+    }
+
+    let profiles = database
+        .list_api_profiles()
+        .map_err(|e| format!("Failed to list profiles: {}", e))?;
+
+    let encryption = match pin {
+        Some(pin) => {
+            let salt = SaltString::generate(&mut OsRng).to_string();
+            let key = database.derive_encryption_key(pin, &salt)?;
+            Some((ExportEncryption { salt }, key))
+        }
+        None => None,
+    };
+
+    let mut exported = Vec::with_capacity(profiles.len());
+    for profile in profiles {
+        let api_info = database
+            .get_api_info(Some(&profile.profile_name))
+            .map_err(|e| format!("Failed to load profile '{}': {}", profile.profile_name, e))?
+            .ok_or_else(|| format!("Profile '{}' not found", profile.profile_name))?;
+
+        let (api_secret, encrypted_secret, encrypted_headers) = match &encryption {
+            Some((_, key)) => {
+                let headers = if api_info.extra_headers.is_empty() {
+                    None
+                } else {
+                    let json = serde_json::to_string(&api_info.extra_headers)
+                        .map_err(|e| format!("Failed to serialize headers: {}", e))?;
+                    Some(seal(database, key, &json)?)
+                };
+                (
+                    None,
+                    Some(seal(database, key, &api_info.api_secret)?),
+                    headers,
+                )
+            }
+            None => (Some(SECRET_PLACEHOLDER.to_string()), None, None),
+        };
+
+        exported.push(ExportedProfile {
+            profile_name: api_info.profile_name,
+            api_url: api_info.api_url,
+            port: api_info.port,
+            verify_tls: api_info.verify_tls,
+            ca_cert_path: api_info.ca_cert_path,
+            max_retries: api_info.max_retries,
+            timeouts: api_info.timeouts,
+            api_key: api_info.api_key,
+            api_secret,
+            encrypted_secret,
+            encrypted_headers,
+        });
+    }
+
+    let count = exported.len();
+    let export = ProfileExport {
+        version: EXPORT_FORMAT_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        encryption: encryption.map(|(encryption, _)| encryption),
+        profiles: exported,
+    };
+
+    let json = serde_json::to_string_pretty(&export)
+        .map_err(|e| format!("Failed to serialize profiles: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+
+    info!("Exported {} profiles to {}", count, path);
+    Ok(count)
+}
+
+/// Adds the profiles from an export file, skipping names that already
+/// exist. `pin` is the PIN the file was exported with, needed only when
+/// it contains encrypted secrets.
+#[tauri::command]
+pub fn import_profiles(
+    path: String,
+    pin: Option<String>,
+    database: State<Database>,
+) -> Result<ProfileImportResult, ApiError> {
+    let json =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let export: ProfileExport =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid profile export: {}", e))?;
+
+    if export.version > EXPORT_FORMAT_VERSION {
+        return Err(format!(
+            "This export was made by a newer version of the app (format {})",
+            export.version
+        )
+        .into());
+    }
+
+    let key = match &export.encryption {
+        Some(encryption) => {
+            let pin = pin.filter(|pin| !pin.is_empty()).ok_or_else(|| {
+                "This export is encrypted. Enter the PIN it was exported with.".to_string()
+            })?;
+            Some(database.derive_encryption_key(&pin, &encryption.salt)?)
+        }
+        None => None,
+    };
+
+    let existing: Vec<String> = database
+        .list_api_profiles()
+        .map_err(|e| format!("Failed to list profiles: {}", e))?
+        .into_iter()
+        .map(|profile| profile.profile_name)
+        .collect();
+
+    let mut result = ProfileImportResult::default();
+    for profile in export.profiles {
+        if existing.contains(&profile.profile_name) {
+            result.skipped.push(profile.profile_name);
+            continue;
+        }
+
+        let api_secret = match (&profile.encrypted_secret, &key) {
+            (Some(secret), Some(key)) => unseal(&database, key, secret)?,
+            _ => profile
+                .api_secret
+                .filter(|secret| secret != SECRET_PLACEHOLDER)
+                .unwrap_or_default(),
+        };
+        let extra_headers = match (&profile.encrypted_headers, &key) {
+            (Some(headers), Some(key)) => {
+                serde_json::from_str(&unseal(&database, key, headers)?)
+                    .map_err(|e| format!("Invalid headers for '{}': {}", profile.profile_name, e))?
+            }
+            _ => HashMap::new(),
+        };
+        validate_extra_headers(&extra_headers)?;
+
+        if api_secret.is_empty() {
+            result.needs_secret.push(profile.profile_name.clone());
+        }
+
+        let api_info = ApiInfo {
+            id: 0,
+            profile_name: profile.profile_name,
+            api_key: profile.api_key,
+            api_secret,
+            api_url: profile.api_url,
+            port: profile.port,
+            is_default: false,
+            verify_tls: profile.verify_tls,
+            ca_cert_path: profile.ca_cert_path,
+            max_retries: profile.max_retries,
+            timeouts: profile.timeouts,
+            extra_headers,
+        };
+
+        database
+            .save_api_info(&api_info)
+            .map_err(|e| format!("Failed to save profile '{}': {}", api_info.profile_name, e))?;
+        resave_or_restore(&database, &api_info, None)?;
+
+        result.imported.push(api_info.profile_name);
+    }
+
+    info!(
+        "Imported {} profiles from {}, skipped {}",
+        result.imported.len(),
+        path,
+        result.skipped.len()
+    );
+    Ok(result)
+}
+
+#[tauri::command]
+pub fn delete_api_profile(profile_name: String, database: State<Database>) -> Result<(), ApiError> {
     info!("Starting delete_api_profile for profile: {}", profile_name);
 
     let profiles = database
@@ -231,7 +719,7 @@ pub fn delete_api_profile(profile_name: String, database: State<Database>) -> Re
         .map_err(|e| format!("Failed to list API profiles: {}", e))?;
 
     if profiles.len() == 1 {
-        return Err("Cannot delete the last profile".to_string());
+        return Err("Cannot delete the last profile".to_string().into());
     }
 
     let is_default = profiles
@@ -264,11 +752,14 @@ pub fn delete_api_profile(profile_name: String, database: State<Database>) -> Re
 }
 
 #[tauri::command]
-pub fn set_default_profile(profile_name: String, database: State<Database>) -> Result<(), String> {
+pub fn set_default_profile(
+    profile_name: String,
+    database: State<Database>,
+) -> Result<(), ApiError> {
     info!("Setting default profile: {}", profile_name);
     database.set_default_profile(&profile_name).map_err(|e| {
         error!("Failed to set default profile: {}", e);
-        format!("Failed to set default profile: {}", e)
+        ApiError::Other(format!("Failed to set default profile: {}", e))
     })
 }
 
@@ -278,7 +769,9 @@ pub async fn test_api_connection(
     api_secret: String,
     api_url: String,
     port: u16,
-) -> Result<bool, String> {
+    verify_tls: Option<bool>,
+    ca_cert_path: Option<String>,
+) -> Result<bool, ApiError> {
     info!("Testing API connection to {}:{}", api_url, port);
 
     if api_key.contains('+')
@@ -290,19 +783,31 @@ pub async fn test_api_connection(
     }
 
     if api_url.ends_with('/') {
-        return Err("Invalid URL format: URL should not end with a trailing slash".to_string());
+        return Err(
+            "Invalid URL format: URL should not end with a trailing slash"
+                .to_string()
+                .into(),
+        );
     }
 
     if api_url.contains('?') || api_url.matches('/').count() > 2 {
         return Err(
             "Invalid URL format: URL should be a base URL without paths or query parameters"
-                .to_string(),
+                .to_string()
+                .into(),
         );
     }
 
     let url = format!("{}:{}/api/diagnostics/system/systemTime", api_url, port);
     info!("Making connection test request to {}", url);
 
+    let settings = ClientSettings {
+        accept_invalid_certs: !verify_tls.unwrap_or_else(default_verify_tls),
+        ca_cert_path: ca_cert_path.filter(|path| !path.is_empty()),
+        extra_headers: Vec::new(),
+        profile_id: None,
+    };
+
     let response = make_http_request(
         "GET",
         &url,
@@ -311,6 +816,7 @@ pub async fn test_api_connection(
         Some(10),
         Some(&api_key),
         Some(&api_secret),
+        Some(&settings),
     )
     .await;
 
@@ -327,10 +833,7 @@ pub async fn test_api_connection(
                 }
                 Err(e) => {
                     error!("Connection succeeded but returned invalid data: {}", e);
-                    Err(format!(
-                        "Connection succeeded but returned invalid data: {}",
-                        e
-                    ))
+                    Err(format!("Connection succeeded but returned invalid data: {}", e).into())
                 }
             }
         }
@@ -341,10 +844,430 @@ pub async fn test_api_connection(
     }
 }
 
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointStatus {
+    Ok,
+    AuthFailed,
+    Forbidden,
+    Unreachable,
+    Error,
+}
+
+#[derive(Serialize, Debug)]
+pub struct EndpointCheck {
+    name: String,
+    endpoint: String,
+    status: EndpointStatus,
+    message: Option<String>,
+    /// Privilege to grant the API user when the endpoint is forbidden
+    required_privilege: Option<String>,
+}
+
+// (name, method, endpoint, privilege) for the endpoints the dashboard needs
+const CONNECTION_PROBES: &[(&str, &str, &str, &str)] = &[
+    (
+        "System status",
+        "GET",
+        "/api/core/system/status",
+        "System: Status",
+    ),
+    (
+        "Gateway status",
+        "GET",
+        "/api/routes/gateway/status",
+        "System: Gateways",
+    ),
+    (
+        "Services",
+        "POST",
+        "/api/core/service/search",
+        "Status: Services",
+    ),
+];
+
+/// Probes a few representative endpoints with the given credentials and
+/// reports, per endpoint, whether the request succeeded and why it failed.
+#[tauri::command]
+pub async fn test_connection(profile: NewApiProfile) -> Result<Vec<EndpointCheck>, ApiError> {
+    info!(
+        "Testing connection for profile '{}' at {}:{}",
+        profile.profile_name, profile.api_url, profile.port
+    );
+
+    validate_extra_headers(&profile.extra_headers)?;
+
+    let settings = ClientSettings {
+        accept_invalid_certs: !profile.verify_tls,
+        ca_cert_path: profile.ca_cert_path.clone().filter(|path| !path.is_empty()),
+        extra_headers: profile
+            .extra_headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect(),
+        profile_id: None,
+    };
+
+    let mut checks = Vec::with_capacity(CONNECTION_PROBES.len());
+    // Bad credentials or an unreachable host fail every probe the same way,
+    // so the remaining probes reuse the first such result
+    let mut fatal: Option<(EndpointStatus, String)> = None;
+
+    for (name, method, endpoint, privilege) in CONNECTION_PROBES {
+        let (status, message) = match &fatal {
+            Some((status, message)) => (*status, Some(message.clone())),
+            None => {
+                let url = format!("{}:{}{}", profile.api_url, profile.port, endpoint);
+                let payload = (*method == "POST").then(|| serde_json::json!({}));
+
+                let result = make_http_request(
+                    method,
+                    &url,
+                    payload,
+                    None,
+                    Some(10),
+                    Some(&profile.api_key),
+                    Some(&profile.api_secret),
+                    Some(&settings),
+                )
+                .await;
+
+                match result {
+                    Ok(response) => match response.json::<Value>().await {
+                        Ok(_) => (EndpointStatus::Ok, None),
+                        Err(e) => (
+                            EndpointStatus::Error,
+                            Some(format!("Invalid response: {}", e)),
+                        ),
+                    },
+                    Err(e) => {
+                        let status = match e {
+                            ApiError::Auth(_) => EndpointStatus::AuthFailed,
+                            ApiError::Forbidden(_) => EndpointStatus::Forbidden,
+                            ApiError::Timeout(_) | ApiError::Connection(_) | ApiError::Tls(_) => {
+                                EndpointStatus::Unreachable
+                            }
+                            _ => EndpointStatus::Error,
+                        };
+
+                        if matches!(
+                            status,
+                            EndpointStatus::AuthFailed | EndpointStatus::Unreachable
+                        ) {
+                            fatal = Some((status, e.to_string()));
+                        }
+
+                        (status, Some(e.to_string()))
+                    }
+                }
+            }
+        };
+
+        info!("Connection probe {}: {:?}", endpoint, status);
+
+        checks.push(EndpointCheck {
+            name: name.to_string(),
+            endpoint: endpoint.to_string(),
+            required_privilege: (status == EndpointStatus::Forbidden)
+                .then(|| privilege.to_string()),
+            status,
+            message,
+        });
+    }
+
+    Ok(checks)
+}
+
+// (privilege, method, endpoint) with one read-only endpoint per privilege
+// the app relies on
+const PRIVILEGE_PROBES: &[(&str, &str, &str)] = &[
+    ("System: Status", "GET", "/api/core/system/status"),
+    ("System: Gateways", "GET", "/api/routes/gateway/status"),
+    ("Status: Services", "POST", "/api/core/service/search"),
+    ("System: Firmware", "GET", "/api/core/firmware/status"),
+    ("System: Routes", "POST", "/api/routes/routes/searchroute"),
+    (
+        "System: Configuration: Backups",
+        "GET",
+        "/api/core/backup/backups/this",
+    ),
+    (
+        "System: Configuration: Snapshots",
+        "POST",
+        "/api/core/snapshots/search",
+    ),
+    ("System: User Manager", "POST", "/api/auth/user/search/"),
+    (
+        "Firewall: Alias: Edit",
+        "POST",
+        "/api/firewall/alias/searchItem",
+    ),
+    (
+        "Firewall: Automation: Filter",
+        "POST",
+        "/api/firewall/filter/search_rule",
+    ),
+    (
+        "Firewall: NAT: Port Forward",
+        "POST",
+        "/api/firewall/d_nat/search_rule",
+    ),
+    (
+        "Diagnostics: Logs: Firewall: Live View",
+        "GET",
+        "/api/diagnostics/firewall/log_filters",
+    ),
+    (
+        "Diagnostics: ARP Table",
+        "POST",
+        "/api/diagnostics/interface/search_arp/",
+    ),
+    (
+        "Diagnostics: System Activity",
+        "GET",
+        "/api/diagnostics/activity/getActivity",
+    ),
+    (
+        "Status: DHCP leases",
+        "POST",
+        "/api/dhcpv4/leases/searchLease/",
+    ),
+];
+
+/// Privileges granted to the current API key. OPNsense has no endpoint that
+/// lists the caller's own privileges, so each one is checked by calling an
+/// endpoint it guards: 403 means missing, and endpoints of plugins or
+/// services that aren't installed are left out.
+#[tauri::command]
+pub async fn get_current_privileges(
+    database: State<'_, Database>,
+) -> Result<Vec<String>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    current_privileges(&api_info).await
+}
+
+pub(crate) async fn current_privileges(api_info: &ApiInfo) -> Result<Vec<String>, ApiError> {
+    let mut granted = Vec::new();
+
+    for (privilege, method, endpoint) in PRIVILEGE_PROBES {
+        let url = format!("{}:{}{}", api_info.api_url, api_info.port, endpoint);
+        let payload = (*method == "POST").then(|| serde_json::json!({}));
+
+        let result = make_http_request(
+            method,
+            &url,
+            payload,
+            None,
+            Some(10),
+            Some(&api_info.api_key),
+            Some(&api_info.api_secret),
+            Some(&api_info.client_settings()),
+        )
+        .await;
+
+        match result {
+            Ok(_) => granted.push(privilege.to_string()),
+            Err(ApiError::Forbidden(_)) | Err(ApiError::NotFound(_)) => {}
+            // Nothing else can be checked with bad credentials or no connection
+            Err(
+                e @ (ApiError::Auth(_)
+                | ApiError::Timeout(_)
+                | ApiError::Connection(_)
+                | ApiError::Tls(_)),
+            ) => {
+                return Err(e);
+            }
+            // Any other failure happened past the privilege check
+            Err(e) => {
+                info!("Privilege probe {} failed: {}", endpoint, e);
+                granted.push(privilege.to_string());
+            }
+        }
+    }
+
+    Ok(granted)
+}
+
+#[derive(Serialize, Debug)]
+pub struct DetectedEndpoint {
+    api_url: String,
+    port: u16,
+}
+
+/// Splits user input such as "fw.lan", "https://fw.lan:8443/api/" or
+/// "10.0.0.1:4443" into an optional scheme, the host and an optional port
+fn parse_host_input(input: &str) -> Result<(Option<String>, String, Option<u16>), ApiError> {
+    let input = input.trim();
+    let has_scheme = input.contains("://");
+    let with_scheme = if has_scheme {
+        input.to_string()
+    } else {
+        format!("https://{}", input)
+    };
+
+    let url =
+        reqwest::Url::parse(&with_scheme).map_err(|e| format!("Invalid host {}: {}", input, e))?;
+    let host = url
+        .host_str()
+        .filter(|host| !host.is_empty())
+        .ok_or_else(|| format!("Invalid host: {}", input))?;
+
+    Ok((
+        has_scheme.then(|| url.scheme().to_string()),
+        host.to_string(),
+        url.port(),
+    ))
+}
+
+/// Finds the scheme and port the firewall API answers on, trying the given
+/// port first and then the HTTPS default. Any reply from the API counts,
+/// including 401 since no credentials are sent.
+#[tauri::command]
+pub async fn detect_api_endpoint(
+    host: String,
+    port: Option<u16>,
+    verify_tls: Option<bool>,
+) -> Result<DetectedEndpoint, ApiError> {
+    let (scheme, host, url_port) = parse_host_input(&host)?;
+    let port = port.or(url_port);
+
+    let mut candidates: Vec<(&str, u16)> = Vec::new();
+    if let Some(port) = port {
+        match scheme.as_deref() {
+            Some("http") => candidates.push(("http", port)),
+            _ => candidates.extend([("https", port), ("http", port)]),
+        }
+    }
+    candidates.push(("https", 443));
+
+    // Only reachability matters here, and a fresh install still has its
+    // self-signed certificate, so verification is opt-in
+    let settings = ClientSettings {
+        accept_invalid_certs: !verify_tls.unwrap_or(false),
+        ca_cert_path: None,
+        extra_headers: Vec::new(),
+        profile_id: None,
+    };
+
+    let mut last_error = String::new();
+    for (scheme, port) in candidates {
+        let api_url = format!("{}://{}", scheme, host);
+        let url = format!("{}:{}/api/core/system/status", api_url, port);
+        info!("Probing API endpoint {}", url);
+
+        match make_http_request("GET", &url, None, None, Some(5), None, None, Some(&settings)).await {
+            Ok(_) | Err(ApiError::Auth(_)) | Err(ApiError::Forbidden(_)) => {
+                info!("Detected API endpoint {}:{}", api_url, port);
+                return Ok(DetectedEndpoint { api_url, port });
+            }
+            Err(e) => last_error = format!("{}:{}: {}", api_url, port, e),
+        }
+    }
+
+    Err(format!("No API endpoint found for {} ({})", host, last_error).into())
+}
+
+/// Custom headers of the default profile, e.g. an access proxy token
+#[tauri::command]
+pub fn get_profile_headers(database: State<Database>) -> Result<HashMap<String, String>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    Ok(api_info.extra_headers)
+}
+
+/// Replaces the default profile's custom headers
+#[tauri::command]
+pub fn save_profile_headers(
+    headers: HashMap<String, String>,
+    database: State<Database>,
+) -> Result<(), ApiError> {
+    validate_extra_headers(&headers)?;
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    Ok(database.save_profile_headers(api_info.id, &headers)?)
+}
+
+// Item kinds that can be pinned: filter and NAT rules by uuid, services by id
+const FAVORITE_KINDS: &[&str] = &["filter_rule", "nat_rule", "service"];
+
+fn validate_favorite(kind: &str, item_id: &str) -> Result<(), ApiError> {
+    if !FAVORITE_KINDS.contains(&kind) {
+        return Err(format!(
+            "Unknown favorite type '{}', expected one of: {}",
+            kind,
+            FAVORITE_KINDS.join(", ")
+        )
+        .into());
+    }
+    if item_id.trim().is_empty() {
+        return Err("Favorite item id cannot be empty".to_string().into());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_favorites(database: State<Database>) -> Result<Vec<db::Favorite>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    database
+        .get_favorites(api_info.id)
+        .map_err(|e| ApiError::Other(format!("Failed to get favorites: {}", e)))
+}
+
+#[tauri::command]
+pub fn add_favorite(
+    kind: String,
+    item_id: String,
+    label: Option<String>,
+    database: State<Database>,
+) -> Result<(), ApiError> {
+    validate_favorite(&kind, &item_id)?;
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let label = label.filter(|label| !label.trim().is_empty());
+    database
+        .add_favorite(api_info.id, &kind, item_id.trim(), label.as_deref())
+        .map_err(|e| ApiError::Other(format!("Failed to add favorite: {}", e)))
+}
+
+#[tauri::command]
+pub fn remove_favorite(
+    kind: String,
+    item_id: String,
+    database: State<Database>,
+) -> Result<bool, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    database
+        .remove_favorite(api_info.id, &kind, item_id.trim())
+        .map_err(|e| ApiError::Other(format!("Failed to remove favorite: {}", e)))
+}
+
 #[tauri::command]
 pub fn get_dashboard_preferences(
     database: State<Database>,
-) -> Result<HashMap<String, db::DashboardWidgetPref>, String> {
+) -> Result<HashMap<String, db::DashboardWidgetPref>, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -352,14 +1275,14 @@ pub fn get_dashboard_preferences(
 
     database
         .get_dashboard_preferences(api_info.id)
-        .map_err(|e| format!("Failed to get dashboard preferences: {}", e))
+        .map_err(|e| ApiError::Other(format!("Failed to get dashboard preferences: {}", e)))
 }
 
 #[tauri::command]
 pub fn save_dashboard_preferences(
     prefs: Vec<db::DashboardWidgetPref>,
     database: State<Database>,
-) -> Result<(), String> {
+) -> Result<(), ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -367,5 +1290,108 @@ pub fn save_dashboard_preferences(
 
     database
         .save_dashboard_preferences(api_info.id, &prefs)
-        .map_err(|e| format!("Failed to save dashboard preferences: {}", e))
+        .map_err(|e| ApiError::Other(format!("Failed to save dashboard preferences: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PIN: &str = "2468";
+
+    // A database set up like after first run, locked until `verify_pin`
+    fn locked_database() -> Database {
+        let database = Database::open_in_memory().unwrap();
+        database
+            .update_password_hash(&Database::hash_password(PIN).unwrap())
+            .unwrap();
+        database
+            .save_initial_api_info(&ApiInfo {
+                id: 0,
+                profile_name: "Office".to_string(),
+                api_key: "office-key".to_string(),
+                api_secret: "office-secret".to_string(),
+                api_url: "https://192.168.1.1".to_string(),
+                port: 443,
+                is_default: true,
+                verify_tls: true,
+                ca_cert_path: None,
+                max_retries: DEFAULT_MAX_RETRIES,
+                timeouts: HashMap::new(),
+                extra_headers: HashMap::new(),
+            })
+            .unwrap();
+        database
+    }
+
+    fn export_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("opnmanager-{}-{}.json", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn credentials(api_key: &str, api_secret: &str, source: &ApiInfo) -> ProfileUpdate {
+        ProfileUpdate {
+            api_key: api_key.to_string(),
+            api_secret: api_secret.to_string(),
+            api_url: source.api_url.clone(),
+            port: source.port,
+            is_default: false,
+            verify_tls: None,
+            ca_cert_path: None,
+            max_retries: None,
+        }
+    }
+
+    #[test]
+    fn cloned_profile_can_be_edited_and_exported() {
+        let database = locked_database();
+        assert!(database.verify_pin(PIN).unwrap());
+        let source = database.get_api_info(Some("Office")).unwrap().unwrap();
+
+        clone_profile(&database, source.id, "Office copy".to_string()).unwrap();
+        let clone = database.get_api_info(Some("Office copy")).unwrap().unwrap();
+        assert!(clone.api_key.is_empty() && clone.api_secret.is_empty());
+
+        // Blank credentials on the clone don't block exporting the others
+        let path = export_path("clone-export");
+        assert_eq!(write_profile_export(&database, &path, None).unwrap(), 2);
+
+        update_profile(
+            &database,
+            "Office copy",
+            credentials("copy-key", "copy-secret", &source),
+        )
+        .unwrap();
+
+        assert_eq!(write_profile_export(&database, &path, Some(PIN)).unwrap(), 2);
+        let export: ProfileExport =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let key = database
+            .derive_encryption_key(PIN, &export.encryption.unwrap().salt)
+            .unwrap();
+        let exported = export
+            .profiles
+            .iter()
+            .find(|profile| profile.profile_name == "Office copy")
+            .unwrap();
+        assert_eq!(exported.api_key, "copy-key");
+        assert_eq!(exported.api_url, source.api_url);
+        assert_eq!(
+            unseal(&database, &key, exported.encrypted_secret.as_ref().unwrap()).unwrap(),
+            "copy-secret"
+        );
+    }
+
+    #[test]
+    fn editing_and_exporting_require_the_pin() {
+        let database = locked_database();
+        let source = database.get_api_info(Some("Office")).unwrap().unwrap();
+
+        assert!(update_profile(&database, "Office", credentials("", "", &source)).is_err());
+        assert!(write_profile_export(&database, &export_path("locked-export"), None).is_err());
+    }
 }
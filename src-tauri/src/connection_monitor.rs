@@ -0,0 +1,134 @@
+use crate::db::Database;
+use crate::error::ApiError;
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+// Consecutive timeouts or connection errors before the firewall is
+// reported as unreachable
+const FAILURE_THRESHOLD: u32 = 3;
+
+// Set once during setup so requests can report without a handle of their own
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+#[derive(Default)]
+struct ProfileConnection {
+    consecutive_failures: u32,
+    lost: bool,
+    last_error: Option<String>,
+}
+
+/// Connectivity to each profile's firewall as seen by all its requests
+/// together, so the frontend can show one banner instead of an error per
+/// widget
+pub struct ConnectionMonitor {
+    profiles: Mutex<HashMap<i64, ProfileConnection>>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ConnectionState {
+    /// `None` when no profile is selected yet
+    profile_id: Option<i64>,
+    connected: bool,
+    consecutive_failures: u32,
+    message: Option<String>,
+}
+
+impl ConnectionMonitor {
+    pub fn new() -> Self {
+        Self {
+            profiles: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn state(&self, profile_id: Option<i64>) -> ConnectionState {
+        let profiles = self.profiles.lock().unwrap();
+        let connection = profile_id.and_then(|id| profiles.get(&id));
+
+        ConnectionState {
+            profile_id,
+            connected: !connection.is_some_and(|c| c.lost),
+            consecutive_failures: connection.map_or(0, |c| c.consecutive_failures),
+            message: connection
+                .filter(|c| c.lost)
+                .and_then(|c| c.last_error.clone()),
+        }
+    }
+}
+
+pub fn register_connection_monitor(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(ConnectionMonitor::new());
+    let _ = APP_HANDLE.set(app.handle().clone());
+    Ok(())
+}
+
+/// Called for every request made for a saved profile. Only timeouts and
+/// connection errors count as failures; any HTTP response, even an error
+/// status, means the firewall is reachable. TLS errors say nothing about
+/// reachability and leave the state unchanged.
+pub(crate) fn record_result<T>(profile_id: i64, result: &Result<T, ApiError>) {
+    let Some(app) = APP_HANDLE.get() else {
+        return;
+    };
+    let monitor = app.state::<ConnectionMonitor>();
+
+    let event = {
+        let mut profiles = monitor.profiles.lock().unwrap();
+        let connection = profiles.entry(profile_id).or_default();
+
+        match result {
+            Err(ApiError::Tls(_)) => None,
+            Err(e @ (ApiError::Timeout(_) | ApiError::Connection(_))) => {
+                connection.consecutive_failures += 1;
+                connection.last_error = Some(e.to_string());
+                if connection.consecutive_failures >= FAILURE_THRESHOLD && !connection.lost {
+                    connection.lost = true;
+                    warn!(
+                        "Firewall for profile {} unreachable after {} failed requests",
+                        profile_id, connection.consecutive_failures
+                    );
+                    Some("connection-lost")
+                } else {
+                    None
+                }
+            }
+            _ => {
+                let was_lost = connection.lost;
+                *connection = ProfileConnection::default();
+                if was_lost {
+                    info!("Firewall for profile {} reachable again", profile_id);
+                    Some("connection-restored")
+                } else {
+                    None
+                }
+            }
+        }
+    };
+
+    if let Some(event) = event {
+        let state = monitor.state(Some(profile_id));
+        if let Err(e) = app.emit(event, state) {
+            warn!("Failed to emit {} event: {}", event, e);
+        }
+    }
+}
+
+/// The connection state of a profile, or of the default profile when no id
+/// is given
+#[tauri::command]
+pub fn get_connection_state(
+    database: State<'_, Database>,
+    monitor: State<'_, ConnectionMonitor>,
+    profile_id: Option<i64>,
+) -> ConnectionState {
+    let profile_id = profile_id.or_else(|| {
+        database
+            .get_default_api_info()
+            .ok()
+            .flatten()
+            .map(|api_info| api_info.id)
+    });
+    monitor.state(profile_id)
+}
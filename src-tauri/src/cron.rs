@@ -0,0 +1,206 @@
+use crate::db::{ApiInfo, Database};
+use crate::error::ApiError;
+use crate::http_client::make_http_request;
+use crate::pending::{PendingChanges, Subsystem};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::State;
+
+#[derive(Serialize, Debug)]
+pub struct CronJobEntry {
+    uuid: String,
+    enabled: bool,
+    /// The five cron fields, e.g. "0 3 * * 1"
+    schedule: String,
+    /// What the job runs, as described by the firewall
+    command: String,
+    parameters: String,
+    description: String,
+    /// Set for jobs created by the system or a plugin rather than by hand
+    origin: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ToggleCronJobResponse {
+    result: String,
+    #[serde(default)]
+    changed: bool,
+}
+
+fn build_api_url(api_info: &ApiInfo, endpoint: &str) -> String {
+    format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
+}
+
+fn field(row: &Value, key: &str) -> String {
+    match &row[key] {
+        Value::String(s) => s.trim().to_string(),
+        Value::Number(n) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn parse_cron_job(row: &Value) -> CronJobEntry {
+    let schedule = ["minutes", "hours", "days", "months", "weekdays"]
+        .iter()
+        .map(|key| {
+            let value = field(row, key);
+            if value.is_empty() {
+                "*".to_string()
+            } else {
+                value
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    CronJobEntry {
+        uuid: field(row, "uuid"),
+        enabled: field(row, "enabled") == "1",
+        schedule,
+        command: field(row, "command"),
+        parameters: field(row, "parameters"),
+        description: field(row, "description"),
+        origin: Some(field(row, "origin")).filter(|origin| !origin.is_empty()),
+    }
+}
+
+#[tauri::command]
+pub async fn list_cron_jobs(database: State<'_, Database>) -> Result<Vec<CronJobEntry>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, "/api/cron/settings/searchJobs/");
+
+    let payload = json!({
+        "current": 1,
+        "rowCount": -1,
+        "sort": {},
+        "searchPhrase": ""
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let body = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse cron jobs: {}", e))?;
+
+    Ok(body["rows"]
+        .as_array()
+        .map(|rows| rows.iter().map(parse_cron_job).collect())
+        .unwrap_or_default())
+}
+
+// Only stages the change; call apply_cron_changes to activate it
+#[tauri::command]
+pub async fn toggle_cron_job(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+) -> Result<ToggleCronJobResponse, ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "toggle_cron_job",
+        &target,
+        toggle_cron_job_request(database.clone(), pending, uuid),
+    )
+    .await
+}
+
+async fn toggle_cron_job_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+) -> Result<ToggleCronJobResponse, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, &format!("/api/cron/settings/toggleJob/{}", uuid));
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(json!({})),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let result = response
+        .json::<ToggleCronJobResponse>()
+        .await
+        .map_err(|e| format!("Failed to parse toggle response: {}", e))?;
+
+    if result.changed {
+        pending.mark(api_info.id, Subsystem::Cron);
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn apply_cron_changes(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+) -> Result<Value, ApiError> {
+    crate::audit::audited(
+        &database,
+        "apply_cron_changes",
+        "",
+        apply_cron_changes_request(database.clone(), pending),
+    )
+    .await
+}
+
+async fn apply_cron_changes_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+) -> Result<Value, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    // Stays pending unless the reconfigure succeeds
+    pending.mark(api_info.id, Subsystem::Cron);
+
+    let url = build_api_url(&api_info, "/api/cron/service/reconfigure");
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(json!({})),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let result = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    pending.clear(api_info.id, Subsystem::Cron);
+    Ok(result)
+}
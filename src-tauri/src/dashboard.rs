@@ -1,23 +1,59 @@
-use crate::db::Database;
-use crate::http_client::make_http_request;
+use crate::db::{ApiInfo, Database};
+use crate::error::ApiError;
+use crate::http_client::{make_http_request, make_http_request_with_retry};
+use crate::response_cache::{request_key, ResponseCache};
+use crate::system_resources::{
+    fetch_system_disk, fetch_system_resources, SystemDisk, SystemResources,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tauri::State;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GatewayStatus {
-    items: Vec<GatewayItem>,
+    pub(crate) items: Vec<GatewayItem>,
     status: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GatewayItem {
-    name: String,
+    pub(crate) name: String,
     address: String,
-    status: String,
+    pub(crate) status: String,
     loss: String,
     delay: String,
     stddev: String,
-    status_translated: String,
+    pub(crate) status_translated: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GatewayMetrics {
+    name: String,
+    address: String,
+    status: String,
+    delay_ms: Option<f64>,
+    stddev_ms: Option<f64>,
+    loss_pct: Option<f64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GatewayGroupMember {
+    name: String,
+    /// Gateway priority; lower tiers are preferred
+    tier: u32,
+    status: String,
+    up: bool,
+    active: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GatewayGroup {
+    name: String,
+    ip_version: String,
+    members: Vec<GatewayGroupMember>,
+    active: Option<String>,
+    /// Why a lower priority member carries the traffic, if one does
+    reason: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -41,8 +77,22 @@ pub struct Service {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct RestartServiceResponse {
     result: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    warning: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ServiceRestartResult {
+    id: String,
+    name: String,
+    description: String,
+    success: bool,
+    error: Option<String>,
 }
 
+// Stopping these leaves the firewall hard to manage until they are started again
+const CRITICAL_SERVICES: &[&str] = &["configd", "openssh", "syslog-ng", "webgui"];
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SystemTime {
     uptime: String,
@@ -51,77 +101,444 @@ pub struct SystemTime {
     loadavg: String,
 }
 
-#[tauri::command]
-pub async fn get_gateway_status(database: State<'_, Database>) -> Result<GatewayStatus, String> {
-    let api_info = database
-        .get_default_api_info()
+/// Dashboard data loaded in one round trip. A section that failed to load is
+/// `None` and its error is listed under the section name in `errors`.
+#[derive(Serialize, Debug)]
+pub struct SystemHealth {
+    resources: Option<SystemResources>,
+    disk: Option<SystemDisk>,
+    gateways: Option<GatewayStatus>,
+    services: Option<ServicesResponse>,
+    errors: HashMap<String, String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ProfileGatewayStatus {
+    profile_id: i64,
+    profile_name: String,
+    status: Option<GatewayStatus>,
+    error: Option<String>,
+}
+
+fn profile_api_info(database: &Database, profile_id: Option<i64>) -> Result<ApiInfo, ApiError> {
+    database
+        .get_profile_api_info(profile_id)
         .map_err(|e| format!("Failed to get API info: {}", e))?
-        .ok_or_else(|| "API info not found".to_string())?;
+        .ok_or_else(|| ApiError::Other("API info not found".to_string()))
+}
+
+#[tauri::command]
+pub async fn get_gateway_status(
+    database: State<'_, Database>,
+    cache: State<'_, ResponseCache>,
+    profile_id: Option<i64>,
+    force: Option<bool>,
+) -> Result<GatewayStatus, ApiError> {
+    let api_info = profile_api_info(&database, profile_id)?;
+    cache
+        .get_or_fetch(
+            &format!("{}:gateway_status", api_info.id),
+            force.unwrap_or(false),
+            fetch_gateway_status(&api_info),
+        )
+        .await
+}
 
+pub(crate) async fn fetch_gateway_status(api_info: &ApiInfo) -> Result<GatewayStatus, ApiError> {
     let url = format!(
         "{}:{}/api/routes/gateway/status",
         api_info.api_url, api_info.port
     );
 
-    let response = make_http_request(
+    let response = make_http_request_with_retry(
         "GET",
         &url,
         None,
         None,
-        Some(30),
+        api_info.dashboard_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+        api_info.max_retries,
     )
     .await?;
 
     response
         .json::<GatewayStatus>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
+}
+
+fn health_section<T>(
+    errors: &mut HashMap<String, String>,
+    name: &str,
+    result: Result<T, ApiError>,
+) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(e) => {
+            errors.insert(name.to_string(), e.to_string());
+            None
+        }
+    }
 }
 
 #[tauri::command]
-pub async fn get_services(database: State<'_, Database>) -> Result<ServicesResponse, String> {
-    let api_info = database
-        .get_default_api_info()
-        .map_err(|e| format!("Failed to get API info: {}", e))?
-        .ok_or_else(|| "API info not found".to_string())?;
+pub async fn get_system_health(
+    database: State<'_, Database>,
+    cache: State<'_, ResponseCache>,
+    force: Option<bool>,
+) -> Result<SystemHealth, ApiError> {
+    let api_info = profile_api_info(&database, None)?;
+    let force = force.unwrap_or(false);
+    let resources_key = format!("{}:system_resources", api_info.id);
+    let gateways_key = format!("{}:gateway_status", api_info.id);
+    let services_key = format!("{}:services", api_info.id);
+
+    // join! rather than try_join! so one failing section does not discard the rest
+    let (resources, disk, gateways, services) = tokio::join!(
+        cache.get_or_fetch(&resources_key, force, fetch_system_resources(&api_info)),
+        fetch_system_disk(&api_info),
+        cache.get_or_fetch(&gateways_key, force, fetch_gateway_status(&api_info)),
+        cache.get_or_fetch(&services_key, force, fetch_services(&api_info)),
+    );
+
+    let mut errors = HashMap::new();
+
+    Ok(SystemHealth {
+        resources: health_section(&mut errors, "resources", resources),
+        disk: health_section(&mut errors, "disk", disk),
+        gateways: health_section(&mut errors, "gateways", gateways),
+        services: health_section(&mut errors, "services", services),
+        errors,
+    })
+}
+
+/// Queries the gateway status of every stored profile concurrently. A failing
+/// firewall is reported in its own entry instead of failing the whole batch.
+#[tauri::command]
+pub async fn get_all_profiles_gateway_status(
+    database: State<'_, Database>,
+) -> Result<Vec<ProfileGatewayStatus>, ApiError> {
+    let profiles = database
+        .list_api_profiles()
+        .map_err(|e| format!("Failed to list profiles: {}", e))?;
+
+    // The profile list omits credentials, so each profile is loaded in full
+    let handles: Vec<_> = profiles
+        .into_iter()
+        .map(|profile| {
+            let api_info = profile_api_info(&database, Some(profile.id));
+            tokio::spawn(async move {
+                let result = match api_info {
+                    Ok(api_info) => fetch_gateway_status(&api_info).await,
+                    Err(e) => Err(e),
+                };
+                (profile, result)
+            })
+        })
+        .collect();
+
+    let mut statuses = Vec::with_capacity(handles.len());
+    for handle in handles {
+        let (profile, result) = handle
+            .await
+            .map_err(|e| format!("Gateway status task failed: {}", e))?;
+
+        let (status, error) = match result {
+            Ok(status) => (Some(status), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+
+        statuses.push(ProfileGatewayStatus {
+            profile_id: profile.id,
+            profile_name: profile.profile_name,
+            status,
+            error,
+        });
+    }
+
+    Ok(statuses)
+}
+
+// dpinger values look like "12.3 ms" or "0.0 %", and "~" when unmonitored
+fn parse_metric(value: &str) -> Option<f64> {
+    value
+        .trim()
+        .trim_end_matches("ms")
+        .trim_end_matches('%')
+        .trim()
+        .parse::<f64>()
+        .ok()
+}
+
+/// Returns numeric delay, stddev and loss for the named gateway, or for every
+/// monitored gateway when the name is empty
+#[tauri::command]
+pub async fn get_gateway_metrics(
+    database: State<'_, Database>,
+    gateway_name: String,
+) -> Result<Vec<GatewayMetrics>, ApiError> {
+    let gateways = fetch_gateway_status(&profile_api_info(&database, None)?).await?;
+
+    let metrics: Vec<GatewayMetrics> = gateways
+        .items
+        .into_iter()
+        .filter(|gateway| gateway_name.is_empty() || gateway.name == gateway_name)
+        .map(|gateway| GatewayMetrics {
+            delay_ms: parse_metric(&gateway.delay),
+            stddev_ms: parse_metric(&gateway.stddev),
+            loss_pct: parse_metric(&gateway.loss),
+            name: gateway.name,
+            address: gateway.address,
+            status: gateway.status,
+        })
+        .collect();
+
+    if metrics.is_empty() && !gateway_name.is_empty() {
+        return Err(format!("Gateway '{}' not found", gateway_name).into());
+    }
+
+    Ok(metrics)
+}
 
+// (name, priority, ipprotocol) of each enabled gateway
+async fn fetch_gateway_config(api_info: &ApiInfo) -> Result<Vec<(String, u32, String)>, ApiError> {
     let url = format!(
-        "{}:{}/api/core/service/search",
+        "{}:{}/api/routing/settings/searchGateway",
         api_info.api_url, api_info.port
     );
 
     let response = make_http_request(
+        "POST",
+        &url,
+        Some(serde_json::json!({
+            "current": 1,
+            "rowCount": -1,
+            "sort": {},
+            "searchPhrase": ""
+        })),
+        None,
+        api_info.dashboard_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let body = response
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| format!("Failed to parse gateways: {}", e))?;
+
+    let field = |row: &serde_json::Value, key: &str| match &row[key] {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Number(n) => n.to_string(),
+        _ => String::new(),
+    };
+
+    Ok(body["rows"]
+        .as_array()
+        .map(|rows| {
+            rows.iter()
+                .filter(|row| field(row, "disabled") != "1")
+                .map(|row| {
+                    (
+                        field(row, "name"),
+                        field(row, "priority").parse().unwrap_or(255),
+                        field(row, "ipprotocol"),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+fn gateway_is_up(status: &str) -> bool {
+    !matches!(status, "down" | "force_down")
+}
+
+/// Failover order per address family, as used by default gateway switching.
+/// Gateway groups configured for policy routing are not exposed by the API,
+/// so each family is reported as one group ordered by gateway priority.
+#[tauri::command]
+pub async fn get_gateway_groups(
+    database: State<'_, Database>,
+    profile_id: Option<i64>,
+) -> Result<Vec<GatewayGroup>, ApiError> {
+    let api_info = profile_api_info(&database, profile_id)?;
+
+    let (config, status) = tokio::try_join!(
+        fetch_gateway_config(&api_info),
+        fetch_gateway_status(&api_info)
+    )?;
+
+    let statuses: HashMap<String, String> = status
+        .items
+        .into_iter()
+        .map(|item| (item.name, item.status))
+        .collect();
+
+    let mut groups = Vec::new();
+    for (family, name) in [("inet", "IPv4"), ("inet6", "IPv6")] {
+        let mut members: Vec<GatewayGroupMember> = config
+            .iter()
+            .filter(|(_, _, protocol)| protocol == family)
+            .map(|(gateway, priority, _)| {
+                let status = statuses.get(gateway).cloned().unwrap_or_default();
+                GatewayGroupMember {
+                    name: gateway.clone(),
+                    tier: *priority,
+                    up: gateway_is_up(&status),
+                    status,
+                    active: false,
+                }
+            })
+            .collect();
+
+        if members.is_empty() {
+            continue;
+        }
+        members.sort_by(|a, b| a.tier.cmp(&b.tier).then_with(|| a.name.cmp(&b.name)));
+
+        let active = members.iter().position(|member| member.up);
+        if let Some(index) = active {
+            members[index].active = true;
+        }
+
+        // Every member ahead of the active one is down
+        let reason = active.filter(|index| *index > 0).map(|index| {
+            let down = members[..index]
+                .iter()
+                .map(|member| member.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{} is active because {} is down", members[index].name, down)
+        });
+
+        groups.push(GatewayGroup {
+            name: format!("{} default", name),
+            ip_version: if family == "inet" { "ipv4" } else { "ipv6" }.to_string(),
+            active: active.map(|index| members[index].name.clone()),
+            reason,
+            members,
+        });
+    }
+
+    Ok(groups)
+}
+
+/// `search` matches the service name or description case-insensitively.
+/// `running_only` keeps only running services when true and only stopped
+/// ones when false. With neither set every service is returned.
+#[tauri::command]
+pub async fn get_services(
+    database: State<'_, Database>,
+    cache: State<'_, ResponseCache>,
+    profile_id: Option<i64>,
+    force: Option<bool>,
+    search: Option<String>,
+    running_only: Option<bool>,
+) -> Result<ServicesResponse, ApiError> {
+    let api_info = profile_api_info(&database, profile_id)?;
+    let mut services = cache
+        .get_or_fetch(
+            &format!("{}:services", api_info.id),
+            force.unwrap_or(false),
+            fetch_services(&api_info),
+        )
+        .await?;
+
+    // Filtered here rather than by the firewall so the cached list serves
+    // every combination
+    let search = search
+        .map(|search| search.trim().to_lowercase())
+        .filter(|search| !search.is_empty());
+    if search.is_some() || running_only.is_some() {
+        services.rows.retain(|service| {
+            let matches_search = search.as_ref().is_none_or(|search| {
+                service.name.to_lowercase().contains(search)
+                    || service.description.to_lowercase().contains(search)
+            });
+            let matches_state =
+                running_only.is_none_or(|running| (service.running != 0) == running);
+            matches_search && matches_state
+        });
+        services.total = services.rows.len() as u32;
+        services.row_count = services.rows.len() as u32;
+    }
+
+    Ok(services)
+}
+
+async fn fetch_services(api_info: &ApiInfo) -> Result<ServicesResponse, ApiError> {
+    let url = format!(
+        "{}:{}/api/core/service/search",
+        api_info.api_url, api_info.port
+    );
+
+    let response = make_http_request_with_retry(
         "GET",
         &url,
         None,
         None,
-        Some(30),
+        api_info.dashboard_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+        api_info.max_retries,
     )
     .await?;
 
     response
         .json::<ServicesResponse>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
 
-#[tauri::command]
-pub async fn restart_service(
+async fn control_service(
     database: State<'_, Database>,
-    service_id: String,
-) -> Result<RestartServiceResponse, String> {
+    cache: State<'_, ResponseCache>,
+    action: &str,
+    service_id: &str,
+) -> Result<RestartServiceResponse, ApiError> {
+    crate::audit::audited(
+        &database,
+        &format!("{}_service", action),
+        service_id,
+        control_service_request(database.clone(), cache, action, service_id),
+    )
+    .await
+}
+
+async fn control_service_request(
+    database: State<'_, Database>,
+    cache: State<'_, ResponseCache>,
+    action: &str,
+    service_id: &str,
+) -> Result<RestartServiceResponse, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
         .ok_or_else(|| "API info not found".to_string())?;
 
+    let result = send_service_action(&api_info, action, service_id).await;
+
+    // Even a failed action may have changed the service state, so the next
+    // get_services always reads it fresh
+    cache.remove(&format!("{}:services", api_info.id));
+
+    result
+}
+
+async fn send_service_action(
+    api_info: &ApiInfo,
+    action: &str,
+    service_id: &str,
+) -> Result<RestartServiceResponse, ApiError> {
     let url = format!(
-        "{}:{}/api/core/service/restart/{}",
-        api_info.api_url, api_info.port, service_id
+        "{}:{}/api/core/service/{}/{}",
+        api_info.api_url, api_info.port, action, service_id
     );
 
     let response = make_http_request(
@@ -129,43 +546,197 @@ pub async fn restart_service(
         &url,
         Some(serde_json::json!({})),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<RestartServiceResponse>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
+}
+
+#[tauri::command]
+pub async fn restart_service(
+    database: State<'_, Database>,
+    cache: State<'_, ResponseCache>,
+    service_id: String,
+) -> Result<RestartServiceResponse, ApiError> {
+    control_service(database, cache, "restart", &service_id).await
 }
 
+/// Restarts every service that is stopped but expected to run. The firewall
+/// only lists enabled services, and ones last stopped from this app are
+/// treated as intentionally stopped and left alone.
 #[tauri::command]
-pub async fn get_system_time(database: State<'_, Database>) -> Result<SystemTime, String> {
+pub async fn restart_stopped_services(
+    database: State<'_, Database>,
+    cache: State<'_, ResponseCache>,
+) -> Result<Vec<ServiceRestartResult>, ApiError> {
+    let api_info = profile_api_info(&database, None)?;
+
+    let intentionally_stopped = database
+        .get_stopped_services(api_info.id)
+        .map_err(|e| format!("Failed to read stopped services: {}", e))?;
+
+    // Always fresh, a cached list could restart services that already recovered
+    let services = fetch_services(&api_info).await?;
+
+    let handles: Vec<_> = services
+        .rows
+        .into_iter()
+        .filter(|service| service.running == 0 && !intentionally_stopped.contains(&service.id))
+        .map(|service| {
+            let api_info = api_info.clone();
+            tokio::spawn(async move {
+                let result = send_service_action(&api_info, "restart", &service.id).await;
+                (service, result)
+            })
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(handles.len());
+    for handle in handles {
+        let (service, result) = handle
+            .await
+            .map_err(|e| format!("Service restart task failed: {}", e))?;
+
+        crate::audit::record(&database, "restart_service", &service.id, &result);
+
+        let error = match result {
+            Ok(response) if response.result == "ok" => None,
+            Ok(response) => Some(format!("Unexpected result: {}", response.result)),
+            Err(e) => Some(e.to_string()),
+        };
+
+        results.push(ServiceRestartResult {
+            id: service.id,
+            name: service.name,
+            description: service.description,
+            success: error.is_none(),
+            error,
+        });
+    }
+
+    if !results.is_empty() {
+        cache.clear();
+    }
+
+    Ok(results)
+}
+
+#[tauri::command]
+pub async fn start_service(
+    database: State<'_, Database>,
+    cache: State<'_, ResponseCache>,
+    service_id: String,
+) -> Result<RestartServiceResponse, ApiError> {
+    control_service(database, cache, "start", &service_id).await
+}
+
+#[tauri::command]
+pub async fn stop_service(
+    database: State<'_, Database>,
+    cache: State<'_, ResponseCache>,
+    service_id: String,
+) -> Result<RestartServiceResponse, ApiError> {
+    let mut response = control_service(database, cache, "stop", &service_id).await?;
+
+    let service_name = service_id.split('/').next().unwrap_or_default();
+    if CRITICAL_SERVICES.contains(&service_name) {
+        response.warning = Some(format!(
+            "{} is a critical service; the firewall may not respond to management requests until it is started again",
+            service_name
+        ));
+    }
+
+    Ok(response)
+}
+
+/// Restarts the dpinger monitor for a single gateway after checking that the
+/// gateway exists
+#[tauri::command]
+pub async fn reset_gateway_monitor(
+    database: State<'_, Database>,
+    cache: State<'_, ResponseCache>,
+    gateway_name: String,
+) -> Result<RestartServiceResponse, ApiError> {
+    let target = gateway_name.clone();
+    crate::audit::audited(
+        &database,
+        "reset_gateway_monitor",
+        &target,
+        reset_gateway_monitor_request(database.clone(), cache, gateway_name),
+    )
+    .await
+}
+
+async fn reset_gateway_monitor_request(
+    database: State<'_, Database>,
+    cache: State<'_, ResponseCache>,
+    gateway_name: String,
+) -> Result<RestartServiceResponse, ApiError> {
+    let gateways = fetch_gateway_status(&profile_api_info(&database, None)?).await?;
+
+    if !gateways
+        .items
+        .iter()
+        .any(|gateway| gateway.name == gateway_name)
+    {
+        let known: Vec<&str> = gateways.items.iter().map(|g| g.name.as_str()).collect();
+        return Err(format!(
+            "Gateway '{}' not found. Available gateways: {}",
+            gateway_name,
+            known.join(", ")
+        )
+        .into());
+    }
+
+    control_service(database, cache, "restart", &format!("dpinger/{}", gateway_name)).await
+}
+
+#[tauri::command]
+pub async fn get_system_time(
+    database: State<'_, Database>,
+    cache: State<'_, ResponseCache>,
+) -> Result<SystemTime, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
         .ok_or_else(|| "API info not found".to_string())?;
 
+    cache
+        .single_flight(
+            &request_key(&api_info, "GET", "/api/diagnostics/system/systemTime", None),
+            fetch_system_time(&api_info),
+        )
+        .await
+}
+
+async fn fetch_system_time(api_info: &ApiInfo) -> Result<SystemTime, ApiError> {
     let url = format!(
         "{}:{}/api/diagnostics/system/systemTime",
         api_info.api_url, api_info.port
     );
 
-    let response = make_http_request(
+    let response = make_http_request_with_retry(
         "GET",
         &url,
         None,
         None,
-        Some(30),
+        api_info.dashboard_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+        api_info.max_retries,
     )
     .await?;
 
     response
         .json::<SystemTime>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
@@ -9,12 +9,14 @@ use rusqlite::{params, types::Type, Connection, OptionalExtension, Result};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::{Arc, Mutex},
 };
 use tauri::Manager;
 
+use crate::http_client::ClientSettings;
 use crate::pin_cache::PinCache;
+use crate::timeouts::TimeoutCategory;
 
 pub struct Database {
     conn: Arc<Mutex<Connection>>,
@@ -22,6 +24,10 @@ pub struct Database {
     pin_cache: Arc<PinCache>,
 }
 
+// Key derivation used for the stored ciphertexts: version 0 keyed ChaCha20 with a
+// bare SHA-256 of the PIN, version 1 uses Argon2 with the stored PIN salt
+const KDF_VERSION_ARGON2: i64 = 1;
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ApiInfo {
     pub id: i64,
@@ -31,6 +37,55 @@ pub struct ApiInfo {
     pub api_url: String,
     pub port: u16,
     pub is_default: bool,
+    pub verify_tls: bool,
+    pub ca_cert_path: Option<String>,
+    pub max_retries: u32,
+    /// Per-category request timeout overrides in seconds, keyed by
+    /// `TimeoutCategory::key`
+    #[serde(default)]
+    pub timeouts: HashMap<String, u64>,
+    /// Custom headers added to every request for this profile
+    #[serde(default)]
+    pub extra_headers: HashMap<String, String>,
+}
+
+impl ApiInfo {
+    pub fn client_settings(&self) -> ClientSettings {
+        ClientSettings {
+            accept_invalid_certs: !self.verify_tls,
+            ca_cert_path: self.ca_cert_path.clone(),
+            extra_headers: {
+                // Sorted so the same headers always map to the same cached client
+                let mut headers: Vec<_> = self
+                    .extra_headers
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect();
+                headers.sort();
+                headers
+            },
+            profile_id: Some(self.id),
+        }
+    }
+
+    pub fn timeout(&self, category: TimeoutCategory) -> u64 {
+        self.timeouts
+            .get(category.key())
+            .copied()
+            .unwrap_or_else(|| category.default_seconds())
+    }
+
+    pub fn dashboard_timeout(&self) -> Option<u64> {
+        Some(self.timeout(TimeoutCategory::Dashboard))
+    }
+
+    pub fn request_timeout(&self) -> Option<u64> {
+        Some(self.timeout(TimeoutCategory::Standard))
+    }
+
+    pub fn long_timeout(&self) -> Option<u64> {
+        Some(self.timeout(TimeoutCategory::LongRunning))
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -40,6 +95,49 @@ pub struct DashboardWidgetPref {
     pub position: i32,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AlertMonitorSettings {
+    pub enabled: bool,
+    pub interval_seconds: u64,
+}
+
+impl Default for AlertMonitorSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_seconds: 900,
+        }
+    }
+}
+
+/// A reboot the app will trigger, keyed by profile name since profile ids
+/// change whenever a profile is saved
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScheduledReboot {
+    pub profile_name: String,
+    /// Unix timestamp in seconds
+    pub reboot_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Favorite {
+    pub kind: String,
+    pub item_id: String,
+    pub label: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub timestamp: String,
+    pub profile_id: Option<i64>,
+    pub command: String,
+    pub target: String,
+    pub outcome: String,
+    pub error: Option<String>,
+}
+
 impl Database {
     pub fn new(app_handle: &tauri::AppHandle) -> Result<Self> {
         let app_dir = app_handle
@@ -50,8 +148,15 @@ impl Database {
 
         let db_path = app_dir.join("app.db");
 
-        let conn = Connection::open(db_path)?;
+        Self::open(Connection::open(db_path)?)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn open_in_memory() -> Result<Self> {
+        Self::open(Connection::open_in_memory()?)
+    }
 
+    fn open(conn: Connection) -> Result<Self> {
         let pin_cache = Arc::new(PinCache::new());
 
         let db = Database {
@@ -80,7 +185,8 @@ impl Database {
             "CREATE TABLE IF NOT EXISTS app_settings (
                 id INTEGER PRIMARY KEY,
                 password_hash TEXT NOT NULL,
-                pin_salt TEXT NOT NULL DEFAULT ''
+                pin_salt TEXT NOT NULL DEFAULT '',
+                kdf_version INTEGER NOT NULL DEFAULT 0
             )",
             [],
         )?;
@@ -97,10 +203,88 @@ impl Database {
             [],
         )?;
 
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS profile_timeouts (
+                profile_id INTEGER NOT NULL,
+                category TEXT NOT NULL,
+                seconds INTEGER NOT NULL,
+                PRIMARY KEY(profile_id, category),
+                FOREIGN KEY(profile_id) REFERENCES api_info(id)
+            )",
+            [],
+        )?;
+
+        // Header values can hold proxy tokens, so they are encrypted like
+        // the API credentials
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS profile_headers (
+                profile_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                encrypted_value BLOB NOT NULL,
+                value_nonce BLOB NOT NULL,
+                PRIMARY KEY(profile_id, name),
+                FOREIGN KEY(profile_id) REFERENCES api_info(id)
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS log_settings (
+                id INTEGER PRIMARY KEY,
+                level TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS alert_monitor_settings (
+                id INTEGER PRIMARY KEY,
+                enabled BOOLEAN NOT NULL DEFAULT 0,
+                interval_seconds INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS scheduled_reboots (
+                profile_name TEXT PRIMARY KEY,
+                reboot_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        // Pinned rules and services; references only, the items themselves
+        // stay on the firewall
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS favorites (
+                profile_id INTEGER NOT NULL,
+                kind TEXT NOT NULL,
+                item_id TEXT NOT NULL,
+                label TEXT,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY(profile_id, kind, item_id),
+                FOREIGN KEY(profile_id) REFERENCES api_info(id)
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY,
+                timestamp TEXT NOT NULL,
+                profile_id INTEGER,
+                command TEXT NOT NULL,
+                target TEXT NOT NULL DEFAULT '',
+                outcome TEXT NOT NULL,
+                error TEXT
+            )",
+            [],
+        )?;
+
         Ok(())
     }
 
-    fn derive_encryption_key(&self, pin: &str, salt: &str) -> Result<Vec<u8>, String> {
+    pub(crate) fn derive_encryption_key(&self, pin: &str, salt: &str) -> Result<Vec<u8>, String> {
         let salt = SaltString::from_b64(salt).map_err(|e| format!("Invalid salt: {}", e))?;
 
         let argon2 = Argon2::default();
@@ -122,19 +306,66 @@ impl Database {
         *current_key = Some(key);
     }
 
-    fn encrypt_string(&self, plaintext: &str, pin: &str) -> Result<(Vec<u8>, Vec<u8>), String> {
+    /// True until the PIN is entered. Profiles read while locked come back
+    /// with blank credentials, which a saved profile may also legitimately
+    /// have, so callers check this instead.
+    pub fn is_locked(&self) -> bool {
+        self.current_pin_key.lock().unwrap().is_none()
+    }
+
+    fn current_encryption_key(&self) -> Result<Vec<u8>, String> {
+        self.current_pin_key
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| "User needs to authenticate first".to_string())
+    }
+
+    // Key used before the Argon2 derivation, only needed to read old ciphertexts
+    fn legacy_encryption_key(pin: &str) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update(pin.as_bytes());
+        hasher.finalize().to_vec()
+    }
+
+    fn ensure_pin_salt(&self) -> Result<String, String> {
+        let conn = self.conn.lock().unwrap();
+
+        let salt: String = conn
+            .query_row(
+                "SELECT pin_salt FROM app_settings WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to get PIN salt: {}", e))?;
+
+        if !salt.is_empty() {
+            return Ok(salt);
+        }
+
+        info!("Creating new salt for encryption");
+        let salt = SaltString::generate(&mut OsRng).to_string();
+        conn.execute(
+            "UPDATE app_settings SET pin_salt = ?1 WHERE id = 1",
+            params![salt],
+        )
+        .map_err(|e| format!("Failed to update salt: {}", e))?;
+
+        Ok(salt)
+    }
+
+    pub(crate) fn encrypt_string(
+        &self,
+        plaintext: &str,
+        key: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), String> {
         use rand::{thread_rng, Rng};
 
         let mut nonce_bytes = [0u8; 12];
         thread_rng().fill(&mut nonce_bytes);
         let nonce = Nonce::from_slice(&nonce_bytes);
 
-        let mut hasher = Sha256::new();
-        hasher.update(pin.as_bytes());
-        let key_bytes = hasher.finalize();
-        let key = Key::from_slice(&key_bytes);
-
-        let cipher = ChaCha20Poly1305::new(key);
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
 
         let ciphertext = cipher
             .encrypt(nonce, plaintext.as_bytes())
@@ -143,18 +374,13 @@ impl Database {
         Ok((ciphertext, nonce_bytes.to_vec()))
     }
 
-    fn decrypt_string(
+    pub(crate) fn decrypt_string(
         &self,
         ciphertext: &[u8],
         nonce_bytes: &[u8],
-        pin: &str,
+        key: &[u8],
     ) -> Result<String, String> {
-        let mut hasher = Sha256::new();
-        hasher.update(pin.as_bytes());
-        let key_bytes = hasher.finalize();
-        let key = Key::from_slice(&key_bytes);
-
-        let cipher = ChaCha20Poly1305::new(key);
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
 
         let nonce = Nonce::from_slice(nonce_bytes);
 
@@ -199,7 +425,10 @@ impl Database {
                     api_secret_nonce BLOB NOT NULL,
                     api_url TEXT NOT NULL,
                     port INTEGER NOT NULL,
-                    is_default BOOLEAN NOT NULL DEFAULT 0
+                    is_default BOOLEAN NOT NULL DEFAULT 0,
+                    verify_tls BOOLEAN NOT NULL DEFAULT 0,
+                    ca_cert_path TEXT,
+                    max_retries INTEGER NOT NULL DEFAULT 3
                 )",
                 [],
             )?;
@@ -221,10 +450,94 @@ impl Database {
             )?;
         }
 
+        let has_kdf_version_column: bool = conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('app_settings') WHERE name='kdf_version'",
+            [],
+            |row| {
+                let count: i64 = row.get(0)?;
+                Ok(count > 0)
+            },
+        )?;
+
+        // Existing ciphertexts are re-keyed on the next successful PIN entry
+        if !has_kdf_version_column {
+            conn.execute(
+                "ALTER TABLE app_settings ADD COLUMN kdf_version INTEGER NOT NULL DEFAULT 0",
+                [],
+            )?;
+        }
+
+        // Profiles created before TLS verification was configurable relied on
+        // invalid certificates being accepted, so they keep verify_tls = 0
+        for table in ["api_info", "api_info_new"] {
+            let table_exists: bool = conn.query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name=?1",
+                params![table],
+                |row| {
+                    let count: i64 = row.get(0)?;
+                    Ok(count > 0)
+                },
+            )?;
+
+            if !table_exists {
+                continue;
+            }
+
+            let has_verify_tls_column: bool = conn.query_row(
+                &format!(
+                    "SELECT COUNT(*) FROM pragma_table_info('{}') WHERE name='verify_tls'",
+                    table
+                ),
+                [],
+                |row| {
+                    let count: i64 = row.get(0)?;
+                    Ok(count > 0)
+                },
+            )?;
+
+            if !has_verify_tls_column {
+                info!("Adding TLS verification columns to {}", table);
+                conn.execute(
+                    &format!(
+                        "ALTER TABLE {} ADD COLUMN verify_tls BOOLEAN NOT NULL DEFAULT 0",
+                        table
+                    ),
+                    [],
+                )?;
+                conn.execute(
+                    &format!("ALTER TABLE {} ADD COLUMN ca_cert_path TEXT", table),
+                    [],
+                )?;
+            }
+
+            let has_max_retries_column: bool = conn.query_row(
+                &format!(
+                    "SELECT COUNT(*) FROM pragma_table_info('{}') WHERE name='max_retries'",
+                    table
+                ),
+                [],
+                |row| {
+                    let count: i64 = row.get(0)?;
+                    Ok(count > 0)
+                },
+            )?;
+
+            if !has_max_retries_column {
+                info!("Adding retry configuration column to {}", table);
+                conn.execute(
+                    &format!(
+                        "ALTER TABLE {} ADD COLUMN max_retries INTEGER NOT NULL DEFAULT 3",
+                        table
+                    ),
+                    [],
+                )?;
+            }
+        }
+
         Ok(())
     }
 
-    fn complete_migration(&self, pin: &str) -> Result<(), String> {
+    fn complete_migration(&self, key: &[u8]) -> Result<(), String> {
         info!("Starting complete_migration process");
 
         let (has_api_key_column, has_encrypted_api_key_column, has_api_info_new) =
@@ -273,32 +586,6 @@ impl Database {
             return Ok(());
         }
 
-        {
-            let conn = self.conn.lock().unwrap();
-
-            info!("Setting up salt for encryption");
-            let salt_exists: bool = conn
-                .query_row(
-                    "SELECT COUNT(*) FROM app_settings WHERE id = 1 AND pin_salt != ''",
-                    [],
-                    |row| {
-                        let count: i64 = row.get(0)?;
-                        Ok(count > 0)
-                    },
-                )
-                .map_err(|e| format!("Failed to check for salt: {}", e))?;
-
-            if !salt_exists {
-                info!("Creating new salt for encryption");
-                let salt = SaltString::generate(&mut OsRng).to_string();
-                conn.execute(
-                    "UPDATE app_settings SET pin_salt = ?1 WHERE id = 1",
-                    params![salt],
-                )
-                .map_err(|e| format!("Failed to update salt: {}", e))?;
-            }
-        }
-
         if !has_api_info_new {
             let conn = self.conn.lock().unwrap();
 
@@ -313,7 +600,10 @@ impl Database {
                     api_secret_nonce BLOB NOT NULL,
                     api_url TEXT NOT NULL,
                     port INTEGER NOT NULL,
-                    is_default BOOLEAN NOT NULL DEFAULT 0
+                    is_default BOOLEAN NOT NULL DEFAULT 0,
+                    verify_tls BOOLEAN NOT NULL DEFAULT 0,
+                    ca_cert_path TEXT,
+                    max_retries INTEGER NOT NULL DEFAULT 3
                 )",
                 [],
             )
@@ -325,7 +615,7 @@ impl Database {
 
             info!("Reading data from unencrypted table");
             let mut stmt = conn.prepare(
-                "SELECT id, profile_name, api_key, api_secret, api_url, port, is_default FROM api_info"
+                "SELECT id, profile_name, api_key, api_secret, api_url, port, is_default, verify_tls, ca_cert_path, max_retries FROM api_info"
             ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
 
             let rows = stmt
@@ -338,6 +628,9 @@ impl Database {
                         row.get::<_, String>(4)?,
                         row.get::<_, i64>(5)?,
                         row.get::<_, bool>(6)?,
+                        row.get::<_, bool>(7)?,
+                        row.get::<_, Option<String>>(8)?,
+                        row.get::<_, u32>(9)?,
                     ))
                 })
                 .map_err(|e| format!("Failed to query old data: {}", e))?;
@@ -348,18 +641,30 @@ impl Database {
 
         info!("Found {} profiles to migrate", profiles.len());
 
-        for (id, profile_name, api_key, api_secret, api_url, port, is_default) in profiles {
+        for (
+            id,
+            profile_name,
+            api_key,
+            api_secret,
+            api_url,
+            port,
+            is_default,
+            verify_tls,
+            ca_cert_path,
+            max_retries,
+        ) in profiles
+        {
             info!("Encrypting data for profile: {}", profile_name);
-            let (encrypted_api_key, api_key_nonce) = self.encrypt_string(&api_key, pin)?;
-            let (encrypted_api_secret, api_secret_nonce) = self.encrypt_string(&api_secret, pin)?;
+            let (encrypted_api_key, api_key_nonce) = self.encrypt_string(&api_key, key)?;
+            let (encrypted_api_secret, api_secret_nonce) = self.encrypt_string(&api_secret, key)?;
 
             {
                 let conn = self.conn.lock().unwrap();
                 info!("Inserting encrypted data for profile: {}", profile_name);
                 conn.execute(
                     "INSERT INTO api_info_new (id, profile_name, encrypted_api_key, api_key_nonce, 
-                     encrypted_api_secret, api_secret_nonce, api_url, port, is_default) 
-                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                     encrypted_api_secret, api_secret_nonce, api_url, port, is_default, verify_tls, ca_cert_path, max_retries) 
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
                     params![
                         id,
                         profile_name,
@@ -369,7 +674,10 @@ impl Database {
                         api_secret_nonce,
                         api_url,
                         port,
-                        is_default
+                        is_default,
+                        verify_tls,
+                        ca_cert_path,
+                        max_retries
                     ],
                 )
                 .map_err(|e| format!("Failed to insert encrypted data: {}", e))?;
@@ -385,12 +693,111 @@ impl Database {
 
             conn.execute("ALTER TABLE api_info_new RENAME TO api_info", [])
                 .map_err(|e| format!("Failed to rename new table: {}", e))?;
+
+            conn.execute(
+                "UPDATE app_settings SET kdf_version = ?1 WHERE id = 1",
+                params![KDF_VERSION_ARGON2],
+            )
+            .map_err(|e| format!("Failed to update key derivation version: {}", e))?;
         }
 
         info!("Migration to encrypted API info completed successfully");
         Ok(())
     }
 
+    // Re-encrypts credentials written with the legacy SHA-256 key using the
+    // salted Argon2 key
+    fn upgrade_legacy_encryption(&self, pin: &str, key: &[u8]) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let kdf_version: i64 = conn
+            .query_row(
+                "SELECT kdf_version FROM app_settings WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to get key derivation version: {}", e))?;
+
+        if kdf_version >= KDF_VERSION_ARGON2 {
+            return Ok(());
+        }
+
+        let has_encrypted_api_key_column: bool = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('api_info') WHERE name='encrypted_api_key'",
+                [],
+                |row| {
+                    let count: i64 = row.get(0)?;
+                    Ok(count > 0)
+                },
+            )
+            .map_err(|e| format!("Failed to check for encrypted_api_key column: {}", e))?;
+
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+        if has_encrypted_api_key_column {
+            let legacy_key = Self::legacy_encryption_key(pin);
+
+            let rows = {
+                let mut stmt = tx
+                    .prepare("SELECT id, profile_name, encrypted_api_key, api_key_nonce, encrypted_api_secret, api_secret_nonce FROM api_info")
+                    .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((
+                            row.get::<_, i64>(0)?,
+                            row.get::<_, String>(1)?,
+                            row.get::<_, Vec<u8>>(2)?,
+                            row.get::<_, Vec<u8>>(3)?,
+                            row.get::<_, Vec<u8>>(4)?,
+                            row.get::<_, Vec<u8>>(5)?,
+                        ))
+                    })
+                    .map_err(|e| format!("Failed to query encrypted data: {}", e))?;
+
+                rows.collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("Failed to collect profiles: {}", e))?
+            };
+
+            info!("Re-encrypting {} profiles with Argon2 key", rows.len());
+
+            for (id, profile_name, api_key, api_key_nonce, api_secret, api_secret_nonce) in rows {
+                let api_key = self.decrypt_string(&api_key, &api_key_nonce, &legacy_key)?;
+                let api_secret =
+                    self.decrypt_string(&api_secret, &api_secret_nonce, &legacy_key)?;
+
+                let (encrypted_api_key, api_key_nonce) = self.encrypt_string(&api_key, key)?;
+                let (encrypted_api_secret, api_secret_nonce) =
+                    self.encrypt_string(&api_secret, key)?;
+
+                tx.execute(
+                    "UPDATE api_info SET encrypted_api_key = ?1, api_key_nonce = ?2,
+                     encrypted_api_secret = ?3, api_secret_nonce = ?4 WHERE id = ?5",
+                    params![
+                        encrypted_api_key,
+                        api_key_nonce,
+                        encrypted_api_secret,
+                        api_secret_nonce,
+                        id
+                    ],
+                )
+                .map_err(|e| format!("Failed to update profile '{}': {}", profile_name, e))?;
+            }
+        }
+
+        tx.execute(
+            "UPDATE app_settings SET kdf_version = ?1 WHERE id = 1",
+            params![KDF_VERSION_ARGON2],
+        )
+        .map_err(|e| format!("Failed to update key derivation version: {}", e))?;
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit re-encryption: {}", e))
+    }
+
     pub fn is_first_run(&self) -> Result<bool> {
         let conn = self.conn.lock().unwrap();
         let count: i64 = conn.query_row("SELECT COUNT(*) FROM first_run", [], |row| row.get(0))?;
@@ -453,21 +860,27 @@ impl Database {
                 api_secret TEXT NOT NULL,
                 api_url TEXT NOT NULL,
                 port INTEGER NOT NULL,
-                is_default BOOLEAN NOT NULL DEFAULT 0
+                is_default BOOLEAN NOT NULL DEFAULT 0,
+                verify_tls BOOLEAN NOT NULL DEFAULT 0,
+                ca_cert_path TEXT,
+                max_retries INTEGER NOT NULL DEFAULT 3
             )",
             [],
         )?;
 
         info!("Inserting first profile with unencrypted schema");
         conn.execute(
-            "INSERT INTO api_info (profile_name, api_key, api_secret, api_url, port, is_default) 
-            VALUES (?1, ?2, ?3, ?4, ?5, 1)",
+            "INSERT INTO api_info (profile_name, api_key, api_secret, api_url, port, is_default, verify_tls, ca_cert_path, max_retries) 
+            VALUES (?1, ?2, ?3, ?4, ?5, 1, ?6, ?7, ?8)",
             params![
                 api_info.profile_name,
                 api_info.api_key,
                 api_info.api_secret,
                 api_info.api_url,
-                api_info.port
+                api_info.port,
+                api_info.verify_tls,
+                api_info.ca_cert_path,
+                api_info.max_retries
             ],
         )?;
 
@@ -538,16 +951,19 @@ impl Database {
                     api_secret TEXT NOT NULL,
                     api_url TEXT NOT NULL,
                     port INTEGER NOT NULL,
-                    is_default BOOLEAN NOT NULL DEFAULT 0
+                    is_default BOOLEAN NOT NULL DEFAULT 0,
+                    verify_tls BOOLEAN NOT NULL DEFAULT 0,
+                    ca_cert_path TEXT,
+                    max_retries INTEGER NOT NULL DEFAULT 3
                 )",
                 [],
             )?;
 
             info!("Inserting first profile with unencrypted schema");
             conn.execute(
-                "INSERT INTO api_info (profile_name, api_key, api_secret, api_url, port, is_default) 
-                 VALUES (?1, ?2, ?3, ?4, ?5, 1)",
-                params![api_info.profile_name,api_info.api_key,api_info.api_secret,api_info.api_url,api_info.port],
+                "INSERT INTO api_info (profile_name, api_key, api_secret, api_url, port, is_default, verify_tls, ca_cert_path, max_retries) 
+                 VALUES (?1, ?2, ?3, ?4, ?5, 1, ?6, ?7, ?8)",
+                params![api_info.profile_name,api_info.api_key,api_info.api_secret,api_info.api_url,api_info.port,api_info.verify_tls,api_info.ca_cert_path,api_info.max_retries],
             )?;
 
             info!("First profile inserted successfully");
@@ -557,9 +973,9 @@ impl Database {
         if has_unencrypted_columns {
             info!("Using existing unencrypted schema");
             conn.execute(
-                "INSERT OR REPLACE INTO api_info (profile_name, api_key, api_secret, api_url, port, is_default) 
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                params![api_info.profile_name,api_info.api_key,api_info.api_secret,api_info.api_url,api_info.port,api_info.is_default],
+                "INSERT OR REPLACE INTO api_info (profile_name, api_key, api_secret, api_url, port, is_default, verify_tls, ca_cert_path, max_retries) 
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![api_info.profile_name,api_info.api_key,api_info.api_secret,api_info.api_url,api_info.port,api_info.is_default,api_info.verify_tls,api_info.ca_cert_path,api_info.max_retries],
             )?;
         } else if has_encrypted_columns {
             info!("Using existing encrypted schema");
@@ -578,21 +994,21 @@ impl Database {
                 ));
             }
 
-            let pin = self.get_cached_pin().map_err(|e| {
-                error!("Failed to get cached PIN: {}", e);
+            let key = self.current_encryption_key().map_err(|e| {
+                error!("Failed to get encryption key: {}", e);
                 rusqlite::Error::InvalidParameterName(
                     "PIN authentication required. Please login again.".to_string(),
                 )
             })?;
 
             let (encrypted_api_key, api_key_nonce) =
-                self.encrypt_string(&api_info.api_key, &pin).map_err(|e| {
+                self.encrypt_string(&api_info.api_key, &key).map_err(|e| {
                     error!("Failed to encrypt API key: {}", e);
                     rusqlite::Error::InvalidParameterName("Failed to encrypt API key".to_string())
                 })?;
 
             let (encrypted_api_secret, api_secret_nonce) = self
-                .encrypt_string(&api_info.api_secret, &pin)
+                .encrypt_string(&api_info.api_secret, &key)
                 .map_err(|e| {
                     error!("Failed to encrypt API secret: {}", e);
                     rusqlite::Error::InvalidParameterName(
@@ -619,8 +1035,11 @@ impl Database {
                         api_secret_nonce = ?4, 
                         api_url = ?5, 
                         port = ?6, 
-                        is_default = ?7
-                    WHERE id = ?8",
+                        is_default = ?7,
+                        verify_tls = ?8,
+                        ca_cert_path = ?9,
+                        max_retries = ?10
+                    WHERE id = ?11",
                     params![
                         encrypted_api_key,
                         api_key_nonce,
@@ -629,6 +1048,9 @@ impl Database {
                         api_info.api_url,
                         api_info.port,
                         api_info.is_default,
+                        api_info.verify_tls,
+                        api_info.ca_cert_path,
+                        api_info.max_retries,
                         id
                     ],
                 )?;
@@ -636,8 +1058,8 @@ impl Database {
                 // Insert a new profile
                 conn.execute(
                     "INSERT INTO api_info (profile_name, encrypted_api_key, api_key_nonce, 
-                    encrypted_api_secret, api_secret_nonce, api_url, port, is_default) 
-                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    encrypted_api_secret, api_secret_nonce, api_url, port, is_default, verify_tls, ca_cert_path, max_retries) 
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
                     params![
                         api_info.profile_name,
                         encrypted_api_key,
@@ -646,7 +1068,10 @@ impl Database {
                         api_secret_nonce,
                         api_info.api_url,
                         api_info.port,
-                        api_info.is_default
+                        api_info.is_default,
+                        api_info.verify_tls,
+                        api_info.ca_cert_path,
+                        api_info.max_retries
                     ],
                 )?;
             }
@@ -661,15 +1086,18 @@ impl Database {
                     api_secret TEXT NOT NULL,
                     api_url TEXT NOT NULL,
                     port INTEGER NOT NULL,
-                    is_default BOOLEAN NOT NULL DEFAULT 0
+                    is_default BOOLEAN NOT NULL DEFAULT 0,
+                    verify_tls BOOLEAN NOT NULL DEFAULT 0,
+                    ca_cert_path TEXT,
+                    max_retries INTEGER NOT NULL DEFAULT 3
                 )",
                 [],
             )?;
 
             conn.execute(
-                "INSERT INTO api_info (profile_name, api_key, api_secret, api_url, port, is_default) 
-                 VALUES (?1, ?2, ?3, ?4, ?5, 1)",
-                params![api_info.profile_name,api_info.api_key,api_info.api_secret,api_info.api_url,api_info.port],
+                "INSERT INTO api_info (profile_name, api_key, api_secret, api_url, port, is_default, verify_tls, ca_cert_path, max_retries) 
+                 VALUES (?1, ?2, ?3, ?4, ?5, 1, ?6, ?7, ?8)",
+                params![api_info.profile_name,api_info.api_key,api_info.api_secret,api_info.api_url,api_info.port,api_info.verify_tls,api_info.ca_cert_path,api_info.max_retries],
             )?;
         }
 
@@ -703,20 +1131,16 @@ impl Database {
         Ok(())
     }
 
-    fn get_cached_pin(&self) -> Result<String, String> {
-        if let Some(pin) = self.pin_cache.get_pin() {
-            log::info!(
-                "Successfully retrieved PIN from cache, length: {}",
-                pin.len()
-            );
-            return Ok(pin);
+    pub fn get_api_info(&self, profile_name: Option<&str>) -> Result<Option<ApiInfo>> {
+        let mut api_info = self.read_api_info(profile_name)?;
+        if let Some(info) = api_info.as_mut() {
+            info.timeouts = self.get_timeout_overrides(info.id)?;
+            info.extra_headers = self.get_profile_headers(info.id)?;
         }
-
-        log::error!("PIN not found in cache");
-        Err("User needs to authenticate first".to_string())
+        Ok(api_info)
     }
 
-    pub fn get_api_info(&self, profile_name: Option<&str>) -> Result<Option<ApiInfo>> {
+    fn read_api_info(&self, profile_name: Option<&str>) -> Result<Option<ApiInfo>> {
         let conn = self.conn.lock().unwrap();
 
         let has_encrypted_columns: bool = conn.query_row(
@@ -730,8 +1154,8 @@ impl Database {
 
         if has_encrypted_columns {
             let query = match profile_name {
-                Some(_) => "SELECT id, profile_name, encrypted_api_key, api_key_nonce, encrypted_api_secret, api_secret_nonce, api_url, port, is_default FROM api_info WHERE profile_name = ?1",
-                None => "SELECT id, profile_name, encrypted_api_key, api_key_nonce, encrypted_api_secret, api_secret_nonce, api_url, port, is_default FROM api_info WHERE is_default = 1",
+                Some(_) => "SELECT id, profile_name, encrypted_api_key, api_key_nonce, encrypted_api_secret, api_secret_nonce, api_url, port, is_default, verify_tls, ca_cert_path, max_retries FROM api_info WHERE profile_name = ?1",
+                None => "SELECT id, profile_name, encrypted_api_key, api_key_nonce, encrypted_api_secret, api_secret_nonce, api_url, port, is_default, verify_tls, ca_cert_path, max_retries FROM api_info WHERE is_default = 1",
             };
 
             let mut stmt = conn.prepare(query)?;
@@ -747,6 +1171,9 @@ impl Database {
                     let api_url: String = row.get(6)?;
                     let port: u16 = row.get(7)?;
                     let is_default: bool = row.get(8)?;
+                    let verify_tls: bool = row.get(9)?;
+                    let ca_cert_path: Option<String> = row.get(10)?;
+                    let max_retries: u32 = row.get(11)?;
 
                     Ok((
                         id,
@@ -758,6 +1185,9 @@ impl Database {
                         api_url,
                         port,
                         is_default,
+                        verify_tls,
+                        ca_cert_path,
+                        max_retries,
                     ))
                 })
             } else {
@@ -771,6 +1201,9 @@ impl Database {
                     let api_url: String = row.get(6)?;
                     let port: u16 = row.get(7)?;
                     let is_default: bool = row.get(8)?;
+                    let verify_tls: bool = row.get(9)?;
+                    let ca_cert_path: Option<String> = row.get(10)?;
+                    let max_retries: u32 = row.get(11)?;
 
                     Ok((
                         id,
@@ -782,6 +1215,9 @@ impl Database {
                         api_url,
                         port,
                         is_default,
+                        verify_tls,
+                        ca_cert_path,
+                        max_retries,
                     ))
                 })
             };
@@ -797,9 +1233,12 @@ impl Database {
                     api_url,
                     port,
                     is_default,
+                    verify_tls,
+                    ca_cert_path,
+                    max_retries,
                 )) => {
-                    let pin = match self.get_cached_pin() {
-                        Ok(pin) => pin,
+                    let key = match self.current_encryption_key() {
+                        Ok(key) => key,
                         Err(_) => {
                             return Ok(Some(ApiInfo {
                                 id,
@@ -809,13 +1248,18 @@ impl Database {
                                 api_url,
                                 port,
                                 is_default,
+                                verify_tls,
+                                ca_cert_path,
+                                max_retries,
+                                timeouts: HashMap::new(),
+                                extra_headers: HashMap::new(),
                             }));
                         }
                     };
 
                     // Decrypt the API key and secret
                     let api_key =
-                        match self.decrypt_string(&encrypted_api_key, &api_key_nonce, &pin) {
+                        match self.decrypt_string(&encrypted_api_key, &api_key_nonce, &key) {
                             Ok(decrypted) => decrypted,
                             Err(e) => {
                                 error!("Failed to decrypt API key: {}", e);
@@ -824,7 +1268,7 @@ impl Database {
                         };
 
                     let api_secret =
-                        match self.decrypt_string(&encrypted_api_secret, &api_secret_nonce, &pin) {
+                        match self.decrypt_string(&encrypted_api_secret, &api_secret_nonce, &key) {
                             Ok(decrypted) => decrypted,
                             Err(e) => {
                                 error!("Failed to decrypt API secret: {}", e);
@@ -840,6 +1284,11 @@ impl Database {
                         api_url,
                         port,
                         is_default,
+                        verify_tls,
+                        ca_cert_path,
+                        max_retries,
+                        timeouts: HashMap::new(),
+                        extra_headers: HashMap::new(),
                     }))
                 }
                 Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
@@ -847,8 +1296,8 @@ impl Database {
             }
         } else {
             let query = match profile_name {
-                Some(_) => "SELECT id, profile_name, api_key, api_secret, api_url, port, is_default FROM api_info WHERE profile_name = ?1",
-                None => "SELECT id, profile_name, api_key, api_secret, api_url, port, is_default FROM api_info WHERE is_default = 1",
+                Some(_) => "SELECT id, profile_name, api_key, api_secret, api_url, port, is_default, verify_tls, ca_cert_path, max_retries FROM api_info WHERE profile_name = ?1",
+                None => "SELECT id, profile_name, api_key, api_secret, api_url, port, is_default, verify_tls, ca_cert_path, max_retries FROM api_info WHERE is_default = 1",
             };
 
             let mut stmt = conn.prepare(query)?;
@@ -870,6 +1319,28 @@ impl Database {
         self.get_api_info(None)
     }
 
+    // Commands that accept an optional profile id fall back to the default profile
+    pub fn get_profile_api_info(&self, profile_id: Option<i64>) -> Result<Option<ApiInfo>> {
+        let Some(id) = profile_id else {
+            return self.get_default_api_info();
+        };
+
+        let profile_name: Option<String> = {
+            let conn = self.conn.lock().unwrap();
+            conn.query_row(
+                "SELECT profile_name FROM api_info WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?
+        };
+
+        match profile_name {
+            Some(name) => self.get_api_info(Some(&name)),
+            None => Ok(None),
+        }
+    }
+
     fn row_to_api_info(&self, row: &rusqlite::Row) -> rusqlite::Result<ApiInfo> {
         Ok(ApiInfo {
             id: row.get(0)?,
@@ -879,6 +1350,11 @@ impl Database {
             api_url: row.get(4)?,
             port: row.get(5)?,
             is_default: row.get(6)?,
+            verify_tls: row.get(7)?,
+            ca_cert_path: row.get(8)?,
+            max_retries: row.get(9)?,
+            timeouts: HashMap::new(),
+            extra_headers: HashMap::new(),
         })
     }
 
@@ -896,7 +1372,7 @@ impl Database {
 
         if has_encrypted_columns {
             let mut stmt = conn.prepare(
-                "SELECT id, profile_name, api_url, port, is_default FROM api_info ORDER BY profile_name"
+                "SELECT id, profile_name, api_url, port, is_default, verify_tls, ca_cert_path, max_retries FROM api_info ORDER BY profile_name"
             )?;
 
             let rows = stmt.query_map([], |row| {
@@ -908,13 +1384,18 @@ impl Database {
                     api_url: row.get(2)?,
                     port: row.get(3)?,
                     is_default: row.get(4)?,
+                    verify_tls: row.get(5)?,
+                    ca_cert_path: row.get(6)?,
+                    max_retries: row.get(7)?,
+                    timeouts: HashMap::new(),
+                    extra_headers: HashMap::new(),
                 })
             })?;
 
             rows.collect::<Result<Vec<ApiInfo>, _>>()
         } else {
             let mut stmt = conn.prepare(
-                "SELECT id, profile_name, api_key, api_secret, api_url, port, is_default FROM api_info ORDER BY profile_name"
+                "SELECT id, profile_name, api_key, api_secret, api_url, port, is_default, verify_tls, ca_cert_path, max_retries FROM api_info ORDER BY profile_name"
             )?;
 
             let profiles = stmt
@@ -954,6 +1435,23 @@ impl Database {
             params![profile_id],
         )?;
 
+        tx.execute(
+            "DELETE FROM profile_timeouts WHERE profile_id = ?1",
+            params![profile_id],
+        )?;
+        tx.execute(
+            "DELETE FROM profile_headers WHERE profile_id = ?1",
+            params![profile_id],
+        )?;
+        tx.execute(
+            "DELETE FROM favorites WHERE profile_id = ?1",
+            params![profile_id],
+        )?;
+        tx.execute(
+            "DELETE FROM scheduled_reboots WHERE profile_name = ?1",
+            params![profile_name],
+        )?;
+
         // Now delete the profile itself
         tx.execute(
             "DELETE FROM api_info WHERE profile_name = ?1",
@@ -1016,35 +1514,31 @@ impl Database {
                     log::info!("PIN verified successfully, saving to cache");
                     self.pin_cache.set_pin(pin.to_string());
 
-                    let salt: String = conn
-                        .query_row(
-                            "SELECT pin_salt FROM app_settings WHERE id = 1",
-                            [],
-                            |row| row.get(0),
-                        )
+                    drop(conn);
+
+                    let key = self
+                        .ensure_pin_salt()
+                        .and_then(|salt| self.derive_encryption_key(pin, &salt))
                         .map_err(|e| {
-                            error!("Failed to get PIN salt: {}", e);
-                            e
+                            error!("Failed to derive encryption key: {}", e);
+                            rusqlite::Error::FromSqlConversionFailure(
+                                0,
+                                Type::Text,
+                                Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)),
+                            )
                         })?;
 
-                    drop(conn);
+                    self.set_current_pin_key(key.clone());
 
-                    match self.complete_migration(pin) {
-                        Ok(_) => {}
-                        Err(e) => {
-                            error!("Failed to complete migration: {}", e);
-                        }
+                    // Plaintext credentials from the initial setup and ciphertexts
+                    // from older versions are re-encrypted on the first unlock
+                    if let Err(e) = self.complete_migration(&key) {
+                        error!("Failed to complete migration: {}", e);
                     }
 
-                    let key = self.derive_encryption_key(pin, &salt).map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(
-                            0,
-                            Type::Text,
-                            Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)),
-                        )
-                    })?;
-
-                    self.set_current_pin_key(key);
+                    if let Err(e) = self.upgrade_legacy_encryption(pin, &key) {
+                        error!("Failed to upgrade legacy encryption: {}", e);
+                    }
                 }
 
                 Ok(result)
@@ -1060,8 +1554,8 @@ impl Database {
         let salt = SaltString::generate(&mut OsRng).to_string();
 
         conn.execute(
-            "INSERT OR REPLACE INTO app_settings (id, password_hash, pin_salt) VALUES (1, ?1, ?2)",
-            params![new_hash, salt],
+            "INSERT OR REPLACE INTO app_settings (id, password_hash, pin_salt, kdf_version) VALUES (1, ?1, ?2, ?3)",
+            params![new_hash, salt, KDF_VERSION_ARGON2],
         )?;
 
         let mut current_key = self.current_pin_key.lock().unwrap();
@@ -1081,77 +1575,128 @@ impl Database {
 
         log::info!("Current PIN verified successfully, proceeding with PIN update");
 
-        // Get all API profiles to re-encrypt
-        let api_profiles = self
-            .list_api_profiles()
-            .map_err(|e| format!("Failed to list API profiles: {}", e))?;
-
-        // Get the decrypted profiles using the current PIN
-        let mut decrypted_profiles = Vec::new();
-        for profile in &api_profiles {
-            match self.get_api_info(Some(&profile.profile_name)) {
-                Ok(Some(api_info)) => {
-                    log::info!(
-                        "Successfully retrieved credentials for profile: {}",
-                        profile.profile_name
-                    );
-                    decrypted_profiles.push(api_info);
-                }
-                _ => {
-                    log::warn!(
-                        "Failed to get credentials for profile: {}",
-                        profile.profile_name
-                    );
-                }
-            }
-        }
+        let old_key = self.current_encryption_key()?;
 
         // Generate the new PIN hash
         let new_hash =
             Self::hash_password(new_pin).map_err(|e| format!("Failed to hash new PIN: {}", e))?;
 
-        // Update the PIN hash in the database
-        {
-            let conn = self.conn.lock().unwrap();
-            conn.execute(
-                "UPDATE app_settings SET password_hash = ? WHERE id = 1",
-                params![new_hash],
+        // A fresh salt means the new key shares nothing with the old one
+        let new_salt = SaltString::generate(&mut OsRng).to_string();
+        let new_key = self.derive_encryption_key(new_pin, &new_salt)?;
+
+        let mut conn = self.conn.lock().unwrap();
+
+        let has_encrypted_api_key_column: bool = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('api_info') WHERE name='encrypted_api_key'",
+                [],
+                |row| {
+                    let count: i64 = row.get(0)?;
+                    Ok(count > 0)
+                },
             )
-            .map_err(|e| format!("Failed to update PIN hash: {}", e))?;
-        }
+            .map_err(|e| format!("Failed to check for encrypted_api_key column: {}", e))?;
+
+        // Everything below commits together, so a failure part way leaves
+        // the old PIN and every secret encrypted with it
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+        if has_encrypted_api_key_column {
+            let rows = {
+                let mut stmt = tx
+                    .prepare("SELECT id, profile_name, encrypted_api_key, api_key_nonce, encrypted_api_secret, api_secret_nonce FROM api_info")
+                    .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((
+                            row.get::<_, i64>(0)?,
+                            row.get::<_, String>(1)?,
+                            row.get::<_, Vec<u8>>(2)?,
+                            row.get::<_, Vec<u8>>(3)?,
+                            row.get::<_, Vec<u8>>(4)?,
+                            row.get::<_, Vec<u8>>(5)?,
+                        ))
+                    })
+                    .map_err(|e| format!("Failed to query encrypted data: {}", e))?;
+
+                rows.collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("Failed to collect profiles: {}", e))?
+            };
 
-        // Clear encryption key from memory
-        {
-            let mut current_key = self.current_pin_key.lock().unwrap();
-            *current_key = None;
+            log::info!("Re-encrypting {} profiles with new PIN", rows.len());
+
+            for (id, profile_name, api_key, api_key_nonce, api_secret, api_secret_nonce) in rows {
+                let api_key = self.decrypt_string(&api_key, &api_key_nonce, &old_key)?;
+                let api_secret = self.decrypt_string(&api_secret, &api_secret_nonce, &old_key)?;
+
+                let (encrypted_api_key, api_key_nonce) = self.encrypt_string(&api_key, &new_key)?;
+                let (encrypted_api_secret, api_secret_nonce) =
+                    self.encrypt_string(&api_secret, &new_key)?;
+
+                tx.execute(
+                    "UPDATE api_info SET encrypted_api_key = ?1, api_key_nonce = ?2,
+                     encrypted_api_secret = ?3, api_secret_nonce = ?4 WHERE id = ?5",
+                    params![
+                        encrypted_api_key,
+                        api_key_nonce,
+                        encrypted_api_secret,
+                        api_secret_nonce,
+                        id
+                    ],
+                )
+                .map_err(|e| format!("Failed to update profile '{}': {}", profile_name, e))?;
+            }
         }
 
-        // Temporarily store the new PIN in the cache for re-encryption
-        let old_pin = self.pin_cache.get_pin().clone(); // Save old PIN value
-        self.pin_cache.set_pin(new_pin.to_string()); // Set new PIN for re-encryption
+        let headers = {
+            let mut stmt = tx
+                .prepare("SELECT profile_id, name, encrypted_value, value_nonce FROM profile_headers")
+                .map_err(|e| format!("Failed to prepare statement: {}", e))?;
 
-        log::info!(
-            "Re-saving {} profiles with new PIN",
-            decrypted_profiles.len()
-        );
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Vec<u8>>(2)?,
+                        row.get::<_, Vec<u8>>(3)?,
+                    ))
+                })
+                .map_err(|e| format!("Failed to query profile headers: {}", e))?;
 
-        // Re-encrypt all API profiles with the new PIN
-        for api_info in decrypted_profiles {
-            self.save_api_info(&api_info).map_err(|e| {
-                // If we fail, restore the old PIN in the cache
-                if let Some(old) = &old_pin {
-                    self.pin_cache.set_pin(old.clone());
-                }
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Failed to collect profile headers: {}", e))?
+        };
 
-                format!(
-                    "Failed to save API info for profile '{}': {}",
-                    api_info.profile_name, e
-                )
-            })?;
+        for (profile_id, name, value, nonce) in headers {
+            let value = self.decrypt_string(&value, &nonce, &old_key)?;
+            let (encrypted_value, value_nonce) = self.encrypt_string(&value, &new_key)?;
 
-            log::info!("Re-saved profile: {}", api_info.profile_name);
+            tx.execute(
+                "UPDATE profile_headers SET encrypted_value = ?1, value_nonce = ?2
+                 WHERE profile_id = ?3 AND name = ?4",
+                params![encrypted_value, value_nonce, profile_id, name],
+            )
+            .map_err(|e| format!("Failed to update header '{}': {}", name, e))?;
         }
 
+        tx.execute(
+            "UPDATE app_settings SET password_hash = ?1, pin_salt = ?2, kdf_version = ?3 WHERE id = 1",
+            params![new_hash, new_salt, KDF_VERSION_ARGON2],
+        )
+        .map_err(|e| format!("Failed to update PIN hash: {}", e))?;
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit PIN update: {}", e))?;
+        drop(conn);
+
+        self.set_current_pin_key(new_key);
+        self.pin_cache.set_pin(new_pin.to_string());
+
         log::info!("PIN updated successfully. All API credentials re-encrypted with new PIN");
 
         Ok(())
@@ -1214,4 +1759,360 @@ impl Database {
         tx.commit()?;
         Ok(())
     }
+    pub fn get_timeout_overrides(&self, profile_id: i64) -> Result<HashMap<String, u64>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt =
+            conn.prepare("SELECT category, seconds FROM profile_timeouts WHERE profile_id = ?1")?;
+
+        let rows = stmt.query_map([profile_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64))
+        })?;
+
+        rows.collect()
+    }
+
+    pub fn save_timeout_overrides(
+        &self,
+        profile_id: i64,
+        timeouts: &HashMap<String, u64>,
+    ) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "DELETE FROM profile_timeouts WHERE profile_id = ?1",
+            [profile_id],
+        )?;
+
+        for (category, seconds) in timeouts {
+            tx.execute(
+                "INSERT INTO profile_timeouts (profile_id, category, seconds) VALUES (?1, ?2, ?3)",
+                params![profile_id, category, *seconds as i64],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Headers that can't be decrypted, e.g. before the PIN is entered, are
+    /// left out
+    pub fn get_profile_headers(&self, profile_id: i64) -> Result<HashMap<String, String>> {
+        let Ok(key) = self.current_encryption_key() else {
+            return Ok(HashMap::new());
+        };
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT name, encrypted_value, value_nonce FROM profile_headers WHERE profile_id = ?1",
+        )?;
+
+        let rows = stmt.query_map([profile_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Vec<u8>>(1)?,
+                row.get::<_, Vec<u8>>(2)?,
+            ))
+        })?;
+
+        let mut headers = HashMap::new();
+        for row in rows {
+            let (name, ciphertext, nonce) = row?;
+            match self.decrypt_string(&ciphertext, &nonce, &key) {
+                Ok(value) => {
+                    headers.insert(name, value);
+                }
+                Err(e) => error!("Failed to decrypt header {}: {}", name, e),
+            }
+        }
+
+        Ok(headers)
+    }
+
+    pub fn save_profile_headers(
+        &self,
+        profile_id: i64,
+        headers: &HashMap<String, String>,
+    ) -> Result<(), String> {
+        let key = self.current_encryption_key()?;
+
+        let mut encrypted = Vec::with_capacity(headers.len());
+        for (name, value) in headers {
+            let (ciphertext, nonce) = self.encrypt_string(value, &key)?;
+            encrypted.push((name, ciphertext, nonce));
+        }
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+        tx.execute(
+            "DELETE FROM profile_headers WHERE profile_id = ?1",
+            [profile_id],
+        )
+        .map_err(|e| e.to_string())?;
+
+        for (name, ciphertext, nonce) in encrypted {
+            tx.execute(
+                "INSERT INTO profile_headers (profile_id, name, encrypted_value, value_nonce)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![profile_id, name, ciphertext, nonce],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    pub fn resave_profile_settings(&self, api_info: &ApiInfo) -> Result<(), String> {
+        let profile_id: i64 = {
+            let conn = self.conn.lock().unwrap();
+            conn.query_row(
+                "SELECT id FROM api_info WHERE profile_name = ?1",
+                params![api_info.profile_name],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?
+        };
+
+        // Favorites aren't part of ApiInfo, so move them from the old id
+        if profile_id != api_info.id {
+            let conn = self.conn.lock().unwrap();
+            conn.execute(
+                "UPDATE favorites SET profile_id = ?1 WHERE profile_id = ?2",
+                params![profile_id, api_info.id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        self.save_timeout_overrides(profile_id, &api_info.timeouts)
+            .map_err(|e| e.to_string())?;
+        self.save_profile_headers(profile_id, &api_info.extra_headers)
+    }
+
+    /// The stored log level name, or `None` when the user hasn't chosen one
+    pub fn get_log_level(&self) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.query_row("SELECT level FROM log_settings WHERE id = 1", [], |row| {
+            row.get(0)
+        })
+        .optional()
+    }
+
+    pub fn save_log_level(&self, level: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "INSERT OR REPLACE INTO log_settings (id, level) VALUES (1, ?1)",
+            params![level],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_alert_monitor_settings(&self) -> Result<AlertMonitorSettings> {
+        let conn = self.conn.lock().unwrap();
+
+        let settings = conn
+            .query_row(
+                "SELECT enabled, interval_seconds FROM alert_monitor_settings WHERE id = 1",
+                [],
+                |row| {
+                    Ok(AlertMonitorSettings {
+                        enabled: row.get(0)?,
+                        interval_seconds: row.get(1)?,
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(settings.unwrap_or_default())
+    }
+
+    pub fn save_alert_monitor_settings(&self, settings: &AlertMonitorSettings) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "INSERT OR REPLACE INTO alert_monitor_settings (id, enabled, interval_seconds)
+             VALUES (1, ?1, ?2)",
+            params![settings.enabled, settings.interval_seconds],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_scheduled_reboots(&self) -> Result<Vec<ScheduledReboot>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn
+            .prepare("SELECT profile_name, reboot_at FROM scheduled_reboots ORDER BY reboot_at")?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(ScheduledReboot {
+                profile_name: row.get(0)?,
+                reboot_at: row.get(1)?,
+            })
+        })?;
+
+        rows.collect()
+    }
+
+    // One schedule per profile, a new one replaces the previous
+    pub fn save_scheduled_reboot(&self, reboot: &ScheduledReboot) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "INSERT OR REPLACE INTO scheduled_reboots (profile_name, reboot_at) VALUES (?1, ?2)",
+            params![reboot.profile_name, reboot.reboot_at],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn delete_scheduled_reboot(&self, profile_name: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+
+        let removed = conn.execute(
+            "DELETE FROM scheduled_reboots WHERE profile_name = ?1",
+            params![profile_name],
+        )?;
+
+        Ok(removed > 0)
+    }
+
+    pub fn get_favorites(&self, profile_id: i64) -> Result<Vec<Favorite>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT kind, item_id, label, created_at FROM favorites
+             WHERE profile_id = ?1 ORDER BY created_at",
+        )?;
+
+        let rows = stmt.query_map([profile_id], |row| {
+            Ok(Favorite {
+                kind: row.get(0)?,
+                item_id: row.get(1)?,
+                label: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?;
+
+        rows.collect()
+    }
+
+    // Pinning an item again only updates its label
+    pub fn add_favorite(
+        &self,
+        profile_id: i64,
+        kind: &str,
+        item_id: &str,
+        label: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "INSERT INTO favorites (profile_id, kind, item_id, label, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(profile_id, kind, item_id) DO UPDATE SET label = excluded.label",
+            params![
+                profile_id,
+                kind,
+                item_id,
+                label,
+                chrono::Utc::now().to_rfc3339()
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns whether the item was pinned
+    pub fn remove_favorite(&self, profile_id: i64, kind: &str, item_id: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+
+        let removed = conn.execute(
+            "DELETE FROM favorites WHERE profile_id = ?1 AND kind = ?2 AND item_id = ?3",
+            params![profile_id, kind, item_id],
+        )?;
+
+        Ok(removed > 0)
+    }
+
+    // The entry is attributed to the profile that is the default at the time
+    pub fn insert_audit_entry(
+        &self,
+        command: &str,
+        target: &str,
+        outcome: &str,
+        error: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "INSERT INTO audit_log (timestamp, profile_id, command, target, outcome, error)
+             VALUES (?1, (SELECT id FROM api_info WHERE is_default = 1), ?2, ?3, ?4, ?5)",
+            params![
+                chrono::Utc::now().to_rfc3339(),
+                command,
+                target,
+                outcome,
+                error
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Services whose most recent successful start, stop or restart from
+    /// this app on the profile was a stop
+    pub fn get_stopped_services(&self, profile_id: i64) -> Result<HashSet<String>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT command, target FROM audit_log
+             WHERE profile_id = ?1 AND outcome = 'success'
+               AND command IN ('start_service', 'stop_service', 'restart_service')
+             ORDER BY id",
+        )?;
+
+        let rows = stmt.query_map([profile_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut stopped = HashSet::new();
+        for row in rows {
+            let (command, target) = row?;
+            if command == "stop_service" {
+                stopped.insert(target);
+            } else {
+                stopped.remove(&target);
+            }
+        }
+
+        Ok(stopped)
+    }
+
+    pub fn get_audit_log(&self, limit: u32) -> Result<Vec<AuditEntry>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT id, timestamp, profile_id, command, target, outcome, error
+             FROM audit_log ORDER BY id DESC LIMIT ?1",
+        )?;
+
+        let rows = stmt.query_map([limit], |row| {
+            Ok(AuditEntry {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                profile_id: row.get(2)?,
+                command: row.get(3)?,
+                target: row.get(4)?,
+                outcome: row.get(5)?,
+                error: row.get(6)?,
+            })
+        })?;
+
+        rows.collect()
+    }
 }
@@ -1,9 +1,11 @@
-use crate::db::Database;
+use crate::db::{ApiInfo, Database};
+use crate::error::ApiError;
 use crate::http_client::make_http_request;
 use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
 use tauri::State;
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -21,6 +23,15 @@ pub struct Device {
     intf_description: String,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DeviceSearchResponse {
+    total: u32,
+    #[serde(rename = "rowCount")]
+    row_count: u32,
+    current: u32,
+    rows: Vec<Device>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct NdpDevice {
     mac: String,
@@ -38,6 +49,12 @@ pub struct NdpResponse {
     rows: Vec<NdpDevice>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeviceInterface {
+    intf: String,
+    intf_description: String,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CombinedDevice {
     mac: String,
@@ -51,6 +68,38 @@ pub struct CombinedDevice {
     manufacturer: String,
     hostname: String,
     intf_description: String,
+    // Every interface the MAC was seen on; intf/intf_description hold the first one
+    interfaces: Vec<DeviceInterface>,
+    // From the DHCP lease with the same MAC, when there is one
+    lease_hostname: Option<String>,
+    lease_status: Option<String>,
+    lease_expires: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CombinedDeviceSearchResponse {
+    total: u32,
+    #[serde(rename = "rowCount")]
+    row_count: u32,
+    current: u32,
+    rows: Vec<CombinedDevice>,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct InterfaceDeviceCount {
+    intf: String,
+    intf_description: String,
+    arp_entries: u32,
+    ndp_entries: u32,
+    unique_macs: u32,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DeviceCounts {
+    arp_entries: u32,
+    ndp_entries: u32,
+    unique_macs: u32,
+    interfaces: Vec<InterfaceDeviceCount>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -62,50 +111,220 @@ fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
     format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
 }
 
+fn profile_api_info(database: &Database, profile_id: Option<i64>) -> Result<ApiInfo, ApiError> {
+    database
+        .get_profile_api_info(profile_id)
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| ApiError::Other("API info not found".to_string()))
+}
+
 fn is_ipv6(ip: &str) -> bool {
     ip.contains(':')
 }
 
+fn add_interface(device: &mut CombinedDevice, intf: String, intf_description: String) {
+    if !device.interfaces.iter().any(|existing| existing.intf == intf) {
+        device.interfaces.push(DeviceInterface {
+            intf,
+            intf_description,
+        });
+    }
+}
+
+// Merges ARP and NDP entries per MAC, keeping every interface a MAC was
+// seen on. With `known_macs_only` NDP entries only add to MACs from the ARP
+// entries.
+fn merge_devices(
+    arp_devices: Vec<Device>,
+    ndp_devices: Vec<NdpDevice>,
+    known_macs_only: bool,
+) -> HashMap<String, CombinedDevice> {
+    // Pre-allocate hash map with capacity to avoid reallocations
+    let mut device_map: HashMap<String, CombinedDevice> =
+        HashMap::with_capacity(arp_devices.len() + ndp_devices.len());
+
+    // Process ARP devices first
+    for device in arp_devices {
+        if let Some(existing_device) = device_map.get_mut(&device.mac) {
+            // Update existing device
+            if is_ipv6(&device.ip) {
+                if !existing_device.ipv6_addresses.contains(&device.ip) {
+                    existing_device.ipv6_addresses.push(device.ip);
+                }
+            } else if !existing_device.ipv4_addresses.contains(&device.ip) {
+                existing_device.ipv4_addresses.push(device.ip);
+            }
+
+            if existing_device.hostname.is_empty() && !device.hostname.is_empty() {
+                existing_device.hostname = device.hostname;
+            }
+
+            add_interface(existing_device, device.intf, device.intf_description);
+        } else {
+            // Create new device entry
+            let mut ipv4_addresses = Vec::with_capacity(1);
+            let mut ipv6_addresses = Vec::with_capacity(1);
+
+            if is_ipv6(&device.ip) {
+                ipv6_addresses.push(device.ip);
+            } else {
+                ipv4_addresses.push(device.ip);
+            }
+
+            let interfaces = vec![DeviceInterface {
+                intf: device.intf.clone(),
+                intf_description: device.intf_description.clone(),
+            }];
+
+            device_map.insert(
+                device.mac.clone(),
+                CombinedDevice {
+                    mac: device.mac,
+                    ipv4_addresses,
+                    ipv6_addresses,
+                    intf: device.intf,
+                    expired: Some(device.expired),
+                    expires: Some(device.expires),
+                    permanent: Some(device.permanent),
+                    device_type: Some(device.device_type),
+                    manufacturer: device.manufacturer,
+                    hostname: device.hostname,
+                    intf_description: device.intf_description,
+                    interfaces,
+                    lease_hostname: None,
+                    lease_status: None,
+                    lease_expires: None,
+                },
+            );
+        }
+    }
+
+    // A page only holds the devices from its slice of the ARP table
+    let ndp_devices = ndp_devices
+        .into_iter()
+        .filter(|device| !known_macs_only || device_map.contains_key(&device.mac))
+        .collect::<Vec<_>>();
+
+    // Process NDP devices
+    for device in ndp_devices {
+        if let Some(existing_device) = device_map.get_mut(&device.mac) {
+            if is_ipv6(&device.ip) {
+                if !existing_device.ipv6_addresses.contains(&device.ip) {
+                    existing_device.ipv6_addresses.push(device.ip);
+                }
+            } else if !existing_device.ipv4_addresses.contains(&device.ip) {
+                existing_device.ipv4_addresses.push(device.ip);
+            }
+
+            if existing_device.manufacturer.is_empty() && !device.manufacturer.is_empty() {
+                existing_device.manufacturer = device.manufacturer;
+            }
+
+            add_interface(existing_device, device.intf, device.intf_description);
+        } else {
+            let mut ipv4_addresses = Vec::with_capacity(1);
+            let mut ipv6_addresses = Vec::with_capacity(1);
+
+            if is_ipv6(&device.ip) {
+                ipv6_addresses.push(device.ip);
+            } else {
+                ipv4_addresses.push(device.ip);
+            }
+
+            let interfaces = vec![DeviceInterface {
+                intf: device.intf.clone(),
+                intf_description: device.intf_description.clone(),
+            }];
+
+            device_map.insert(
+                device.mac.clone(),
+                CombinedDevice {
+                    mac: device.mac,
+                    ipv4_addresses,
+                    ipv6_addresses,
+                    intf: device.intf,
+                    expired: None,
+                    expires: None,
+                    permanent: None,
+                    device_type: None,
+                    manufacturer: device.manufacturer,
+                    hostname: String::new(),
+                    intf_description: device.intf_description,
+                    interfaces,
+                    lease_hostname: None,
+                    lease_status: None,
+                    lease_expires: None,
+                },
+            );
+        }
+    }
+
+    device_map
+}
+
+// Omitting the paging arguments returns every entry in a single page
 #[tauri::command]
-pub async fn get_devices(database: State<'_, Database>) -> Result<Vec<Device>, String> {
-    let api_info = database
-        .get_default_api_info()
-        .map_err(|e| format!("Failed to get API info: {}", e))?
-        .ok_or_else(|| "API info not found".to_string())?;
+pub async fn get_devices(
+    database: State<'_, Database>,
+    current_page: Option<u32>,
+    rows_per_page: Option<u32>,
+    search_phrase: Option<String>,
+) -> Result<DeviceSearchResponse, ApiError> {
+    let api_info = profile_api_info(&database, None)?;
+    search_arp_devices(&api_info, current_page, rows_per_page, search_phrase).await
+}
 
-    let url = build_api_url(&api_info, "/api/diagnostics/interface/getArp");
+async fn search_arp_devices(
+    api_info: &ApiInfo,
+    current_page: Option<u32>,
+    rows_per_page: Option<u32>,
+    search_phrase: Option<String>,
+) -> Result<DeviceSearchResponse, ApiError> {
+    let url = build_api_url(api_info, "/api/diagnostics/interface/search_arp/");
+
+    let payload = json!({
+        "current": current_page.unwrap_or(1),
+        "rowCount": rows_per_page.map(i64::from).unwrap_or(-1),
+        "sort": {},
+        "searchPhrase": search_phrase.unwrap_or_default()
+    });
 
     let response = make_http_request(
-        "GET",
+        "POST",
         &url,
+        Some(payload),
         None,
-        None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
-        .json::<Vec<Device>>()
+        .json::<DeviceSearchResponse>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
 
 #[tauri::command]
-pub async fn get_ndp_devices(database: State<'_, Database>) -> Result<Vec<NdpDevice>, String> {
-    let api_info = database
-        .get_default_api_info()
-        .map_err(|e| format!("Failed to get API info: {}", e))?
-        .ok_or_else(|| "API info not found".to_string())?;
+pub async fn get_ndp_devices(database: State<'_, Database>) -> Result<Vec<NdpDevice>, ApiError> {
+    let api_info = profile_api_info(&database, None)?;
+    search_ndp_devices(&api_info, String::new()).await
+}
 
-    let url = build_api_url(&api_info, "/api/diagnostics/interface/search_ndp/");
+async fn search_ndp_devices(
+    api_info: &ApiInfo,
+    search_phrase: String,
+) -> Result<Vec<NdpDevice>, ApiError> {
+    let url = build_api_url(api_info, "/api/diagnostics/interface/search_ndp/");
 
+    // -1 returns every neighbour; merging by MAC needs all of them
     let payload = json!({
         "current": 1,
-        "rowCount": 1000,
+        "rowCount": -1,
         "sort": {},
-        "searchPhrase": ""
+        "searchPhrase": search_phrase
     });
 
     let response = make_http_request(
@@ -113,9 +332,10 @@ pub async fn get_ndp_devices(database: State<'_, Database>) -> Result<Vec<NdpDev
         &url,
         Some(payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -127,20 +347,30 @@ pub async fn get_ndp_devices(database: State<'_, Database>) -> Result<Vec<NdpDev
     Ok(ndp_response.rows)
 }
 
+/// ARP and NDP entries merged per MAC. With `rows_per_page` the firewall
+/// pages the ARP table and only the neighbours of the devices on that page
+/// are merged in, so IPv6-only neighbours are left to `get_ndp_devices` and
+/// `total` counts ARP entries. Without it every device is returned in a
+/// single page.
 #[tauri::command]
 pub async fn get_combined_devices(
     database: State<'_, Database>,
-) -> Result<Vec<CombinedDevice>, String> {
+    current_page: Option<u32>,
+    rows_per_page: Option<u32>,
+    search_phrase: Option<String>,
+    profile_id: Option<i64>,
+) -> Result<CombinedDeviceSearchResponse, ApiError> {
+    let api_info = profile_api_info(&database, profile_id)?;
+
     // Start time tracking for performance monitoring
     let start_time = std::time::Instant::now();
 
     // Create an async function to fetch devices with timeout
-    async fn fetch_with_timeout<T, E>(
+    async fn fetch_with_timeout<T, E: Into<ApiError>>(
         fut: impl std::future::Future<Output = Result<T, E>>,
         timeout_seconds: u64,
-        default: T,
         data_type: &str,
-    ) -> Result<T, E> {
+    ) -> Result<T, ApiError> {
         let fetch_start = std::time::Instant::now();
 
         match tokio::time::timeout(std::time::Duration::from_secs(timeout_seconds), fut).await {
@@ -152,15 +382,12 @@ pub async fn get_combined_devices(
                     data_type,
                     fetch_duration.as_millis()
                 );
-                result
+                result.map_err(Into::into)
             }
             Err(_) => {
-                let error_msg = format!(
-                    "{} fetch timed out after {} seconds",
-                    data_type, timeout_seconds
-                );
+                let error_msg = format!("{} fetch timed out after {}s", data_type, timeout_seconds);
                 warn!("{}", error_msg);
-                Ok(default)
+                Err(ApiError::Timeout(error_msg))
             }
         }
     }
@@ -168,19 +395,39 @@ pub async fn get_combined_devices(
     // Set timeouts
     let main_timeout = 30; // 30 seconds for main fetch
 
+    let rows_per_page = rows_per_page.filter(|rows| *rows > 0);
+    let current = current_page.unwrap_or(1).max(1);
+
     // First, try to fetch both in parallel with the main timeout
-    let arp_future = get_devices(database.clone());
-    let ndp_future = get_ndp_devices(database.clone());
+    let search_phrase = search_phrase.unwrap_or_default();
+    let arp_future = async {
+        search_arp_devices(
+            &api_info,
+            rows_per_page.map(|_| current),
+            rows_per_page,
+            Some(search_phrase.clone()),
+        )
+        .await
+        .map(|response| (response.total, response.rows))
+    };
+    let ndp_future = search_ndp_devices(&api_info, search_phrase.clone());
+    let lease_future = async { Ok::<_, String>(crate::dhcp::leases_by_mac(&api_info).await) };
 
     // Start the futures in parallel
-    let (arp_result, ndp_result) = tokio::join!(
-        fetch_with_timeout(arp_future, main_timeout, Vec::new(), "ARP"),
-        fetch_with_timeout(ndp_future, main_timeout, Vec::new(), "NDP")
+    let (arp_result, ndp_result, lease_result) = tokio::join!(
+        fetch_with_timeout(arp_future, main_timeout, "ARP"),
+        fetch_with_timeout(ndp_future, main_timeout, "NDP"),
+        fetch_with_timeout(lease_future, main_timeout, "DHCP lease")
     );
 
-    // Check for errors and extract results
-    let arp_devices = arp_result?;
-    let ndp_devices = ndp_result?;
+    // Check for errors and extract results, naming the table that failed.
+    // Timeouts already name it and keep their kind.
+    let named = |table: &str, e: ApiError| match e {
+        ApiError::Timeout(_) => e,
+        e => ApiError::from(format!("Failed to fetch {} table: {}", table, e)),
+    };
+    let (arp_total, arp_devices) = arp_result.map_err(|e| named("ARP", e))?;
+    let ndp_devices = ndp_result.map_err(|e| named("NDP", e))?;
 
     // Store the counts for later use
     let arp_count = arp_devices.len();
@@ -191,95 +438,29 @@ pub async fn get_combined_devices(
         arp_count, ndp_count
     );
 
-    // Pre-allocate hash map with capacity to avoid reallocations
-    let mut device_map: HashMap<String, CombinedDevice> =
-        HashMap::with_capacity(arp_devices.len() + ndp_devices.len());
+    let mut device_map = merge_devices(arp_devices, ndp_devices, rows_per_page.is_some());
 
-    // Process ARP devices first
-    for device in arp_devices {
-        if let Some(existing_device) = device_map.get_mut(&device.mac) {
-            // Update existing device
-            if is_ipv6(&device.ip) {
-                if !existing_device.ipv6_addresses.contains(&device.ip) {
-                    existing_device.ipv6_addresses.push(device.ip);
+    // Many clients have no PTR record but did send a hostname with their lease
+    let leases = lease_result?;
+    for device in device_map.values_mut() {
+        if let Some(lease) = leases.get(&device.mac.to_lowercase()) {
+            if !lease.hostname.is_empty() {
+                if device.hostname.is_empty() {
+                    device.hostname = lease.hostname.clone();
                 }
-            } else if !existing_device.ipv4_addresses.contains(&device.ip) {
-                existing_device.ipv4_addresses.push(device.ip);
-            }
-
-            if existing_device.hostname.is_empty() && !device.hostname.is_empty() {
-                existing_device.hostname = device.hostname;
+                device.lease_hostname = Some(lease.hostname.clone());
             }
-        } else {
-            // Create new device entry
-            let mut ipv4_addresses = Vec::with_capacity(1);
-            let mut ipv6_addresses = Vec::with_capacity(1);
-
-            if is_ipv6(&device.ip) {
-                ipv6_addresses.push(device.ip);
-            } else {
-                ipv4_addresses.push(device.ip);
-            }
-
-            device_map.insert(
-                device.mac.clone(),
-                CombinedDevice {
-                    mac: device.mac,
-                    ipv4_addresses,
-                    ipv6_addresses,
-                    intf: device.intf,
-                    expired: Some(device.expired),
-                    expires: Some(device.expires),
-                    permanent: Some(device.permanent),
-                    device_type: Some(device.device_type),
-                    manufacturer: device.manufacturer,
-                    hostname: device.hostname,
-                    intf_description: device.intf_description,
-                },
-            );
+            device.lease_status = Some(lease.status.clone());
+            device.lease_expires = Some(lease.ends.clone()).filter(|ends| !ends.is_empty());
         }
     }
 
-    // Process NDP devices
-    for device in ndp_devices {
-        if let Some(existing_device) = device_map.get_mut(&device.mac) {
-            if is_ipv6(&device.ip) {
-                if !existing_device.ipv6_addresses.contains(&device.ip) {
-                    existing_device.ipv6_addresses.push(device.ip);
-                }
-            } else if !existing_device.ipv4_addresses.contains(&device.ip) {
-                existing_device.ipv4_addresses.push(device.ip);
-            }
-
-            if existing_device.manufacturer.is_empty() && !device.manufacturer.is_empty() {
-                existing_device.manufacturer = device.manufacturer;
-            }
-        } else {
-            let mut ipv4_addresses = Vec::with_capacity(1);
-            let mut ipv6_addresses = Vec::with_capacity(1);
-
-            if is_ipv6(&device.ip) {
-                ipv6_addresses.push(device.ip);
-            } else {
-                ipv4_addresses.push(device.ip);
+    // The firewall's OUI database lags behind new hardware; only fill gaps
+    for device in device_map.values_mut() {
+        if device.manufacturer.is_empty() {
+            if let Some(vendor) = crate::oui::lookup(&device.mac) {
+                device.manufacturer = vendor.to_string();
             }
-
-            device_map.insert(
-                device.mac.clone(),
-                CombinedDevice {
-                    mac: device.mac,
-                    ipv4_addresses,
-                    ipv6_addresses,
-                    intf: device.intf,
-                    expired: None,
-                    expires: None,
-                    permanent: None,
-                    device_type: None,
-                    manufacturer: device.manufacturer,
-                    hostname: String::new(),
-                    intf_description: device.intf_description,
-                },
-            );
         }
     }
 
@@ -299,15 +480,24 @@ pub async fn get_combined_devices(
         .into_iter()
         .map(|(mac, mut device)| {
             // Check if device is on a CARP interface
-            let is_on_carp_iface = device.intf.contains("_vip")
-                || device.intf.starts_with("carp")
-                || device.intf_description.to_lowercase().contains("carp");
+            let is_carp_iface = |intf: &str, intf_description: &str| {
+                intf.contains("_vip")
+                    || intf.starts_with("carp")
+                    || intf_description.to_lowercase().contains("carp")
+            };
 
             // If it's on a CARP interface, tag it for special handling
-            if is_on_carp_iface {
+            if is_carp_iface(&device.intf, &device.intf_description) {
                 device.intf_description = format!("{} (CARP VIP)", device.intf_description);
             }
 
+            for interface in device.interfaces.iter_mut() {
+                if is_carp_iface(&interface.intf, &interface.intf_description) {
+                    interface.intf_description =
+                        format!("{} (CARP VIP)", interface.intf_description);
+                }
+            }
+
             (mac, device)
         })
         .collect::<HashMap<String, CombinedDevice>>();
@@ -366,7 +556,94 @@ pub async fn get_combined_devices(
         );
     }
 
-    Ok(combined_devices)
+    let total = match rows_per_page {
+        Some(_) => arp_total,
+        None => combined_devices.len() as u32,
+    };
+
+    Ok(CombinedDeviceSearchResponse {
+        total,
+        row_count: combined_devices.len() as u32,
+        current,
+        rows: combined_devices,
+    })
+}
+
+/// Table sizes for dashboard widgets. The tables are still fetched in full,
+/// but only the counts are sent to the frontend.
+#[tauri::command]
+pub async fn get_device_counts(
+    database: State<'_, Database>,
+    profile_id: Option<i64>,
+) -> Result<DeviceCounts, ApiError> {
+    let api_info = profile_api_info(&database, profile_id)?;
+
+    let (arp_devices, ndp_devices) = tokio::try_join!(
+        async {
+            search_arp_devices(&api_info, None, None, None)
+                .await
+                .map(|response| response.rows)
+                .map_err(|e| format!("Failed to fetch ARP table: {}", e))
+        },
+        async {
+            search_ndp_devices(&api_info, String::new())
+                .await
+                .map_err(|e| format!("Failed to fetch NDP table: {}", e))
+        }
+    )?;
+
+    let mut interfaces: HashMap<String, InterfaceDeviceCount> = HashMap::new();
+    let mut interface_macs: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut macs = HashSet::new();
+
+    let entries = arp_devices
+        .iter()
+        .map(|device| (&device.mac, &device.intf, &device.intf_description, true))
+        .chain(
+            ndp_devices
+                .iter()
+                .map(|device| (&device.mac, &device.intf, &device.intf_description, false)),
+        );
+
+    for (mac, intf, intf_description, is_arp) in entries {
+        let count = interfaces
+            .entry(intf.clone())
+            .or_insert_with(|| InterfaceDeviceCount {
+                intf: intf.clone(),
+                intf_description: intf_description.clone(),
+                ..Default::default()
+            });
+        if is_arp {
+            count.arp_entries += 1;
+        } else {
+            count.ndp_entries += 1;
+        }
+
+        let mac = mac.to_lowercase();
+        interface_macs
+            .entry(intf.clone())
+            .or_default()
+            .insert(mac.clone());
+        macs.insert(mac);
+    }
+
+    let mut interfaces: Vec<InterfaceDeviceCount> = interfaces
+        .into_values()
+        .map(|mut count| {
+            count.unique_macs = interface_macs
+                .get(&count.intf)
+                .map_or(0, |macs| macs.len() as u32);
+            count
+        })
+        .collect();
+    interfaces.sort_by(|a, b| a.intf.cmp(&b.intf));
+
+    Ok(DeviceCounts {
+        arp_entries: arp_devices.len() as u32,
+        ndp_entries: ndp_devices.len() as u32,
+        unique_macs: macs.len() as u32,
+        interfaces,
+    })
 }
 
 fn natural_sort(a: &str, b: &str) -> std::cmp::Ordering {
@@ -391,7 +668,19 @@ fn natural_sort(a: &str, b: &str) -> std::cmp::Ordering {
 }
 
 #[tauri::command]
-pub async fn flush_arp_table(database: State<'_, Database>) -> Result<FlushArpResponse, String> {
+pub async fn flush_arp_table(database: State<'_, Database>) -> Result<FlushArpResponse, ApiError> {
+    crate::audit::audited(
+        &database,
+        "flush_arp_table",
+        "",
+        flush_arp_table_request(database.clone()),
+    )
+    .await
+}
+
+async fn flush_arp_table_request(
+    database: State<'_, Database>,
+) -> Result<FlushArpResponse, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -404,9 +693,10 @@ pub async fn flush_arp_table(database: State<'_, Database>) -> Result<FlushArpRe
         &url,
         Some(json!({})),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -415,11 +705,113 @@ pub async fn flush_arp_table(database: State<'_, Database>) -> Result<FlushArpRe
         .await
         .map_err(|e| format!("Failed to get response body: {}", e))?;
 
-    let deleted: Vec<String> = body
-        .lines()
-        .map(|line| line.split_whitespace().next().unwrap_or("").to_string())
-        .filter(|ip| !ip.is_empty())
-        .collect();
+    Ok(FlushArpResponse {
+        deleted: parse_deleted_ips(&body),
+    })
+}
+
+// Newer firmware returns {"deleted": [...]}, older firmware returns the raw
+// `arp -d` output with one "<ip> (<ip>) deleted" line per entry
+fn parse_deleted_ips(body: &str) -> Vec<String> {
+    if let Ok(parsed) = serde_json::from_str::<FlushArpResponse>(body) {
+        return parsed
+            .deleted
+            .into_iter()
+            .filter(|ip| ip.parse::<IpAddr>().is_ok())
+            .collect();
+    }
+
+    body.lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|token| token.parse::<IpAddr>().is_ok())
+        .map(|ip| ip.to_string())
+        .collect()
+}
 
-    Ok(FlushArpResponse { deleted })
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arp(mac: &str, ip: &str, intf: &str) -> Device {
+        Device {
+            mac: mac.to_string(),
+            ip: ip.to_string(),
+            intf: intf.to_string(),
+            expired: false,
+            expires: 1200,
+            permanent: false,
+            device_type: "ethernet".to_string(),
+            manufacturer: String::new(),
+            hostname: String::new(),
+            intf_description: intf.to_uppercase(),
+        }
+    }
+
+    fn ndp(mac: &str, ip: &str, intf: &str) -> NdpDevice {
+        NdpDevice {
+            mac: mac.to_string(),
+            ip: ip.to_string(),
+            intf: intf.to_string(),
+            manufacturer: String::new(),
+            intf_description: intf.to_uppercase(),
+        }
+    }
+
+    #[test]
+    fn keeps_every_interface_of_a_mac() {
+        let devices = merge_devices(
+            vec![
+                arp("00:11:32:aa:bb:cc", "192.168.1.10", "igb0"),
+                arp("00:11:32:aa:bb:cc", "10.0.20.10", "vlan0.20"),
+                arp("00:11:32:aa:bb:cc", "192.168.1.10", "igb0"),
+            ],
+            vec![ndp("00:11:32:aa:bb:cc", "fe80::1", "igb0")],
+            false,
+        );
+
+        assert_eq!(devices.len(), 1);
+        let device = &devices["00:11:32:aa:bb:cc"];
+        let interfaces: Vec<&str> = device.interfaces.iter().map(|i| i.intf.as_str()).collect();
+        assert_eq!(interfaces, ["igb0", "vlan0.20"]);
+        assert_eq!(device.intf, "igb0");
+        assert_eq!(device.ipv4_addresses, ["192.168.1.10", "10.0.20.10"]);
+        assert_eq!(device.ipv6_addresses, ["fe80::1"]);
+    }
+
+    #[test]
+    fn known_macs_only_skips_ipv6_only_neighbours() {
+        let arp_devices = || vec![arp("00:11:32:aa:bb:cc", "192.168.1.10", "igb0")];
+        let ndp_devices = || {
+            vec![
+                ndp("00:11:32:aa:bb:cc", "fe80::1", "igb0"),
+                ndp("02:00:00:00:00:01", "fe80::2", "igb0"),
+            ]
+        };
+
+        assert_eq!(merge_devices(arp_devices(), ndp_devices(), false).len(), 2);
+
+        let devices = merge_devices(arp_devices(), ndp_devices(), true);
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices["00:11:32:aa:bb:cc"].ipv6_addresses, ["fe80::1"]);
+    }
+
+    #[test]
+    fn parses_deleted_ips_from_json() {
+        let body = r#"{"deleted": ["10.0.0.5", "(incomplete)", "10.0.0.7"]}"#;
+        assert_eq!(parse_deleted_ips(body), ["10.0.0.5", "10.0.0.7"]);
+    }
+
+    #[test]
+    fn parses_deleted_ips_from_arp_output() {
+        let body = "10.0.0.5 (10.0.0.5) deleted\n192.168.1.20 (192.168.1.20) deleted\n";
+        assert_eq!(parse_deleted_ips(body), ["10.0.0.5", "192.168.1.20"]);
+    }
+
+    #[test]
+    fn ignores_malformed_responses() {
+        assert!(parse_deleted_ips(r#"{"deleted":"#).is_empty());
+        assert!(parse_deleted_ips(r#"{"status": "ok"}"#).is_empty());
+        assert!(parse_deleted_ips("arp: cannot delete\n\n").is_empty());
+        assert!(parse_deleted_ips("").is_empty());
+    }
 }
@@ -0,0 +1,618 @@
+use crate::db::{ApiInfo, Database};
+use crate::error::ApiError;
+use crate::http_client::make_http_request;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use std::sync::{Mutex, OnceLock};
+use tauri::State;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DhcpBackend {
+    Isc,
+    Kea,
+}
+
+// Detected backend per profile, kept for the lifetime of the app
+static BACKENDS: OnceLock<Mutex<HashMap<i64, DhcpBackend>>> = OnceLock::new();
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DhcpLease {
+    address: String,
+    pub(crate) mac: String,
+    pub(crate) hostname: String,
+    interface: String,
+    interface_description: String,
+    starts: String,
+    pub(crate) ends: String,
+    lease_type: String,
+    state: String,
+    description: String,
+    manufacturer: String,
+    /// Normalized lease status: "active", "expired", "static" or the raw state
+    pub(crate) status: String,
+    /// "isc" or "kea", whichever server handed out the lease
+    backend: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DhcpLeasesResponse {
+    total: u32,
+    #[serde(rename = "rowCount")]
+    row_count: u32,
+    current: u32,
+    rows: Vec<Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DhcpLeases {
+    total: u32,
+    rows: Vec<DhcpLease>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StaticMappingResponse {
+    result: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uuid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    validations: Option<Value>,
+}
+
+fn build_api_url(api_info: &ApiInfo, endpoint: &str) -> String {
+    format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
+}
+
+/// Returns the first non-empty string among the given keys, since ISC, Kea
+/// and DHCPv6 lease rows name the same attributes differently
+fn first_field(row: &Value, keys: &[&str]) -> String {
+    keys.iter()
+        .filter_map(|key| match row.get(*key) {
+            Some(Value::String(s)) => Some(s.clone()),
+            Some(Value::Number(n)) => Some(n.to_string()),
+            _ => None,
+        })
+        .find(|v| !v.is_empty())
+        .unwrap_or_default()
+}
+
+fn lease_status(lease_type: &str, state: &str) -> String {
+    if lease_type == "static" {
+        return "static".to_string();
+    }
+
+    match state {
+        "active" => "active".to_string(),
+        "expired" | "free" | "released" | "abandoned" => "expired".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn parse_lease(row: &Value) -> DhcpLease {
+    let lease_type = first_field(row, &["type"]);
+    let state = first_field(row, &["state", "binding"]);
+    let status = lease_status(&lease_type, &state);
+
+    DhcpLease {
+        address: first_field(row, &["address"]),
+        mac: first_field(row, &["mac", "hwaddr"]),
+        hostname: first_field(row, &["hostname", "client-hostname"]),
+        interface: first_field(row, &["if"]),
+        interface_description: first_field(row, &["if_descr"]),
+        starts: first_field(row, &["starts"]),
+        ends: first_field(row, &["ends"]),
+        lease_type,
+        state,
+        description: first_field(row, &["descr"]),
+        manufacturer: first_field(row, &["man", "manufacturer"]),
+        status,
+        backend: "isc".to_string(),
+    }
+}
+
+// Kea reports expiry as a unix timestamp; format it like ISC's "ends"
+fn kea_timestamp(value: &str) -> String {
+    value
+        .parse::<i64>()
+        .ok()
+        .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+        .map(|dt| dt.format("%Y/%m/%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+fn parse_kea_lease(row: &Value) -> DhcpLease {
+    let state = first_field(row, &["state"]);
+    let status = match state.as_str() {
+        "0" => "active",
+        "1" => "declined",
+        "2" => "expired",
+        other => other,
+    }
+    .to_string();
+
+    DhcpLease {
+        address: first_field(row, &["address"]),
+        mac: first_field(row, &["hwaddr", "hw_address"]),
+        hostname: first_field(row, &["hostname"])
+            .trim_end_matches('.')
+            .to_string(),
+        interface: first_field(row, &["if_name", "if"]),
+        interface_description: first_field(row, &["if_descr"]),
+        starts: String::new(),
+        ends: kea_timestamp(&first_field(row, &["expire"])),
+        lease_type: "dynamic".to_string(),
+        state,
+        description: first_field(row, &["description"]),
+        manufacturer: first_field(row, &["man", "manufacturer"]),
+        status,
+        backend: "kea".to_string(),
+    }
+}
+
+fn parse_kea_reservation(row: &Value) -> DhcpLease {
+    DhcpLease {
+        address: first_field(row, &["ip_address"]),
+        mac: first_field(row, &["hw_address"]),
+        hostname: first_field(row, &["hostname"]),
+        interface: String::new(),
+        interface_description: first_field(row, &["%subnet", "subnet"]),
+        starts: String::new(),
+        ends: String::new(),
+        lease_type: "static".to_string(),
+        state: String::new(),
+        description: first_field(row, &["description"]),
+        manufacturer: String::new(),
+        status: "static".to_string(),
+        backend: "kea".to_string(),
+    }
+}
+
+/// Kea when its service is running, otherwise the legacy ISC server. Older
+/// firmware without the Kea API falls back to ISC as well.
+async fn dhcp_backend(api_info: &ApiInfo) -> DhcpBackend {
+    let backends = BACKENDS.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(backend) = backends.lock().unwrap().get(&api_info.id) {
+        return *backend;
+    }
+
+    let url = build_api_url(api_info, "/api/kea/service/status");
+    let status = match make_http_request(
+        "GET",
+        &url,
+        None,
+        None,
+        Some(15),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await
+    {
+        Ok(response) => response.json::<Value>().await.ok(),
+        Err(e) => {
+            log::info!("Kea status unavailable, using ISC DHCP: {}", e);
+            None
+        }
+    };
+
+    let backend = match status {
+        Some(body) if body["status"] == "running" => DhcpBackend::Kea,
+        _ => DhcpBackend::Isc,
+    };
+    backends.lock().unwrap().insert(api_info.id, backend);
+    backend
+}
+
+async fn search_rows(api_info: &ApiInfo, endpoint: &str) -> Result<DhcpLeasesResponse, ApiError> {
+    let url = build_api_url(api_info, endpoint);
+
+    let payload = json!({
+        "current": 1,
+        "rowCount": -1,
+        "sort": {},
+        "searchPhrase": ""
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    response
+        .json::<DhcpLeasesResponse>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse DHCP leases: {}", e)))
+}
+
+async fn fetch_leases(
+    api_info: &ApiInfo,
+    endpoint: &str,
+    parse: fn(&Value) -> DhcpLease,
+) -> Result<DhcpLeases, ApiError> {
+    let leases = search_rows(api_info, endpoint).await?;
+
+    Ok(DhcpLeases {
+        total: leases.total,
+        rows: leases.rows.iter().map(parse).collect(),
+    })
+}
+
+/// Leases from whichever DHCP server is active, with Kea reservations
+/// included as static entries
+async fn fetch_family_leases(api_info: &ApiInfo, ipv6: bool) -> Result<DhcpLeases, ApiError> {
+    let family = if ipv6 { "6" } else { "4" };
+
+    match dhcp_backend(api_info).await {
+        DhcpBackend::Isc => {
+            let endpoint = format!("/api/dhcpv{}/leases/searchLease/", family);
+            fetch_leases(api_info, &endpoint, parse_lease).await
+        }
+        DhcpBackend::Kea => {
+            let endpoint = format!("/api/kea/leases{}/search", family);
+            let mut leases = fetch_leases(api_info, &endpoint, parse_kea_lease).await?;
+
+            let endpoint = format!("/api/kea/dhcpv{}/search_reservation", family);
+            match fetch_leases(api_info, &endpoint, parse_kea_reservation).await {
+                Ok(reservations) => {
+                    leases.total += reservations.total;
+                    leases.rows.extend(reservations.rows);
+                }
+                Err(e) => log::warn!("Failed to fetch Kea DHCPv{} reservations: {}", family, e),
+            }
+
+            Ok(leases)
+        }
+    }
+}
+
+async fn search_leases(database: State<'_, Database>, ipv6: bool) -> Result<DhcpLeases, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    fetch_family_leases(&api_info, ipv6).await
+}
+
+/// DHCPv4 and DHCPv6 leases keyed by lowercase MAC. A lease table that can't
+/// be read (e.g. the DHCP service isn't in use) is skipped rather than failing.
+/// When a MAC has several leases an active one wins.
+pub(crate) async fn leases_by_mac(api_info: &ApiInfo) -> HashMap<String, DhcpLease> {
+    let (v4, v6) = tokio::join!(
+        fetch_family_leases(api_info, false),
+        fetch_family_leases(api_info, true)
+    );
+
+    let mut leases: HashMap<String, DhcpLease> = HashMap::new();
+    for (family, result) in [("DHCPv4", v4), ("DHCPv6", v6)] {
+        let rows = match result {
+            Ok(table) => table.rows,
+            Err(e) => {
+                log::warn!("Skipping {} leases: {}", family, e);
+                continue;
+            }
+        };
+
+        for lease in rows.into_iter().filter(|lease| !lease.mac.is_empty()) {
+            let mac = lease.mac.to_lowercase();
+            let replace = leases
+                .get(&mac)
+                .is_none_or(|existing| existing.status != "active" && lease.status == "active");
+            if replace {
+                leases.insert(mac, lease);
+            }
+        }
+    }
+
+    leases
+}
+
+#[tauri::command]
+pub async fn get_dhcpv4_leases(database: State<'_, Database>) -> Result<DhcpLeases, ApiError> {
+    search_leases(database, false).await
+}
+
+#[tauri::command]
+pub async fn get_dhcpv6_leases(database: State<'_, Database>) -> Result<DhcpLeases, ApiError> {
+    search_leases(database, true).await
+}
+
+#[tauri::command]
+pub async fn add_static_mapping(
+    database: State<'_, Database>,
+    interface: String,
+    mac: String,
+    ip_address: String,
+    hostname: String,
+    description: String,
+) -> Result<StaticMappingResponse, ApiError> {
+    let target = mac.clone();
+    crate::audit::audited(
+        &database,
+        "add_static_mapping",
+        &target,
+        add_static_mapping_request(
+            database.clone(),
+            interface,
+            mac,
+            ip_address,
+            hostname,
+            description,
+        ),
+    )
+    .await
+}
+
+async fn add_static_mapping_request(
+    database: State<'_, Database>,
+    interface: String,
+    mac: String,
+    ip_address: String,
+    hostname: String,
+    description: String,
+) -> Result<StaticMappingResponse, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, "/api/dhcpv4/settings/addStaticMap/");
+
+    let payload = json!({
+        "staticmap": {
+            "interface": interface,
+            "mac": mac,
+            "ipaddr": ip_address,
+            "hostname": hostname,
+            "descr": description
+        }
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let result = response
+        .json::<StaticMappingResponse>()
+        .await
+        .map_err(|e| format!("Failed to parse static mapping response: {}", e))?;
+
+    if result.result == "saved" {
+        let reconfigure_url = build_api_url(&api_info, "/api/dhcpv4/service/reconfigure");
+
+        make_http_request(
+            "POST",
+            &reconfigure_url,
+            Some(json!({})),
+            None,
+            api_info.request_timeout(),
+            Some(&api_info.api_key),
+            Some(&api_info.api_secret),
+            Some(&api_info.client_settings()),
+        )
+        .await?;
+    }
+
+    Ok(result)
+}
+
+// True when `ip` is a host address inside `network`, given as "192.0.2.0/24".
+// The network and broadcast addresses don't count as usable.
+fn is_usable_address(network: &str, ip: Ipv4Addr) -> bool {
+    let Some((address, prefix)) = network.trim().split_once('/') else {
+        return false;
+    };
+    let (Ok(address), Ok(prefix)) = (address.parse::<Ipv4Addr>(), prefix.parse::<u32>()) else {
+        return false;
+    };
+    if prefix > 32 {
+        return false;
+    }
+
+    let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+    let base = u32::from(address) & mask;
+    let ip = u32::from(ip);
+
+    if ip & mask != base {
+        return false;
+    }
+    prefix >= 31 || (ip != base && ip != base | !mask)
+}
+
+/// Pins a device to an address: a static mapping for ISC DHCP or a
+/// reservation for Kea, applied right away. `ip`, `hostname` and `interface`
+/// default to the device's current lease. Returns the uuid of the new entry.
+#[tauri::command]
+pub async fn create_dhcp_reservation(
+    database: State<'_, Database>,
+    mac: String,
+    ip: Option<String>,
+    hostname: Option<String>,
+    description: Option<String>,
+    interface: Option<String>,
+) -> Result<String, ApiError> {
+    let mac = mac.trim().replace('-', ":").to_lowercase();
+    if !crate::wol::is_valid_mac(&mac) {
+        return Err(format!("Invalid MAC address: {}", mac).into());
+    }
+
+    let target = mac.clone();
+    crate::audit::audited(
+        &database,
+        "create_dhcp_reservation",
+        &target,
+        create_dhcp_reservation_request(
+            database.clone(),
+            mac,
+            ip,
+            hostname,
+            description.unwrap_or_default(),
+            interface,
+        ),
+    )
+    .await
+}
+
+async fn create_dhcp_reservation_request(
+    database: State<'_, Database>,
+    mac: String,
+    ip: Option<String>,
+    hostname: Option<String>,
+    description: String,
+    interface: Option<String>,
+) -> Result<String, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let non_empty = |value: &String| !value.trim().is_empty();
+    let lease = leases_by_mac(&api_info).await.remove(&mac);
+
+    let ip = ip
+        .filter(non_empty)
+        .or_else(|| lease.as_ref().map(|lease| lease.address.clone()))
+        .filter(non_empty)
+        .ok_or_else(|| format!("No IP address given and no lease found for {}", mac))?;
+    let ip = ip
+        .trim()
+        .parse::<Ipv4Addr>()
+        .map_err(|_| format!("Invalid IPv4 address: {}", ip.trim()))?;
+    let hostname = hostname
+        .or_else(|| lease.as_ref().map(|lease| lease.hostname.clone()))
+        .unwrap_or_default();
+    let interface = interface
+        .filter(non_empty)
+        .or_else(|| lease.as_ref().map(|lease| lease.interface.clone()))
+        .filter(non_empty);
+
+    match dhcp_backend(&api_info).await {
+        DhcpBackend::Isc => {
+            let interface = interface
+                .ok_or_else(|| "An interface is required for the reservation".to_string())?;
+
+            let networks =
+                crate::interfaces::interface_ipv4_networks(database.clone(), &interface).await?;
+            if networks.is_empty() {
+                return Err(format!("Interface {} has no IPv4 network", interface).into());
+            }
+            if !networks
+                .iter()
+                .any(|network| is_usable_address(network, ip))
+            {
+                return Err(format!(
+                    "{} is not a usable address on {} ({})",
+                    ip,
+                    interface,
+                    networks.join(", ")
+                )
+                .into());
+            }
+
+            let result = add_static_mapping_request(
+                database,
+                interface,
+                mac,
+                ip.to_string(),
+                hostname,
+                description,
+            )
+            .await?;
+            if result.result != "saved" {
+                return Err(format!(
+                    "Failed to save static mapping: {}",
+                    result.validations.unwrap_or(Value::String(result.result))
+                )
+                .into());
+            }
+
+            result.uuid.ok_or_else(|| {
+                ApiError::Other("Static mapping saved without an identifier".to_string())
+            })
+        }
+        DhcpBackend::Kea => add_kea_reservation(&api_info, &mac, ip, &hostname, &description).await,
+    }
+}
+
+async fn add_kea_reservation(
+    api_info: &ApiInfo,
+    mac: &str,
+    ip: Ipv4Addr,
+    hostname: &str,
+    description: &str,
+) -> Result<String, ApiError> {
+    // Kea reservations belong to a subnet rather than an interface
+    let subnets = search_rows(api_info, "/api/kea/dhcpv4/search_subnet").await?;
+    let subnet = subnets
+        .rows
+        .iter()
+        .find(|row| is_usable_address(&first_field(row, &["subnet"]), ip))
+        .ok_or_else(|| format!("{} is not a usable address in any Kea subnet", ip))?;
+
+    let payload = json!({
+        "reservation": {
+            "subnet": first_field(subnet, &["uuid"]),
+            "ip_address": ip.to_string(),
+            "hw_address": mac,
+            "hostname": hostname,
+            "description": description
+        }
+    });
+
+    let response = make_http_request(
+        "POST",
+        &build_api_url(api_info, "/api/kea/dhcpv4/add_reservation"),
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let result = response
+        .json::<StaticMappingResponse>()
+        .await
+        .map_err(|e| format!("Failed to parse reservation response: {}", e))?;
+
+    if result.result != "saved" {
+        return Err(format!(
+            "Failed to save reservation: {}",
+            result.validations.unwrap_or(Value::String(result.result))
+        )
+        .into());
+    }
+
+    make_http_request(
+        "POST",
+        &build_api_url(api_info, "/api/kea/service/reconfigure"),
+        Some(json!({})),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    result
+        .uuid
+        .ok_or_else(|| ApiError::Other("Reservation saved without an identifier".to_string()))
+}
@@ -0,0 +1,396 @@
+use crate::db::{ApiInfo, Database};
+use crate::error::ApiError;
+use crate::http_client::make_http_request;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tauri::State;
+
+const DEFAULT_PING_COUNT: u32 = 4;
+const MAX_PING_COUNT: u32 = 50;
+const DNS_RECORD_TYPES: [&str; 5] = ["A", "AAAA", "MX", "TXT", "PTR"];
+
+#[derive(Serialize, Debug)]
+pub struct PingResult {
+    host: String,
+    sent: u32,
+    received: u32,
+    loss_percent: f64,
+    min_ms: Option<f64>,
+    avg_ms: Option<f64>,
+    max_ms: Option<f64>,
+    stddev_ms: Option<f64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TracerouteHop {
+    hop: u32,
+    host: Option<String>,
+    address: Option<String>,
+    rtts_ms: Vec<Option<f64>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TracerouteResult {
+    host: String,
+    hops: Vec<TracerouteHop>,
+    reached: bool,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DnsLookupStatus {
+    NoError,
+    NxDomain,
+    ServFail,
+    Error,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DnsRecord {
+    name: Option<String>,
+    record_type: String,
+    ttl: Option<u32>,
+    value: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DnsLookupResult {
+    hostname: String,
+    record_type: String,
+    status: DnsLookupStatus,
+    server: Option<String>,
+    records: Vec<DnsRecord>,
+}
+
+fn build_api_url(api_info: &ApiInfo, endpoint: &str) -> String {
+    format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
+}
+
+// Hostnames and IPv4/IPv6 literals only, so nothing else reaches the shell
+// command the firewall runs
+fn validate_host(host: &str) -> Result<String, ApiError> {
+    let host = host.trim();
+    if host.is_empty() || host.len() > 253 {
+        return Err("Host must be between 1 and 253 characters"
+            .to_string()
+            .into());
+    }
+    if host.starts_with('-')
+        || !host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':' | '_'))
+    {
+        return Err(format!("Invalid host: {}", host).into());
+    }
+    Ok(host.to_string())
+}
+
+fn address_family(host: &str) -> &'static str {
+    if host.contains(':') {
+        "ip6"
+    } else {
+        "ip"
+    }
+}
+
+async fn post_json(api_info: &ApiInfo, endpoint: &str, payload: Value) -> Result<Value, ApiError> {
+    let url = build_api_url(api_info, endpoint);
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        Some(120),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    response
+        .json::<Value>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
+}
+
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().trim_end_matches('%').parse().ok(),
+        _ => None,
+    }
+}
+
+fn text(value: &Value) -> Option<String> {
+    value
+        .as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty() && *s != "*")
+        .map(str::to_string)
+}
+
+// Probe timings come back as e.g. "1.204 ms  0.982 ms *"
+fn parse_rtts(probes: &str) -> Vec<Option<f64>> {
+    probes
+        .split_whitespace()
+        .filter(|token| *token != "ms")
+        .map(|token| token.parse::<f64>().ok())
+        .collect()
+}
+
+/// `interface` is passed as the source address, the same selection the
+/// OPNsense diagnostics pages offer
+#[tauri::command]
+pub async fn ping_host(
+    database: State<'_, Database>,
+    host: String,
+    count: Option<u32>,
+    interface: Option<String>,
+) -> Result<PingResult, ApiError> {
+    let host = validate_host(&host)?;
+    let count = count.unwrap_or(DEFAULT_PING_COUNT);
+    if count == 0 || count > MAX_PING_COUNT {
+        return Err(format!("Count must be between 1 and {}", MAX_PING_COUNT).into());
+    }
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    // Ping runs as a background job on the firewall: create it, start it and
+    // poll its counters until the requested number of packets went out
+    let created = post_json(
+        &api_info,
+        "/api/diagnostics/ping/set",
+        json!({
+            "ping": {
+                "settings": {
+                    "hostname": host,
+                    "fam": address_family(&host),
+                    "source_address": interface.unwrap_or_default(),
+                }
+            }
+        }),
+    )
+    .await?;
+
+    let job_id = created["uuid"]
+        .as_str()
+        .ok_or_else(|| format!("Failed to create ping job: {}", created))?
+        .to_string();
+
+    post_json(
+        &api_info,
+        &format!("/api/diagnostics/ping/start/{}", job_id),
+        json!({}),
+    )
+    .await?;
+
+    let mut job = Value::Null;
+    for _ in 0..count + 5 {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let jobs = post_json(&api_info, "/api/diagnostics/ping/search_jobs", json!({})).await?;
+        if let Some(row) = jobs["rows"]
+            .as_array()
+            .and_then(|rows| rows.iter().find(|row| row["id"] == job_id.as_str()))
+        {
+            job = row.clone();
+            if number(&job["send"]).unwrap_or(0.0) >= count as f64 {
+                break;
+            }
+        }
+    }
+
+    // Always clean up so jobs don't keep pinging on the firewall
+    for action in ["stop", "remove"] {
+        let endpoint = format!("/api/diagnostics/ping/{}/{}", action, job_id);
+        if let Err(e) = post_json(&api_info, &endpoint, json!({})).await {
+            log::warn!("Failed to {} ping job {}: {}", action, job_id, e);
+        }
+    }
+
+    if job.is_null() {
+        return Err("Ping job did not report any results".to_string().into());
+    }
+
+    Ok(PingResult {
+        host,
+        sent: number(&job["send"]).unwrap_or(0.0) as u32,
+        received: number(&job["received"]).unwrap_or(0.0) as u32,
+        loss_percent: number(&job["loss"]).unwrap_or(100.0),
+        min_ms: number(&job["min"]),
+        avg_ms: number(&job["avg"]),
+        max_ms: number(&job["max"]),
+        stddev_ms: number(&job["std-dev"]).or_else(|| number(&job["stddev"])),
+    })
+}
+
+#[tauri::command]
+pub async fn traceroute_host(
+    database: State<'_, Database>,
+    host: String,
+    interface: Option<String>,
+) -> Result<TracerouteResult, ApiError> {
+    let host = validate_host(&host)?;
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let response = post_json(
+        &api_info,
+        "/api/diagnostics/traceroute/set",
+        json!({
+            "traceroute": {
+                "settings": {
+                    "hostname": host,
+                    "fam": address_family(&host),
+                    "source_address": interface.unwrap_or_default(),
+                }
+            }
+        }),
+    )
+    .await?;
+
+    let rows = response["response"]
+        .as_array()
+        .ok_or_else(|| format!("Unexpected traceroute response: {}", response))?;
+
+    let hops = rows
+        .iter()
+        .enumerate()
+        .map(|(index, row)| TracerouteHop {
+            hop: number(&row["ttl"])
+                .or_else(|| number(&row["hop"]))
+                .map_or(index as u32 + 1, |hop| hop as u32),
+            host: text(&row["host"]),
+            address: text(&row["address"]),
+            rtts_ms: row["probes"].as_str().map(parse_rtts).unwrap_or_default(),
+        })
+        .collect::<Vec<_>>();
+
+    let reached = hops.last().is_some_and(|hop| {
+        hop.address.as_deref() == Some(host.as_str()) || hop.host.as_deref() == Some(host.as_str())
+    });
+
+    Ok(TracerouteResult {
+        host,
+        hops,
+        reached,
+    })
+}
+
+fn lookup_status(body: &Value) -> DnsLookupStatus {
+    let rcode = [
+        &body["response"]["rcode"],
+        &body["response"]["status"],
+        &body["rcode"],
+        &body["status"],
+        &body["message"],
+    ]
+    .iter()
+    .filter_map(|value| value.as_str())
+    .map(str::to_uppercase)
+    .collect::<Vec<_>>()
+    .join(" ");
+
+    if rcode.contains("NXDOMAIN") {
+        DnsLookupStatus::NxDomain
+    } else if rcode.contains("SERVFAIL") {
+        DnsLookupStatus::ServFail
+    } else if rcode.contains("REFUSED") || rcode.contains("ERROR") || rcode.contains("FAIL") {
+        DnsLookupStatus::Error
+    } else {
+        DnsLookupStatus::NoError
+    }
+}
+
+fn parse_dns_record(row: &Value, record_type: &str) -> Option<DnsRecord> {
+    let value = ["data", "value", "address", "target"]
+        .iter()
+        .find_map(|key| text(&row[*key]))?;
+
+    Some(DnsRecord {
+        name: text(&row["name"]),
+        record_type: text(&row["type"]).unwrap_or_else(|| record_type.to_string()),
+        ttl: number(&row["ttl"]).map(|ttl| ttl as u32),
+        value,
+    })
+}
+
+/// nslookup-style query against the firewall's own resolver. NXDOMAIN and
+/// SERVFAIL are reported through `status` rather than as an error.
+#[tauri::command]
+pub async fn dns_lookup(
+    database: State<'_, Database>,
+    hostname: String,
+    record_type: Option<String>,
+) -> Result<DnsLookupResult, ApiError> {
+    let hostname = validate_host(&hostname)?;
+    let record_type = record_type
+        .map(|t| t.trim().to_uppercase())
+        .unwrap_or_else(|| "A".to_string());
+    if !DNS_RECORD_TYPES.contains(&record_type.as_str()) {
+        return Err(format!(
+            "Unsupported record type {}, expected one of {}",
+            record_type,
+            DNS_RECORD_TYPES.join(", ")
+        )
+        .into());
+    }
+    if record_type == "PTR" && hostname.parse::<std::net::IpAddr>().is_err() {
+        return Err("PTR lookups require an IP address".to_string().into());
+    }
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let body = post_json(
+        &api_info,
+        "/api/diagnostics/dns_diagnostics/set",
+        json!({
+            "dns": {
+                "settings": {
+                    "hostname": hostname,
+                    "type": record_type,
+                }
+            }
+        }),
+    )
+    .await?;
+
+    let rows = [
+        &body["response"]["records"],
+        &body["response"],
+        &body["records"],
+    ]
+    .into_iter()
+    .find_map(|value| value.as_array())
+    .cloned()
+    .unwrap_or_default();
+
+    let records = rows
+        .iter()
+        .filter_map(|row| parse_dns_record(row, &record_type))
+        .filter(|record| record.record_type.eq_ignore_ascii_case(&record_type))
+        .collect::<Vec<_>>();
+
+    let server = [&body["response"]["server"], &body["server"]]
+        .into_iter()
+        .find_map(text);
+
+    Ok(DnsLookupResult {
+        hostname,
+        record_type,
+        status: lookup_status(&body),
+        server,
+        records,
+    })
+}
@@ -0,0 +1,665 @@
+use crate::db::Database;
+use crate::error::ApiError;
+use crate::http_client::make_http_request;
+use crate::pending::{PendingChanges, Subsystem};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::State;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HostOverride {
+    #[serde(default)]
+    uuid: String,
+    #[serde(default)]
+    enabled: String,
+    #[serde(default)]
+    hostname: String,
+    #[serde(default)]
+    domain: String,
+    #[serde(rename = "rr", default)]
+    record_type: String,
+    #[serde(rename = "server", default)]
+    ip_address: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HostOverrideResponse {
+    total: u32,
+    #[serde(rename = "rowCount")]
+    row_count: u32,
+    current: u32,
+    rows: Vec<HostOverride>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NewHostOverride {
+    pub host: String,
+    pub domain: String,
+    pub record_type: String,
+    pub ip_address: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UnboundAcl {
+    #[serde(default)]
+    uuid: String,
+    #[serde(default)]
+    enabled: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    action: String,
+    /// Comma separated list of networks the action applies to
+    #[serde(default)]
+    networks: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UnboundAclResponse {
+    total: u32,
+    #[serde(rename = "rowCount")]
+    row_count: u32,
+    current: u32,
+    rows: Vec<UnboundAcl>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NewUnboundAcl {
+    pub network: String,
+    pub action: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+// Actions accepted by Unbound's access-control option
+const ACL_ACTIONS: &[&str] = &[
+    "allow",
+    "deny",
+    "refuse",
+    "allow_snoop",
+    "deny_nonlocal",
+    "refuse_nonlocal",
+];
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BlocklistEntry {
+    name: String,
+    description: String,
+    enabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BlocklistStats {
+    enabled: bool,
+    lists: Vec<BlocklistEntry>,
+    total_blocked_domains: Option<u64>,
+    blocked_queries: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DomainCount {
+    domain: String,
+    count: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DnsQueryStats {
+    total: u64,
+    blocked: u64,
+    cached: u64,
+    local: u64,
+    resolved: u64,
+    top_domains: Vec<DomainCount>,
+    top_blocked: Vec<DomainCount>,
+}
+
+fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
+    format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
+}
+
+fn value_as_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+// Reporting endpoints return either {"domain": {"total": n}} or {"domain": n}
+fn parse_domain_counts(value: &Value) -> Vec<DomainCount> {
+    let mut counts: Vec<DomainCount> = value
+        .as_object()
+        .map(|domains| {
+            domains
+                .iter()
+                .map(|(domain, entry)| DomainCount {
+                    domain: domain.clone(),
+                    count: value_as_u64(&entry["total"])
+                        .or_else(|| value_as_u64(entry))
+                        .unwrap_or(0),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    counts.sort_by_key(|entry| std::cmp::Reverse(entry.count));
+    counts
+}
+
+async fn fetch_unbound_totals(api_info: &crate::db::ApiInfo) -> Result<Value, ApiError> {
+    let url = build_api_url(api_info, "/api/unbound/overview/totals/10");
+
+    let response = make_http_request(
+        "GET",
+        &url,
+        None,
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    response
+        .json::<Value>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse Unbound statistics: {}", e)))
+}
+
+#[tauri::command]
+pub async fn list_host_overrides(
+    database: State<'_, Database>,
+) -> Result<HostOverrideResponse, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, "/api/unbound/settings/searchHostOverride/");
+
+    let payload = json!({
+        "current": 1,
+        "rowCount": -1,
+        "sort": {},
+        "searchPhrase": ""
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    response
+        .json::<HostOverrideResponse>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse host overrides: {}", e)))
+}
+
+// Pass apply = false to batch several edits and call apply_unbound_changes once
+#[tauri::command]
+pub async fn add_host_override(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    host_override: NewHostOverride,
+    apply: Option<bool>,
+) -> Result<Value, ApiError> {
+    let target = format!("{}.{}", host_override.host, host_override.domain);
+    crate::audit::audited(
+        &database,
+        "add_host_override",
+        &target,
+        add_host_override_request(database.clone(), pending, host_override, apply),
+    )
+    .await
+}
+
+async fn add_host_override_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    host_override: NewHostOverride,
+    apply: Option<bool>,
+) -> Result<Value, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, "/api/unbound/settings/addHostOverride/");
+
+    let payload = json!({
+        "host": {
+            "enabled": "1",
+            "hostname": host_override.host,
+            "domain": host_override.domain,
+            "rr": host_override.record_type,
+            "server": host_override.ip_address,
+            "description": host_override.description
+        }
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let result = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    if result["result"].as_str() == Some("saved") {
+        pending.mark(api_info.id, Subsystem::Unbound);
+        if apply.unwrap_or(true) {
+            apply_unbound_changes_request(database, pending).await?;
+        }
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn delete_host_override(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+    apply: Option<bool>,
+) -> Result<Value, ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "delete_host_override",
+        &target,
+        delete_host_override_request(database.clone(), pending, uuid, apply),
+    )
+    .await
+}
+
+async fn delete_host_override_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+    apply: Option<bool>,
+) -> Result<Value, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(
+        &api_info,
+        &format!("/api/unbound/settings/delHostOverride/{}", uuid),
+    );
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(json!({})),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let result = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    if result["result"].as_str() == Some("deleted") {
+        pending.mark(api_info.id, Subsystem::Unbound);
+        if apply.unwrap_or(true) {
+            apply_unbound_changes_request(database, pending).await?;
+        }
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn list_unbound_acls(
+    database: State<'_, Database>,
+) -> Result<UnboundAclResponse, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, "/api/unbound/settings/searchAcl/");
+
+    let payload = json!({
+        "current": 1,
+        "rowCount": -1,
+        "sort": {},
+        "searchPhrase": ""
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    response
+        .json::<UnboundAclResponse>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse access lists: {}", e)))
+}
+
+// Pass apply = false to batch several edits and call apply_unbound_changes once
+#[tauri::command]
+pub async fn add_unbound_acl(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    acl: NewUnboundAcl,
+    apply: Option<bool>,
+) -> Result<Value, ApiError> {
+    let target = acl.network.clone();
+    crate::audit::audited(
+        &database,
+        "add_unbound_acl",
+        &target,
+        add_unbound_acl_request(database.clone(), pending, acl, apply),
+    )
+    .await
+}
+
+async fn add_unbound_acl_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    acl: NewUnboundAcl,
+    apply: Option<bool>,
+) -> Result<Value, ApiError> {
+    let action = acl.action.trim().to_lowercase();
+    if !ACL_ACTIONS.contains(&action.as_str()) {
+        return Err(format!(
+            "Invalid action '{}'. Expected one of: {}",
+            acl.action,
+            ACL_ACTIONS.join(", ")
+        )
+        .into());
+    }
+
+    let network = acl.network.trim();
+    if network.is_empty() {
+        return Err("Network cannot be empty".to_string().into());
+    }
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, "/api/unbound/settings/addAcl/");
+
+    // OPNsense requires a name, so fall back to the network when there's
+    // no description to use
+    let name = if acl.description.trim().is_empty() {
+        network.to_string()
+    } else {
+        acl.description.trim().to_string()
+    };
+
+    let payload = json!({
+        "acl": {
+            "enabled": "1",
+            "name": name,
+            "action": action,
+            "networks": network,
+            "description": acl.description
+        }
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let result = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    if result["result"].as_str() == Some("saved") {
+        pending.mark(api_info.id, Subsystem::Unbound);
+        if apply.unwrap_or(true) {
+            apply_unbound_changes_request(database, pending).await?;
+        }
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn delete_unbound_acl(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+    apply: Option<bool>,
+) -> Result<Value, ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "delete_unbound_acl",
+        &target,
+        delete_unbound_acl_request(database.clone(), pending, uuid, apply),
+    )
+    .await
+}
+
+async fn delete_unbound_acl_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+    apply: Option<bool>,
+) -> Result<Value, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, &format!("/api/unbound/settings/delAcl/{}", uuid));
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(json!({})),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let result = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    if result["result"].as_str() == Some("deleted") {
+        pending.mark(api_info.id, Subsystem::Unbound);
+        if apply.unwrap_or(true) {
+            apply_unbound_changes_request(database, pending).await?;
+        }
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn apply_unbound_changes(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+) -> Result<Value, ApiError> {
+    crate::audit::audited(
+        &database,
+        "apply_unbound_changes",
+        "",
+        apply_unbound_changes_request(database.clone(), pending),
+    )
+    .await
+}
+
+async fn apply_unbound_changes_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+) -> Result<Value, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    // Stays pending unless the reconfigure succeeds
+    pending.mark(api_info.id, Subsystem::Unbound);
+
+    let url = build_api_url(&api_info, "/api/unbound/service/reconfigure");
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(json!({})),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let result = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse reconfigure response: {}", e))?;
+
+    pending.clear(api_info.id, Subsystem::Unbound);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn get_dns_blocklist_stats(
+    database: State<'_, Database>,
+) -> Result<BlocklistStats, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, "/api/unbound/settings/get");
+
+    let response = make_http_request(
+        "GET",
+        &url,
+        None,
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let settings = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse Unbound settings: {}", e))?;
+
+    let dnsbl = &settings["unbound"]["dnsbl"];
+    let enabled = dnsbl["enabled"].as_str() == Some("1");
+
+    // Blocklist types are rendered as a selectable option map
+    let lists = dnsbl["type"]
+        .as_object()
+        .map(|options| {
+            options
+                .iter()
+                .map(|(name, option)| BlocklistEntry {
+                    name: name.clone(),
+                    description: option["value"].as_str().unwrap_or_default().to_string(),
+                    enabled: value_as_u64(&option["selected"]) == Some(1),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // The statistics endpoint only exists when Unbound reporting is available
+    let (total_blocked_domains, blocked_queries) = match fetch_unbound_totals(&api_info).await {
+        Ok(totals) => (
+            value_as_u64(&totals["blocklist_size"]),
+            value_as_u64(&totals["blocked"]["total"]).or_else(|| value_as_u64(&totals["blocked"])),
+        ),
+        Err(_) => (None, None),
+    };
+
+    Ok(BlocklistStats {
+        enabled,
+        lists,
+        total_blocked_domains,
+        blocked_queries,
+    })
+}
+
+#[tauri::command]
+pub async fn get_dns_query_stats(database: State<'_, Database>) -> Result<DnsQueryStats, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let totals = fetch_unbound_totals(&api_info).await.map_err(|e| {
+        e.plugin_missing(
+            "DNS query statistics are not available: enable Unbound reporting on the firewall",
+        )
+    })?;
+
+    let count = |key: &str| {
+        value_as_u64(&totals[key]["total"])
+            .or_else(|| value_as_u64(&totals[key]))
+            .unwrap_or(0)
+    };
+
+    Ok(DnsQueryStats {
+        total: count("total"),
+        blocked: count("blocked"),
+        cached: count("cached"),
+        local: count("local"),
+        resolved: count("resolved"),
+        top_domains: parse_domain_counts(&totals["top"]),
+        top_blocked: parse_domain_counts(&totals["top_blocked"]),
+    })
+}
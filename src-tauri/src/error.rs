@@ -0,0 +1,168 @@
+use serde::Serialize;
+use std::fmt;
+
+/// Errors returned by requests to the firewall API.
+///
+/// Serialized as `{ "kind": "...", "message": "..." }` so the frontend can
+/// branch on the kind instead of matching on message text.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "message")]
+pub enum ApiError {
+    /// HTTP 401: the API key or secret is wrong
+    Auth(String),
+    /// HTTP 403: the credentials lack the privilege for this endpoint
+    Forbidden(String),
+    /// HTTP 404: the endpoint does not exist on this firewall
+    NotFound(String),
+    /// The request did not complete within its timeout
+    Timeout(String),
+    /// The firewall could not be reached (DNS, refused, reset, proxy)
+    Connection(String),
+    /// The certificate was rejected or the TLS handshake failed. Repeating
+    /// the request gives the same result, so it is never retried.
+    Tls(String),
+    /// The response body could not be decoded
+    Parse(String),
+    /// The endpoint belongs to a plugin that is not installed
+    PluginMissing(String),
+    /// HTTP 403 for a feature whose required privilege is known
+    MissingPrivilege(String),
+    /// Any other non-success HTTP status
+    Status {
+        status: u16,
+        message: String,
+    },
+    Other(String),
+}
+
+impl ApiError {
+    pub fn message(&self) -> &str {
+        match self {
+            ApiError::Auth(message)
+            | ApiError::Forbidden(message)
+            | ApiError::NotFound(message)
+            | ApiError::Timeout(message)
+            | ApiError::Connection(message)
+            | ApiError::Tls(message)
+            | ApiError::Parse(message)
+            | ApiError::PluginMissing(message)
+            | ApiError::MissingPrivilege(message)
+            | ApiError::Other(message) => message,
+            ApiError::Status { message, .. } => message,
+        }
+    }
+
+    /// Transient failures that may succeed when the request is repeated
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ApiError::Timeout(_) | ApiError::Connection(_) => true,
+            ApiError::Status { status, .. } => matches!(status, 502..=504),
+            _ => false,
+        }
+    }
+
+    /// Reports a missing endpoint as a missing plugin with a friendlier message
+    pub fn plugin_missing(self, message: &str) -> ApiError {
+        match self {
+            ApiError::NotFound(_) => ApiError::PluginMissing(message.to_string()),
+            other => other,
+        }
+    }
+
+    /// Reports a 403 as the privilege the feature needs, see `privileges.rs`
+    pub fn missing_privilege(self, feature: &str) -> ApiError {
+        match (self, crate::privileges::required_privileges(feature)) {
+            (ApiError::Forbidden(_), Some(privileges)) => ApiError::MissingPrivilege(format!(
+                "Missing privilege for {}: grant {} to the API user",
+                feature.replace('_', " "),
+                privileges
+                    .iter()
+                    .map(|p| format!("\"{}\"", p))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            (other, _) => other,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+// Commands that still return `Result<T, String>` can keep using `?`
+impl From<ApiError> for String {
+    fn from(error: ApiError) -> Self {
+        error.to_string()
+    }
+}
+
+// Validation and database errors inside commands are plain messages
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        ApiError::Other(message)
+    }
+}
+
+impl From<&str> for ApiError {
+    fn from(message: &str) -> Self {
+        ApiError::Other(message.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn serializes_kind_and_message() {
+        let error = ApiError::Auth("bad key".to_string());
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({ "kind": "Auth", "message": "bad key" })
+        );
+
+        let error = ApiError::MissingPrivilege("grant it".to_string());
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({ "kind": "MissingPrivilege", "message": "grant it" })
+        );
+    }
+
+    #[test]
+    fn serializes_status_with_code() {
+        let error = ApiError::Status {
+            status: 502,
+            message: "bad gateway".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({ "kind": "Status", "message": { "status": 502, "message": "bad gateway" } })
+        );
+    }
+
+    #[test]
+    fn tls_errors_are_not_retried() {
+        let error = ApiError::Tls("certificate rejected".to_string());
+        assert!(!error.is_retryable());
+        assert!(ApiError::Connection("refused".to_string()).is_retryable());
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({ "kind": "Tls", "message": "certificate rejected" })
+        );
+    }
+
+    #[test]
+    fn plain_messages_become_other() {
+        let error: ApiError = "Alias 'x' not found".into();
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({ "kind": "Other", "message": "Alias 'x' not found" })
+        );
+    }
+}
@@ -1,7 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
 
 use crate::db::Database;
+use crate::error::ApiError;
 use crate::http_client::make_http_request;
+use crate::pending::{PendingChanges, PendingRuleChange, Subsystem};
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
@@ -24,6 +27,30 @@ pub struct FirewallRulesResponse {
     current: u32,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NatRule {
+    uuid: String,
+    enabled: bool,
+    sequence: String,
+    interface: String,
+    protocol: String,
+    source: String,
+    destination: String,
+    destination_port: String,
+    target: String,
+    target_port: String,
+    description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NatRulesResponse {
+    rows: Vec<NatRule>,
+    #[serde(rename = "rowCount")]
+    row_count: u32,
+    total: u32,
+    current: u32,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ToggleRuleResponse {
     result: String,
@@ -45,6 +72,12 @@ pub struct AddRuleResponse {
     validations: Option<serde_json::Value>,
 }
 
+#[derive(Serialize, Debug)]
+pub struct DeleteRuleResponse {
+    uuid: String,
+    description: String,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct NetworkSelectOptions {
     pub aliases: Option<AliasOrNetwork>,
@@ -79,6 +112,31 @@ pub struct InterfaceListResponse {
     pub interfaces: InterfaceGroup,
 }
 
+#[derive(Serialize, Debug)]
+pub struct PortUsage {
+    /// "filter_rule", "port_forward" or "outbound_nat"
+    kind: String,
+    uuid: String,
+    enabled: bool,
+    description: String,
+    interface: String,
+    protocol: String,
+    /// The field that holds the port, e.g. "destination_port"
+    field: String,
+    /// The port alias the port was found through, if not listed directly
+    alias: Option<String>,
+}
+
+// Rule fields that can hold a port, flattened and legacy names included
+const RULE_PORT_FIELDS: &[&str] = &[
+    "source_port",
+    "destination_port",
+    "source.port",
+    "destination.port",
+    "local-port",
+    "target_port",
+];
+
 fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
     format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
 }
@@ -86,7 +144,7 @@ fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
 #[tauri::command]
 pub async fn get_interface_list(
     database: State<'_, Database>,
-) -> Result<InterfaceListResponse, String> {
+) -> Result<InterfaceListResponse, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -99,22 +157,21 @@ pub async fn get_interface_list(
         &url,
         None,
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<InterfaceListResponse>()
         .await
-        .map_err(|e| format!("Failed to parse interface list response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse interface list response: {}", e)))
 }
 
 #[tauri::command]
-pub async fn check_api_version(
-    database: State<'_, Database>,
-) -> Result<bool, String> {
+pub async fn check_api_version(database: State<'_, Database>) -> Result<bool, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -130,6 +187,7 @@ pub async fn check_api_version(
         Some(10), 
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     ).await;
  
     Ok(response.is_ok())
@@ -139,7 +197,7 @@ pub async fn check_api_version(
 pub async fn get_firewall_rules(
     database: State<'_, Database>,
     interface: Option<String>,
-) -> Result<FirewallRulesResponse, String> {
+) -> Result<FirewallRulesResponse, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -170,23 +228,41 @@ pub async fn get_firewall_rules(
         &url,
         Some(payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
-    .await?;
+    .await
+    .map_err(|e| e.missing_privilege("firewall_rules"))?;
 
     response
         .json::<FirewallRulesResponse>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
 
 #[tauri::command]
 pub async fn toggle_firewall_rule(
     database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
     uuid: String,
-) -> Result<ToggleRuleResponse, String> {
+) -> Result<ToggleRuleResponse, ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "toggle_firewall_rule",
+        &target,
+        toggle_firewall_rule_request(database.clone(), pending, uuid),
+    )
+    .await
+}
+
+async fn toggle_firewall_rule_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+) -> Result<ToggleRuleResponse, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -202,27 +278,190 @@ pub async fn toggle_firewall_rule(
         &toggle_url,
         Some(serde_json::json!({})),
         None,
-        Some(30),
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let result = toggle_response
+        .json::<ToggleRuleResponse>()
+        .await
+        .map_err(|e| format!("Failed to parse toggle response: {}", e))?;
+
+    if result.changed {
+        let enabled = result.result.eq_ignore_ascii_case("enabled");
+        pending.record_rule_toggle(api_info.id, &uuid, "filter", enabled);
+    }
+
+    Ok(result)
+}
+
+// Port-forward rows flatten nested fields into dotted keys and, depending on
+// the firmware, use either `disabled` or `enabled`
+fn parse_nat_rule(row: &serde_json::Value) -> NatRule {
+    let field = |keys: &[&str]| -> String {
+        keys.iter()
+            .filter_map(|key| row.get(*key).and_then(|v| v.as_str()))
+            .find(|v| !v.is_empty())
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    let enabled = match row.get("enabled").and_then(|v| v.as_str()) {
+        Some(enabled) => enabled == "1",
+        None => field(&["disabled"]) != "1",
+    };
+
+    NatRule {
+        uuid: field(&["uuid"]),
+        enabled,
+        sequence: field(&["sequence"]),
+        interface: field(&["interface"]),
+        protocol: field(&["protocol"]),
+        source: field(&["source.network", "source_net"]),
+        destination: field(&["destination.network", "destination_net"]),
+        destination_port: field(&["destination.port", "destination_port"]),
+        target: field(&["target"]),
+        target_port: field(&["local-port", "target_port"]),
+        description: field(&["descr", "description"]),
+    }
+}
+
+#[tauri::command]
+pub async fn get_nat_rules(database: State<'_, Database>) -> Result<NatRulesResponse, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, "/api/firewall/d_nat/search_rule");
+
+    let payload = serde_json::json!({
+        "current": 1,
+        "rowCount": -1,
+        "sort": {},
+        "searchPhrase": ""
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await
+    .map_err(|e| e.missing_privilege("port_forwards"))?;
+
+    let result = response
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| format!("Failed to parse NAT rules: {}", e))?;
+
+    let rows: Vec<NatRule> = result["rows"]
+        .as_array()
+        .map(|rows| rows.iter().map(parse_nat_rule).collect())
+        .unwrap_or_default();
+
+    Ok(NatRulesResponse {
+        row_count: rows.len() as u32,
+        total: result["total"].as_u64().unwrap_or(rows.len() as u64) as u32,
+        current: result["current"].as_u64().unwrap_or(1) as u32,
+        rows,
+    })
+}
+
+// Like toggle_firewall_rule this only stages the change; call
+// apply_firewall_changes to commit it
+#[tauri::command]
+pub async fn toggle_nat_rule(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+) -> Result<ToggleRuleResponse, ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "toggle_nat_rule",
+        &target,
+        toggle_nat_rule_request(database.clone(), pending, uuid),
+    )
+    .await
+}
+
+async fn toggle_nat_rule_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+) -> Result<ToggleRuleResponse, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let toggle_url = build_api_url(
+        &api_info,
+        &format!("/api/firewall/d_nat/toggle_rule/{}", uuid),
+    );
+
+    let toggle_response = make_http_request(
+        "POST",
+        &toggle_url,
+        Some(serde_json::json!({})),
+        None,
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
-    toggle_response
+    let result = toggle_response
         .json::<ToggleRuleResponse>()
         .await
-        .map_err(|e| format!("Failed to parse toggle response: {}", e))
+        .map_err(|e| format!("Failed to parse toggle response: {}", e))?;
+
+    if result.changed {
+        let enabled = result.result.eq_ignore_ascii_case("enabled");
+        pending.record_rule_toggle(api_info.id, &uuid, "nat", enabled);
+    }
+
+    Ok(result)
 }
 
+// Reloads the whole pf ruleset, so staged filter and NAT changes are
+// committed together
 #[tauri::command]
 pub async fn apply_firewall_changes(
     database: State<'_, Database>,
-) -> Result<ApplyResponse, String> {
+    pending: State<'_, PendingChanges>,
+) -> Result<ApplyResponse, ApiError> {
+    crate::audit::audited(
+        &database,
+        "apply_firewall_changes",
+        "",
+        apply_firewall_changes_request(database.clone(), pending),
+    )
+    .await
+}
+
+async fn apply_firewall_changes_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+) -> Result<ApplyResponse, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
         .ok_or_else(|| "API info not found".to_string())?;
 
+    // Stays pending unless the apply succeeds
+    pending.mark(api_info.id, Subsystem::Firewall);
+
     let apply_url = build_api_url(&api_info, "/api/firewall/filter/apply");
 
     let apply_response = make_http_request(
@@ -230,20 +469,78 @@ pub async fn apply_firewall_changes(
         &apply_url,
         Some(serde_json::json!({})),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
-    apply_response
+    let result = apply_response
         .json::<ApplyResponse>()
         .await
-        .map_err(|e| format!("Failed to parse apply response: {}", e))
+        .map_err(|e| format!("Failed to parse apply response: {}", e))?;
+
+    pending.clear(api_info.id, Subsystem::Firewall);
+
+    Ok(result)
 }
 
+/// Rules toggled since the last apply, i.e. what apply_firewall_changes
+/// will activate. Toggles made outside the app are not included.
 #[tauri::command]
-pub async fn get_rule_template(database: State<'_, Database>) -> Result<serde_json::Value, String> {
+pub async fn get_pending_firewall_changes(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+) -> Result<Vec<PendingRuleChange>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let mut changes = pending.rule_changes(api_info.id);
+
+    if changes.is_empty() {
+        return Ok(changes);
+    }
+
+    // Descriptions are best effort, the uuids alone are enough to apply
+    let mut descriptions: HashMap<String, String> = HashMap::new();
+    match get_firewall_rules(database.clone(), None).await {
+        Ok(rules) => descriptions.extend(
+            rules
+                .rows
+                .into_iter()
+                .map(|rule| (rule.uuid, rule.description)),
+        ),
+        Err(e) => log::warn!("Failed to load filter rule descriptions: {}", e),
+    }
+    if changes.iter().any(|change| change.kind == "nat") {
+        match get_nat_rules(database.clone()).await {
+            Ok(rules) => descriptions.extend(
+                rules
+                    .rows
+                    .into_iter()
+                    .map(|rule| (rule.uuid, rule.description)),
+            ),
+            Err(e) => log::warn!("Failed to load NAT rule descriptions: {}", e),
+        }
+    }
+
+    for change in changes.iter_mut() {
+        if let Some(description) = descriptions.remove(&change.uuid) {
+            change.description = description;
+        }
+    }
+    changes.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| a.uuid.cmp(&b.uuid)));
+
+    Ok(changes)
+}
+
+#[tauri::command]
+pub async fn get_rule_template(
+    database: State<'_, Database>,
+) -> Result<serde_json::Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -256,23 +553,119 @@ pub async fn get_rule_template(database: State<'_, Database>) -> Result<serde_js
         &url,
         None,
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<serde_json::Value>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
+}
+
+// Addresses, CIDR networks, "any" and alias or interface network names
+// such as "lan" or "(self)"
+fn validate_rule_target(field: &str, value: &str) -> Result<(), ApiError> {
+    if value.is_empty() || value == "any" || value.parse::<IpAddr>().is_ok() {
+        return Ok(());
+    }
+
+    if let Some((address, prefix)) = value.split_once('/') {
+        let max_prefix = match address.parse::<IpAddr>() {
+            Ok(IpAddr::V4(_)) => 32,
+            Ok(IpAddr::V6(_)) => 128,
+            Err(_) => return Err(format!("Invalid {}: {}", field, value).into()),
+        };
+        return match prefix.parse::<u8>() {
+            Ok(bits) if bits <= max_prefix => Ok(()),
+            _ => Err(format!("Invalid prefix length in {}: {}", field, value).into()),
+        };
+    }
+
+    if value.len() <= 32
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '(' | ')'))
+    {
+        Ok(())
+    } else {
+        Err(format!("Invalid {}: {}", field, value).into())
+    }
+}
+
+// A single port, a range ("1000-2000" or "1000:2000") or a port alias
+fn validate_rule_port(field: &str, value: &str) -> Result<(), ApiError> {
+    if value.is_empty() {
+        return Ok(());
+    }
+
+    let is_port = |part: &str| part.parse::<u16>().is_ok_and(|port| port > 0);
+    let valid = match value.split_once(['-', ':']) {
+        Some((start, end)) => is_port(start) && is_port(end),
+        None => {
+            is_port(value)
+                || (value.len() <= 32
+                    && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+        }
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid {}: {}", field, value).into())
+    }
+}
+
+/// Checks the fields of a `{"rule": {...}}` payload that are easy to get
+/// wrong before they reach the firewall
+fn validate_rule_data(rule_data: &serde_json::Value) -> Result<(), ApiError> {
+    let rule = rule_data.get("rule").unwrap_or(rule_data);
+    let field = |key: &str| rule.get(key).and_then(|v| v.as_str()).unwrap_or("");
+
+    if !matches!(field("action"), "" | "pass" | "block" | "reject") {
+        return Err(format!("Invalid action: {}", field("action")).into());
+    }
+    if !matches!(field("direction"), "" | "in" | "out") {
+        return Err(format!("Invalid direction: {}", field("direction")).into());
+    }
+
+    validate_rule_target("source", field("source_net"))?;
+    validate_rule_target("destination", field("destination_net"))?;
+    validate_rule_port("source port", field("source_port"))?;
+    validate_rule_port("destination port", field("destination_port"))?;
+
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn add_firewall_rule(
     database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
     rule_data: serde_json::Value,
-) -> Result<AddRuleResponse, String> {
+) -> Result<AddRuleResponse, ApiError> {
+    let target = rule_data["rule"]["description"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    crate::audit::audited(
+        &database,
+        "add_firewall_rule",
+        &target,
+        add_firewall_rule_request(database.clone(), pending, rule_data),
+    )
+    .await
+}
+
+async fn add_firewall_rule_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    rule_data: serde_json::Value,
+) -> Result<AddRuleResponse, ApiError> {
+    validate_rule_data(&rule_data)?;
+
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -285,9 +678,10 @@ pub async fn add_firewall_rule(
         &url,
         Some(rule_data),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -317,24 +711,135 @@ pub async fn add_firewall_rule(
                     }
                 }
                 Err(_) => {
-                    return Err(format!("Failed to parse API response: {}", response_text));
+                    return Err(
+                        format!("Failed to parse API response: {}", response_text).into(),
+                    );
                 }
             }
         }
     };
 
     if add_result.result == "saved" {
-        apply_firewall_changes(database).await?;
+        apply_firewall_changes(database, pending).await?;
     }
 
     Ok(add_result)
 }
 
+/// Adds a simple pass/block/reject rule, e.g. to block an address seen in the
+/// logs, and applies it. Fails with the firewall's validation messages when
+/// the rule is not saved, so a returned uuid always refers to a real rule.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn add_basic_firewall_rule(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    action: String,
+    interface: String,
+    direction: String,
+    protocol: String,
+    source: String,
+    destination: String,
+    port: Option<String>,
+    description: String,
+) -> Result<AddRuleResponse, ApiError> {
+    let port = port.unwrap_or_default();
+
+    // Protocol names differ between firmware versions, so check against the
+    // options the firewall itself offers
+    let template = get_rule_template(database.clone()).await?;
+    let protocol = template["rule"]["protocol"]
+        .as_object()
+        .and_then(|options| {
+            options
+                .keys()
+                .find(|key| key.eq_ignore_ascii_case(&protocol))
+                .cloned()
+        })
+        .ok_or_else(|| format!("Unsupported protocol: {}", protocol))?;
+
+    if !port.is_empty() && !matches!(protocol.as_str(), "TCP" | "UDP" | "TCP/UDP") {
+        return Err("A port can only be set for TCP and UDP rules"
+            .to_string()
+            .into());
+    }
+
+    let is_ipv6 = |target: &str| {
+        target
+            .split('/')
+            .next()
+            .and_then(|address| address.parse::<IpAddr>().ok())
+            .is_some_and(|address| address.is_ipv6())
+    };
+    let ipprotocol = if is_ipv6(&source) || is_ipv6(&destination) {
+        "inet6"
+    } else {
+        "inet"
+    };
+
+    let rule_data = serde_json::json!({
+        "rule": {
+            "enabled": "1",
+            "action": action,
+            "quick": "1",
+            "interface": interface,
+            "direction": direction,
+            "ipprotocol": ipprotocol,
+            "protocol": protocol,
+            "source_net": if source.is_empty() { "any" } else { source.as_str() },
+            "destination_net": if destination.is_empty() { "any" } else { destination.as_str() },
+            "destination_port": port,
+            "description": description,
+        }
+    });
+
+    let result = add_firewall_rule(database, pending, rule_data).await?;
+    if result.result != "saved" || result.uuid.is_none() {
+        return Err(format!(
+            "Firewall rejected the rule: {}",
+            result
+                .validations
+                .as_ref()
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| result.result.clone())
+        )
+        .into());
+    }
+
+    Ok(result)
+}
+
+/// Deletes a filter rule and applies the change. The rule is looked up first
+/// so an unknown uuid gives a clear error and the result can name the rule.
 #[tauri::command]
 pub async fn delete_firewall_rule(
     database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
     uuid: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<DeleteRuleResponse, ApiError> {
+    let rules = get_firewall_rules(database.clone(), None).await?;
+    let rule = rules
+        .rows
+        .into_iter()
+        .find(|rule| rule.uuid == uuid)
+        .ok_or_else(|| format!("Firewall rule not found: {}", uuid))?;
+
+    let target = format!("{} ({})", uuid, rule.description);
+    crate::audit::audited(
+        &database,
+        "delete_firewall_rule",
+        &target,
+        delete_firewall_rule_request(database.clone(), pending, uuid, rule.description),
+    )
+    .await
+}
+
+async fn delete_firewall_rule_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+    description: String,
+) -> Result<DeleteRuleResponse, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -350,9 +855,10 @@ pub async fn delete_firewall_rule(
         &url,
         Some(serde_json::json!({})),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -361,15 +867,128 @@ pub async fn delete_firewall_rule(
         .await
         .map_err(|e| format!("Failed to parse delete rule response: {}", e))?;
 
-    apply_firewall_changes(database).await?;
+    if result["result"] != "deleted" {
+        return Err(format!("Failed to delete firewall rule: {}", result).into());
+    }
+
+    apply_firewall_changes(database, pending).await?;
 
-    Ok(result)
+    Ok(DeleteRuleResponse { uuid, description })
+}
+
+/// Places a rule directly after `after_uuid`, or at the top of the list when
+/// no uuid is given, applies the change and returns the reordered rules
+#[tauri::command]
+pub async fn move_firewall_rule(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+    after_uuid: Option<String>,
+) -> Result<FirewallRulesResponse, ApiError> {
+    let target = match &after_uuid {
+        Some(after) => format!("{} after {}", uuid, after),
+        None => format!("{} to top", uuid),
+    };
+    crate::audit::audited(
+        &database,
+        "move_firewall_rule",
+        &target,
+        move_firewall_rule_request(database.clone(), pending, uuid, after_uuid),
+    )
+    .await
+}
+
+async fn move_firewall_rule_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+    after_uuid: Option<String>,
+) -> Result<FirewallRulesResponse, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let sequence = |rule: &FirewallRule| rule.sequence.parse::<u64>().unwrap_or(0);
+
+    let mut rules = get_firewall_rules(database.clone(), None).await?.rows;
+    rules.sort_by_key(sequence);
+
+    if !rules.iter().any(|rule| rule.uuid == uuid) {
+        return Err(format!("Firewall rule not found: {}", uuid).into());
+    }
+    let others: Vec<&FirewallRule> = rules.iter().filter(|rule| rule.uuid != uuid).collect();
+    if others.is_empty() {
+        return get_firewall_rules(database, None).await;
+    }
+
+    // The API can only move a rule in front of another one, so "after X"
+    // becomes "before whatever follows X"
+    let before = match &after_uuid {
+        None => others.first().copied(),
+        Some(after) => {
+            let position = others
+                .iter()
+                .position(|rule| &rule.uuid == after)
+                .ok_or_else(|| format!("Firewall rule not found: {}", after))?;
+            others.get(position + 1).copied()
+        }
+    };
+
+    let (url, payload) = match before {
+        Some(before) => (
+            build_api_url(
+                &api_info,
+                &format!(
+                    "/api/firewall/filter/move_rule_before/{}/{}",
+                    uuid, before.uuid
+                ),
+            ),
+            serde_json::json!({}),
+        ),
+        // Moving behind the last rule: give it the highest sequence
+        None => {
+            let last = rules.iter().map(sequence).max().unwrap_or(0);
+            (
+                build_api_url(
+                    &api_info,
+                    &format!("/api/firewall/filter/set_rule/{}", uuid),
+                ),
+                serde_json::json!({ "rule": { "sequence": (last + 1).to_string() } }),
+            )
+        }
+    };
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let result = response
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| format!("Failed to parse move rule response: {}", e))?;
+
+    if result["status"] != "ok" && result["result"] != "saved" {
+        return Err(format!("Failed to move firewall rule: {}", result).into());
+    }
+
+    apply_firewall_changes(database.clone(), pending).await?;
+
+    get_firewall_rules(database, None).await
 }
 
 #[tauri::command]
 pub async fn list_network_select_options(
     database: State<'_, Database>,
-) -> Result<NetworkSelectOptions, String> {
+) -> Result<NetworkSelectOptions, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -385,23 +1004,24 @@ pub async fn list_network_select_options(
         &url,
         None,
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<NetworkSelectOptions>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
 
 #[tauri::command]
 pub async fn get_rule(
     database: State<'_, Database>,
     uuid: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -417,30 +1037,45 @@ pub async fn get_rule(
         &url,
         None,
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await;
 
     match response {
-        Ok(resp) => {
-            resp.json::<serde_json::Value>()
-                .await
-                .map_err(|e| format!("Failed to parse response: {}", e))
-        },
-        Err(e) => {
-            Err(format!("Failed to get rule: {}", e))
-        }
+        Ok(resp) => resp
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e))),
+        Err(e) => Err(format!("Failed to get rule: {}", e).into()),
     }
 }
 
 #[tauri::command]
 pub async fn set_rule(
     database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+    rule_data: serde_json::Value,
+) -> Result<serde_json::Value, ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "set_rule",
+        &target,
+        set_rule_request(database.clone(), pending, uuid, rule_data),
+    )
+    .await
+}
+
+async fn set_rule_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
     uuid: String,
     rule_data: serde_json::Value,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -473,9 +1108,10 @@ pub async fn set_rule(
         &url,
         Some(actual_payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -489,15 +1125,161 @@ pub async fn set_rule(
     let result = match serde_json::from_str::<serde_json::Value>(&response_text) {
         Ok(value) => value,
         Err(e) => {
-            return Err(format!("Failed to parse set rule response as JSON: {}. Raw response: {}", e, response_text));
+            return Err(format!(
+                "Failed to parse set rule response as JSON: {}. Raw response: {}",
+                e, response_text
+            )
+            .into());
         }
     };
 
     if let Some(result_field) = result.get("result") {
         if result_field.as_str() == Some("saved") {
-            apply_firewall_changes(database).await?;
+            apply_firewall_changes(database, pending).await?;
         }
     }
 
     Ok(result)
 }
+
+// A single port, a "from:to" or "from-to" range, or something else such as
+// an alias name, which never matches here
+fn port_spec_matches(spec: &str, port: u16) -> bool {
+    match spec.split_once([':', '-']) {
+        Some((from, to)) => match (from.trim().parse::<u16>(), to.trim().parse::<u16>()) {
+            (Ok(from), Ok(to)) => (from..=to).contains(&port),
+            _ => false,
+        },
+        None => spec.trim().parse::<u16>() == Ok(port),
+    }
+}
+
+// Port aliases that include the port, directly or through a nested alias
+fn aliases_with_port(port_aliases: &HashMap<String, Vec<String>>, port: u16) -> HashSet<String> {
+    let mut matching = HashSet::new();
+    loop {
+        let found: Vec<String> = port_aliases
+            .iter()
+            .filter(|(name, _)| !matching.contains(*name))
+            .filter(|(_, entries)| {
+                entries
+                    .iter()
+                    .any(|entry| port_spec_matches(entry, port) || matching.contains(entry))
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        if found.is_empty() {
+            return matching;
+        }
+        matching.extend(found);
+    }
+}
+
+// "any" on either side matches; rules list combined protocols as "TCP/UDP"
+fn protocol_matches(rule_protocol: &str, protocol: &str) -> bool {
+    let rule_protocol = rule_protocol.trim().to_ascii_lowercase();
+    protocol == "any"
+        || rule_protocol.is_empty()
+        || rule_protocol == "any"
+        || rule_protocol.split('/').any(|p| p == protocol)
+}
+
+fn port_usages(
+    rows: &[serde_json::Value],
+    kind: &str,
+    port: u16,
+    protocol: &str,
+    aliases: &HashSet<String>,
+) -> Vec<PortUsage> {
+    let text = |row: &serde_json::Value, keys: &[&str]| -> String {
+        keys.iter()
+            .filter_map(|key| row[*key].as_str())
+            .find(|v| !v.is_empty())
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    rows.iter()
+        .filter(|row| protocol_matches(&text(row, &["protocol"]), protocol))
+        .filter_map(|row| {
+            // Negated entries ("!443") exclude the port rather than use it
+            let (field, alias) = RULE_PORT_FIELDS.iter().find_map(|field| {
+                row[*field]
+                    .as_str()?
+                    .split([',', '\n'])
+                    .map(str::trim)
+                    .filter(|item| !item.starts_with('!'))
+                    .find_map(|item| {
+                        if port_spec_matches(item, port) {
+                            Some((field.to_string(), None))
+                        } else if aliases.contains(item) {
+                            Some((field.to_string(), Some(item.to_string())))
+                        } else {
+                            None
+                        }
+                    })
+            })?;
+
+            let enabled = match row["enabled"].as_str() {
+                Some(enabled) => enabled == "1",
+                None => row["disabled"].as_str() != Some("1"),
+            };
+
+            Some(PortUsage {
+                kind: kind.to_string(),
+                uuid: text(row, &["uuid"]),
+                enabled,
+                description: text(row, &["description", "descr"]),
+                interface: text(row, &["interface"]),
+                protocol: text(row, &["protocol"]),
+                field,
+                alias,
+            })
+        })
+        .collect()
+}
+
+/// Filter rules, port forwards and outbound NAT rules that reference a port,
+/// directly, in a range or through a port alias. `protocol` is "tcp", "udp"
+/// or "any". Only rules managed through the automation (MVC) API are searched.
+#[tauri::command]
+pub async fn find_port_usage(
+    database: State<'_, Database>,
+    port: u16,
+    protocol: String,
+) -> Result<Vec<PortUsage>, ApiError> {
+    let protocol = protocol.trim().to_ascii_lowercase();
+    if !["tcp", "udp", "any"].contains(&protocol.as_str()) {
+        return Err(format!("Invalid protocol '{}'. Expected tcp, udp or any", protocol).into());
+    }
+    if port == 0 {
+        return Err("Port must be between 1 and 65535".to_string().into());
+    }
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let port_aliases = crate::alias::port_alias_entries(database.clone()).await?;
+    let aliases = aliases_with_port(&port_aliases, port);
+
+    let filter_rows = crate::alias::search_rule_rows(&api_info, "/api/firewall/filter/search_rule")
+        .await
+        .map_err(|e| e.missing_privilege("firewall_rules"))?;
+    let mut usages = port_usages(&filter_rows, "filter_rule", port, &protocol, &aliases);
+
+    // The NAT APIs only exist on newer firmware
+    for (endpoint, kind) in [
+        ("/api/firewall/d_nat/search_rule", "port_forward"),
+        ("/api/firewall/source_nat/search_rule", "outbound_nat"),
+    ] {
+        match crate::alias::search_rule_rows(&api_info, endpoint).await {
+            Ok(rows) => usages.extend(port_usages(&rows, kind, port, &protocol, &aliases)),
+            Err(ApiError::NotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(usages)
+}
@@ -1,12 +1,14 @@
 use crate::db::Database;
+use crate::error::ApiError;
 use crate::http_client::make_http_request;
 use log::error;
 use reqwest::header::{HeaderMap, ACCEPT};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::IpAddr;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use tauri::{Emitter, Manager, State, Window};
+use tauri::{AppHandle, Emitter, Manager, State, Window};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct FirewallLog {
@@ -73,8 +75,55 @@ pub struct LogCache {
     last_update: Instant,
     active_listeners: usize,
     filter_criteria: LogFilterCriteria,
+    stream_id: Option<u64>,
+    next_stream_id: u64,
 }
 
+const STREAM_POLL_INTERVAL: Duration = Duration::from_secs(2);
+// Number of recently emitted digests remembered to avoid repeating rows
+const STREAM_SEEN_CAPACITY: usize = 2000;
+
+impl LogFilterCriteria {
+    /// An empty criterion matches every log
+    fn matches(&self, log: &FirewallLog) -> bool {
+        (self.action.is_empty() || log.action.as_ref().is_some_and(|a| a == &self.action))
+            && (self.interface.is_empty()
+                || log.interface.as_ref().is_some_and(|i| i == &self.interface))
+            && (self.direction.is_empty() || log.dir.as_ref().is_some_and(|d| d == &self.direction))
+    }
+
+    fn apply(&self, logs: &[FirewallLog]) -> Vec<FirewallLog> {
+        logs.iter()
+            .filter(|log| self.matches(log))
+            .take(self.limit)
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct LogExport {
+    path: String,
+    rows: usize,
+}
+
+const CSV_COLUMNS: [&str; 14] = [
+    "timestamp",
+    "action",
+    "interface",
+    "dir",
+    "ipversion",
+    "protoname",
+    "src",
+    "srcport",
+    "dst",
+    "dstport",
+    "length",
+    "tcpflags",
+    "rid",
+    "label",
+];
+
 impl LogCache {
     pub fn new() -> Self {
         Self {
@@ -88,6 +137,8 @@ impl LogCache {
                 direction: String::new(),
                 limit: 500,
             },
+            stream_id: None,
+            next_stream_id: 0,
         }
     }
 }
@@ -98,7 +149,7 @@ pub fn register_log_cache(app: &mut tauri::App) -> Result<(), Box<dyn std::error
 }
 
 #[tauri::command]
-pub async fn get_log_filters(database: State<'_, Database>) -> Result<LogFilters, String> {
+pub async fn get_log_filters(database: State<'_, Database>) -> Result<LogFilters, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -117,20 +168,23 @@ pub async fn get_log_filters(database: State<'_, Database>) -> Result<LogFilters
         &url,
         None,
         Some(headers),
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<LogFilters>()
         .await
-        .map_err(|e| format!("Failed to parse log filters: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse log filters: {}", e)))
 }
 
 #[tauri::command]
-pub async fn get_interface_names(database: State<'_, Database>) -> Result<InterfaceNames, String> {
+pub async fn get_interface_names(
+    database: State<'_, Database>,
+) -> Result<InterfaceNames, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -149,22 +203,23 @@ pub async fn get_interface_names(database: State<'_, Database>) -> Result<Interf
         &url,
         None,
         Some(headers),
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<InterfaceNames>()
         .await
-        .map_err(|e| format!("Failed to parse interface names: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse interface names: {}", e)))
 }
 
 async fn fetch_firewall_logs(
     database: State<'_, Database>,
     digest: &str,
-) -> Result<Vec<FirewallLog>, String> {
+) -> Result<Vec<FirewallLog>, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -183,11 +238,13 @@ async fn fetch_firewall_logs(
         &url,
         None,
         Some(headers),
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
-    .await?;
+    .await
+    .map_err(|e| e.missing_privilege("firewall_logs"))?;
 
     let response_text = response
         .text()
@@ -206,7 +263,8 @@ async fn fetch_firewall_logs(
                 "Failed to parse logs: {}. First 1000 chars of response: {}",
                 e,
                 &response_text.chars().take(1000).collect::<String>()
-            ))
+            )
+            .into())
         }
     }
 }
@@ -215,7 +273,7 @@ async fn fetch_firewall_logs(
 pub async fn get_firewall_logs(
     database: State<'_, Database>,
     log_cache: State<'_, Arc<Mutex<LogCache>>>,
-) -> Result<Vec<FirewallLog>, String> {
+) -> Result<Vec<FirewallLog>, ApiError> {
     let digest;
     {
         let cache = log_cache.lock().unwrap();
@@ -260,33 +318,7 @@ pub async fn get_firewall_logs(
 
         cache.last_update = Instant::now();
     }
-    let filtered_logs = cache
-        .logs
-        .iter()
-        .filter(|log| {
-            (cache.filter_criteria.action.is_empty()
-                || log
-                    .action
-                    .as_ref()
-                    .is_some_and(|a| a == &cache.filter_criteria.action))
-                && (cache.filter_criteria.interface.is_empty()
-                    || log
-                        .interface
-                        .as_ref()
-                        .is_some_and(|i| i == &cache.filter_criteria.interface))
-                && (cache.filter_criteria.direction.is_empty()
-                    || log
-                        .dir
-                        .as_ref()
-                        .is_some_and(|d| d == &cache.filter_criteria.direction))
-        })
-        .cloned()
-        .collect::<Vec<_>>();
-
-    Ok(filtered_logs
-        .into_iter()
-        .take(cache.filter_criteria.limit)
-        .collect())
+    Ok(cache.filter_criteria.apply(&cache.logs))
 }
 
 #[tauri::command]
@@ -296,7 +328,7 @@ pub fn update_log_filters(
     interface: String,
     direction: String,
     limit: Option<usize>,
-) -> Result<(), String> {
+) -> Result<(), ApiError> {
     let mut cache = log_cache.lock().unwrap();
 
     cache.filter_criteria = LogFilterCriteria {
@@ -313,7 +345,7 @@ pub fn update_log_filters(
 pub fn start_log_polling(
     window: Window,
     log_cache: State<'_, Arc<Mutex<LogCache>>>,
-) -> Result<(), String> {
+) -> Result<(), ApiError> {
     {
         let mut cache = log_cache.lock().unwrap();
         cache.active_listeners += 1;
@@ -401,28 +433,7 @@ pub fn start_log_polling(
                         }
 
                         // Apply filters for the UI
-                        let filtered_logs =
-                            cache
-                                .logs
-                                .iter()
-                                .filter(|log| {
-                                    (cache.filter_criteria.action.is_empty()
-                                        || log
-                                            .action
-                                            .as_ref()
-                                            .is_some_and(|a| a == &cache.filter_criteria.action))
-                                        && (cache.filter_criteria.interface.is_empty()
-                                            || log.interface.as_ref().is_some_and(|i| {
-                                                i == &cache.filter_criteria.interface
-                                            }))
-                                        && (cache.filter_criteria.direction.is_empty()
-                                            || log.dir.as_ref().is_some_and(|d| {
-                                                d == &cache.filter_criteria.direction
-                                            }))
-                                })
-                                .take(cache.filter_criteria.limit)
-                                .cloned()
-                                .collect::<Vec<_>>();
+                        let filtered_logs = cache.filter_criteria.apply(&cache.logs);
 
                         // Send the filtered logs to the frontend
                         if let Err(e) = window_clone.emit("firewall-logs-updated", filtered_logs) {
@@ -486,7 +497,7 @@ pub fn start_log_polling(
 }
 
 #[tauri::command]
-pub fn stop_log_polling(log_cache: State<'_, Arc<Mutex<LogCache>>>) -> Result<(), String> {
+pub fn stop_log_polling(log_cache: State<'_, Arc<Mutex<LogCache>>>) -> Result<(), ApiError> {
     // Acquire the lock and decrement active_listeners
     let mut cache = log_cache.lock().unwrap();
 
@@ -513,9 +524,251 @@ pub fn stop_log_polling(log_cache: State<'_, Arc<Mutex<LogCache>>>) -> Result<()
 }
 
 #[tauri::command]
-pub fn clear_log_cache(log_cache: State<'_, Arc<Mutex<LogCache>>>) -> Result<(), String> {
+pub fn clear_log_cache(log_cache: State<'_, Arc<Mutex<LogCache>>>) -> Result<(), ApiError> {
     let mut cache = log_cache.lock().unwrap();
     cache.logs.clear();
     cache.last_digest = String::new();
     Ok(())
 }
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn logs_to_csv(logs: &[FirewallLog]) -> String {
+    let mut csv = CSV_COLUMNS.join(",");
+    csv.push('\n');
+
+    for log in logs {
+        let values = [
+            &log.timestamp,
+            &log.action,
+            &log.interface,
+            &log.dir,
+            &log.ipversion,
+            &log.protoname,
+            &log.src,
+            &log.srcport,
+            &log.dst,
+            &log.dstport,
+            &log.length,
+            &log.tcpflags,
+            &log.rid,
+            &log.label,
+        ];
+        let row = values
+            .iter()
+            .map(|value| csv_field(value.as_deref().unwrap_or("")))
+            .collect::<Vec<_>>()
+            .join(",");
+        csv.push_str(&row);
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// Writes the cached logs to `path` as CSV or JSON. Without explicit `filters`
+/// the current on-screen filters are used so the export matches the view.
+#[tauri::command]
+pub fn export_firewall_logs(
+    log_cache: State<'_, Arc<Mutex<LogCache>>>,
+    format: String,
+    path: String,
+    filters: Option<LogFilterCriteria>,
+) -> Result<LogExport, ApiError> {
+    if path.trim().is_empty() {
+        return Err("Export path is required".to_string().into());
+    }
+
+    let logs = {
+        let cache = log_cache.lock().unwrap();
+        filters
+            .as_ref()
+            .unwrap_or(&cache.filter_criteria)
+            .apply(&cache.logs)
+    };
+
+    let content = match format.to_lowercase().as_str() {
+        "csv" => logs_to_csv(&logs),
+        "json" => serde_json::to_string_pretty(&logs)
+            .map_err(|e| format!("Failed to serialize logs: {}", e))?,
+        other => return Err(format!("Unsupported export format: {}", other).into()),
+    };
+
+    std::fs::write(&path, content)
+        .map_err(|e| format!("Failed to write logs to {}: {}", path, e))?;
+
+    Ok(LogExport {
+        path,
+        rows: logs.len(),
+    })
+}
+
+/// Remembers the digests of rows already sent to the frontend
+struct SeenDigests {
+    order: VecDeque<String>,
+    digests: HashSet<String>,
+}
+
+impl SeenDigests {
+    fn new() -> Self {
+        Self {
+            order: VecDeque::with_capacity(STREAM_SEEN_CAPACITY),
+            digests: HashSet::with_capacity(STREAM_SEEN_CAPACITY),
+        }
+    }
+
+    /// Returns true the first time a digest is seen
+    fn insert(&mut self, digest: &str) -> bool {
+        if !self.digests.insert(digest.to_string()) {
+            return false;
+        }
+        self.order.push_back(digest.to_string());
+        if self.order.len() > STREAM_SEEN_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.digests.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+fn stream_is_current(log_cache: &Arc<Mutex<LogCache>>, stream_id: u64) -> bool {
+    log_cache.lock().unwrap().stream_id == Some(stream_id)
+}
+
+/// Tails the live log view and emits only rows not sent before as
+/// `firewall-log-stream` events. Starting a stream replaces any running one.
+#[tauri::command]
+pub fn start_firewall_log_stream(
+    app_handle: AppHandle,
+    log_cache: State<'_, Arc<Mutex<LogCache>>>,
+) -> Result<(), ApiError> {
+    let stream_id = {
+        let mut cache = log_cache.lock().unwrap();
+        cache.next_stream_id += 1;
+        cache.stream_id = Some(cache.next_stream_id);
+        cache.next_stream_id
+    };
+
+    let log_cache = log_cache.inner().clone();
+
+    tauri::async_runtime::spawn(async move {
+        let database = app_handle.state::<Database>();
+        let mut seen = SeenDigests::new();
+        let mut last_digest = String::new();
+
+        while stream_is_current(&log_cache, stream_id) {
+            match fetch_firewall_logs(database.clone(), &last_digest).await {
+                Ok(logs) => {
+                    if let Some(digest) = logs.last().and_then(|log| log.digest.clone()) {
+                        last_digest = digest;
+                    }
+
+                    let criteria = log_cache.lock().unwrap().filter_criteria.clone();
+                    let new_rows = logs
+                        .into_iter()
+                        .filter(|log| log.digest.as_deref().is_none_or(|d| seen.insert(d)))
+                        .filter(|log| criteria.matches(log))
+                        .collect::<Vec<_>>();
+
+                    // A stop request may have arrived while the request was in flight
+                    if !new_rows.is_empty() && stream_is_current(&log_cache, stream_id) {
+                        if let Err(e) = app_handle.emit("firewall-log-stream", new_rows) {
+                            error!("Failed to emit firewall-log-stream event: {}", e);
+                        }
+                    }
+                }
+                Err(e) => error!("Failed to fetch firewall log stream: {}", e),
+            }
+
+            tokio::time::sleep(STREAM_POLL_INTERVAL).await;
+        }
+
+        log::info!("Firewall log stream {} stopped", stream_id);
+    });
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_firewall_log_stream(
+    log_cache: State<'_, Arc<Mutex<LogCache>>>,
+) -> Result<(), ApiError> {
+    log_cache.lock().unwrap().stream_id = None;
+    Ok(())
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressScope {
+    Lan,
+    Public,
+    Invalid,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct GeoIpInfo {
+    ip: String,
+    scope: AddressScope,
+    country: Option<String>,
+    asn: Option<String>,
+}
+
+/// Private, loopback, link-local and other non-routable ranges
+fn is_reserved(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let octets = v4.octets();
+            v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_documentation()
+                || v4.is_multicast()
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (octets[0] == 100 && (octets[1] & 0xc0) == 64)
+        }
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                // Unique local fc00::/7 and link-local fe80::/10
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+/// Classifies log addresses for the visible rows. OPNsense only uses its GeoIP
+/// data to build aliases and has no per-address lookup endpoint, so public
+/// addresses are returned without a country or ASN.
+#[tauri::command]
+pub fn resolve_log_geoip(ips: Vec<String>) -> Result<Vec<GeoIpInfo>, ApiError> {
+    let mut seen = HashSet::new();
+
+    Ok(ips
+        .into_iter()
+        .filter(|ip| seen.insert(ip.clone()))
+        .map(|ip| {
+            let scope = match ip.trim().parse::<IpAddr>() {
+                Ok(addr) if is_reserved(&addr) => AddressScope::Lan,
+                Ok(_) => AddressScope::Public,
+                Err(_) => AddressScope::Invalid,
+            };
+            GeoIpInfo {
+                ip,
+                scope,
+                country: None,
+                asn: None,
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,146 @@
+use crate::db::Database;
+use crate::error::ApiError;
+use crate::http_client::make_http_request;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::State;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CarpVip {
+    interface: String,
+    vhid: String,
+    address: String,
+    advbase: String,
+    advskew: String,
+    /// MASTER, BACKUP or INIT as reported by the node
+    state: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CarpStatus {
+    maintenance_mode: bool,
+    demotion: i64,
+    vips: Vec<CarpVip>,
+}
+
+fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
+    format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
+}
+
+fn value_as_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+async fn fetch_vip_status(database: &State<'_, Database>) -> Result<Value, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, "/api/diagnostics/interface/get_vip_status/");
+
+    let response = make_http_request(
+        "GET",
+        &url,
+        None,
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    response
+        .json::<Value>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse VIP status: {}", e)))
+}
+
+#[tauri::command]
+pub async fn get_carp_status(database: State<'_, Database>) -> Result<CarpStatus, ApiError> {
+    let status = fetch_vip_status(&database).await?;
+
+    // Nodes outside a CARP cluster simply report no CARP rows
+    let vips = status["rows"]
+        .as_array()
+        .map(|rows| {
+            rows.iter()
+                .filter(|row| row["mode"].as_str() == Some("carp"))
+                .map(|row| CarpVip {
+                    interface: value_as_string(&row["interface"]),
+                    vhid: value_as_string(&row["vhid"]),
+                    address: value_as_string(&row["subnet"]),
+                    advbase: value_as_string(&row["advbase"]),
+                    advskew: value_as_string(&row["advskew"]),
+                    state: value_as_string(&row["status"]),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let maintenance_mode = match &status["carp"]["maintenancemode"] {
+        Value::Bool(enabled) => *enabled,
+        other => value_as_string(other) == "1",
+    };
+
+    let demotion = value_as_string(&status["carp"]["demotion"])
+        .parse()
+        .unwrap_or(0);
+
+    Ok(CarpStatus {
+        maintenance_mode,
+        demotion,
+        vips,
+    })
+}
+
+#[tauri::command]
+pub async fn carp_maintenance_mode(
+    database: State<'_, Database>,
+    enable: bool,
+) -> Result<CarpStatus, ApiError> {
+    let target = enable.to_string();
+    crate::audit::audited(
+        &database,
+        "carp_maintenance_mode",
+        &target,
+        carp_maintenance_mode_request(database.clone(), enable),
+    )
+    .await
+}
+
+async fn carp_maintenance_mode_request(
+    database: State<'_, Database>,
+    enable: bool,
+) -> Result<CarpStatus, ApiError> {
+    let current = get_carp_status(database.clone()).await?;
+
+    // The endpoint toggles the persistent flag, so only call it when the state differs
+    if current.maintenance_mode != enable {
+        let api_info = database
+            .get_default_api_info()
+            .map_err(|e| format!("Failed to get API info: {}", e))?
+            .ok_or_else(|| "API info not found".to_string())?;
+
+        let url = build_api_url(&api_info, "/api/diagnostics/interface/carp_status/maintenance");
+
+        make_http_request(
+            "POST",
+            &url,
+            Some(json!({})),
+            None,
+            api_info.request_timeout(),
+            Some(&api_info.api_key),
+            Some(&api_info.api_secret),
+            Some(&api_info.client_settings()),
+        )
+        .await?;
+    }
+
+    get_carp_status(database).await
+}
@@ -1,14 +1,305 @@
 use base64::{engine::general_purpose, Engine as _};
-use log::{error, info};
+use log::{debug, error, info, log_enabled, warn, Level};
+use crate::error::ApiError;
 use reqwest::{
-    header::{HeaderMap, AUTHORIZATION, HeaderValue, CONTENT_TYPE},
-    Client, Response,
+    header::{HeaderMap, HeaderName, AUTHORIZATION, HeaderValue, CONTENT_TYPE},
+    Certificate, Client, RequestBuilder, Response, StatusCode,
 };
 use serde_json::Value;
-use std::cmp::min;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
 use std::time::Duration;
 
+/// Per-profile HTTP client settings: TLS verification and extra headers
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ClientSettings {
+    /// Skip certificate validation entirely (self-signed certificates)
+    pub accept_invalid_certs: bool,
+    /// Optional PEM encoded CA certificate to trust in addition to the system roots
+    pub ca_cert_path: Option<String>,
+    /// Headers sent with every request in addition to Basic auth, e.g. for an
+    /// access proxy in front of the firewall
+    pub extra_headers: Vec<(String, String)>,
+    /// Set for a saved profile so its requests feed the connection monitor
+    pub profile_id: Option<i64>,
+}
+
+/// Parses a custom profile header. Authorization is reserved for the API
+/// key and secret.
+pub fn parse_extra_header(name: &str, value: &str) -> Result<(HeaderName, HeaderValue), String> {
+    let header_name = HeaderName::from_bytes(name.trim().as_bytes())
+        .map_err(|e| format!("Invalid header name {}: {}", name, e))?;
+    if header_name == AUTHORIZATION {
+        return Err(
+            "The Authorization header carries the API key and secret and can't be overridden"
+                .to_string(),
+        );
+    }
+    let header_value = HeaderValue::from_str(value.trim())
+        .map_err(|e| format!("Invalid value for header {}: {}", name, e))?;
+    Ok((header_name, header_value))
+}
+
+/// Clients are cached per `ClientSettings` so connections and TLS sessions
+/// are pooled across commands instead of being rebuilt for every request
+static CLIENTS: OnceLock<Mutex<HashMap<ClientSettings, Client>>> = OnceLock::new();
+
+fn build_client(settings: &ClientSettings) -> Result<Client, String> {
+    let mut client_builder = Client::builder();
+
+    if settings.accept_invalid_certs {
+        client_builder = client_builder.danger_accept_invalid_certs(true);
+    }
+
+    if let Some(path) = settings.ca_cert_path.as_deref().filter(|p| !p.is_empty()) {
+        let pem = std::fs::read(path).map_err(|e| {
+            let error_message = format!("Failed to read CA certificate {}: {}", path, e);
+            error!("{}", error_message);
+            error_message
+        })?;
+        let certificate = Certificate::from_pem(&pem).map_err(|e| {
+            let error_message = format!("Invalid CA certificate {}: {}", path, e);
+            error!("{}", error_message);
+            error_message
+        })?;
+        client_builder = client_builder.add_root_certificate(certificate);
+    }
+
+    if !settings.extra_headers.is_empty() {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &settings.extra_headers {
+            let (name, value) = parse_extra_header(name, value)?;
+            headers.insert(name, value);
+        }
+        client_builder = client_builder.default_headers(headers);
+    }
+
+    client_builder.build().map_err(|e| {
+        let error_message = format!("Failed to build HTTP client: {}", e);
+        error!("{}", error_message);
+        error_message
+    })
+}
+
+fn shared_client(settings: Option<&ClientSettings>) -> Result<Client, String> {
+    let settings = settings.cloned().unwrap_or_default();
+    let clients = CLIENTS.get_or_init(|| Mutex::new(HashMap::new()));
+
+    if let Some(client) = clients.lock().unwrap().get(&settings) {
+        return Ok(client.clone());
+    }
+
+    let client = build_client(&settings)?;
+    clients.lock().unwrap().insert(settings, client.clone());
+    Ok(client)
+}
+
+// Payload and header names that hold credentials, compared lowercase with
+// '_' and '-' removed
+const SENSITIVE_NAMES: &[&str] = &[
+    "secret",
+    "password",
+    "passwd",
+    "apikey",
+    "token",
+    "privkey",
+    "privatekey",
+    "psk",
+    "presharedkey",
+    "authorization",
+];
+
+/// Masked auth details are only logged at this level; at any less verbose
+/// level they are left out entirely
+const AUTH_LOG_LEVEL: Level = Level::Trace;
+
+const REDACTED: &str = "[redacted]";
+
+fn is_sensitive(name: &str) -> bool {
+    let name = name.to_lowercase().replace(['_', '-'], "");
+    SENSITIVE_NAMES
+        .iter()
+        .any(|sensitive| name.contains(sensitive))
+}
+
+/// Copy of a JSON payload with credential fields masked, for logging
+fn redact_json(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let value = if is_sensitive(key) && !value.is_object() {
+                        Value::String(REDACTED.to_string())
+                    } else {
+                        redact_json(value)
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact_json).collect()),
+        other => other.clone(),
+    }
+}
+
+// Replaces the RequestBuilder debug output, which includes header values.
+// `body` is a redacted JSON payload or just a size; form and multipart bodies
+// such as a config.xml restore are never logged.
+fn log_request(request_type: &str, url: &str, headers: &HeaderMap, body: Option<String>) {
+    if !log_enabled!(Level::Debug) {
+        return;
+    }
+
+    // Custom profile headers can carry proxy tokens, so only their names
+    let header_names = headers
+        .keys()
+        .map(|name| name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    debug!(
+        "Request {} {} headers: [{}] body: {}",
+        request_type,
+        url,
+        header_names,
+        body.unwrap_or_default()
+    );
+}
+
+fn log_auth() {
+    if log_enabled!(AUTH_LOG_LEVEL) {
+        log::log!(AUTH_LOG_LEVEL, "Using auth header: Basic {}", REDACTED);
+    }
+}
+
+fn classify_status(status: StatusCode, url: &str, body: String) -> ApiError {
+    match status.as_u16() {
+        401 => ApiError::Auth(
+            "Authentication failed (HTTP 401): Your API key or secret is incorrect".to_string(),
+        ),
+        403 => ApiError::Forbidden(
+            "Permission denied (HTTP 403): Your API credentials don't have sufficient permissions"
+                .to_string(),
+        ),
+        404 => {
+            if url.contains("/api/core/tunables/") {
+                ApiError::NotFound("API endpoint not found (HTTP 404): Tunables API requires OPNsense 25.x or newer".to_string())
+            } else {
+                ApiError::NotFound(
+                    "API endpoint not found (HTTP 404): Check your firewall URL and port"
+                        .to_string(),
+                )
+            }
+        }
+        code => ApiError::Status {
+            status: code,
+            message: format!("Request to {} failed with status {}: {}", url, status, body),
+        },
+    }
+}
+
+// reqwest's own message is generic ("error sending request"); the cause,
+// such as a rejected certificate, is further down the source chain
+fn error_chain(e: &reqwest::Error) -> String {
+    let mut message = e.to_string();
+    let mut source = std::error::Error::source(e);
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+fn classify_send_error(e: reqwest::Error, url: &str) -> ApiError {
+    let chain = error_chain(&e);
+
+    if e.is_timeout() {
+        ApiError::Timeout(format!(
+            "Connection timed out: Server at {} is unreachable or not responding. This may be due to high load on the firewall or network congestion.",
+            url
+        ))
+    } else if chain.contains("certificate") || chain.contains("SSL") || chain.contains("TLS") {
+        // Checked before is_connect, which is also set for TLS failures
+        ApiError::Tls(format!("SSL/TLS error: The security certificate at {} could not be verified. For self-signed certificates, disable TLS verification or add your CA certificate in the profile settings.", url))
+    } else if chain.contains("handshake") {
+        ApiError::Tls(format!("TLS handshake error: Failed to establish secure connection to {}. This may be due to protocol incompatibility or firewall restrictions.", url))
+    } else if e.is_connect() {
+        // More detailed connection error message
+        ApiError::Connection(if chain.contains("proxy") {
+            format!("Proxy connection error: Unable to connect through proxy to {}. Check your proxy settings.", url)
+        } else if chain.contains("refused") {
+            format!("Connection refused: The server at {} actively refused the connection. Please verify the port is correct and any firewall rules allow this connection.", url)
+        } else if chain.contains("reset") {
+            format!("Connection reset: The connection to {} was reset. This may indicate network instability or an intermediate firewall blocking the connection.", url)
+        } else {
+            format!("Connection error: Unable to connect to server at {}. Check your network connectivity, firewall settings, and verify the server is running.", url)
+        })
+    } else if e.is_status() {
+        ApiError::Other(format!(
+            "Invalid status: The server at {} returned an unexpected response. This may indicate API changes or incompatibility.",
+            url
+        ))
+    } else if chain.contains("dns error") || chain.contains("not resolve") {
+        ApiError::Connection(format!(
+            "DNS resolution error: Could not resolve hostname in URL {}. Please check your DNS settings and verify the hostname is correct.",
+            url
+        ))
+    } else {
+        ApiError::Other(format!("Request to {} failed: {} - Please check your network connectivity and firewall configuration.", url, e))
+    }
+}
+
+async fn send_request(
+    request_builder: RequestBuilder,
+    url: &str,
+    settings: Option<&ClientSettings>,
+) -> Result<Response, ApiError> {
+    let result = send_and_classify(request_builder, url).await;
+    if let Some(profile_id) = settings.and_then(|settings| settings.profile_id) {
+        crate::connection_monitor::record_result(profile_id, &result);
+    }
+    result
+}
+
+async fn send_and_classify(
+    request_builder: RequestBuilder,
+    url: &str,
+) -> Result<Response, ApiError> {
+    let error = match request_builder.send().await {
+        Ok(response) if response.status().is_success() => {
+            info!("Request to {} successful", url);
+            return Ok(response);
+        }
+        Ok(response) => {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_else(|_| "".to_string());
+            classify_status(status, url, body)
+        }
+        Err(e) => classify_send_error(e, url),
+    };
+
+    error!("{}", error);
+    Err(error)
+}
+
+fn request_builder(client: &Client, request_type: &str, url: &str) -> Result<RequestBuilder, ApiError> {
+    match request_type {
+        "GET" => Ok(client.get(url)),
+        "POST" => Ok(client.post(url)),
+        "PATCH" => Ok(client.patch(url)),
+        "PUT" => Ok(client.put(url)),
+        "DELETE" => Ok(client.delete(url)),
+        _ => {
+            let error = ApiError::Other(format!("Invalid request type: {}", request_type));
+            error!("{}", error);
+            Err(error)
+        }
+    }
+}
+
 /// Makes an HTTP request with a JSON payload
+#[allow(clippy::too_many_arguments)]
 pub async fn make_http_request(
     request_type: &str,
     url: &str,
@@ -17,129 +308,48 @@ pub async fn make_http_request(
     timeout_seconds: Option<u64>,
     api_key: Option<&str>,
     api_secret: Option<&str>,
-) -> Result<Response, String> {
+    settings: Option<&ClientSettings>,
+) -> Result<Response, ApiError> {
     info!("Making a {} request to {}", request_type, url);
 
-    let client_builder = Client::builder().danger_accept_invalid_certs(true);
-    let client = if let Some(timeout_sec) = timeout_seconds {
-        client_builder
-            .timeout(Duration::from_secs(timeout_sec))
-            .build()
-    } else {
-        client_builder.build()
-    }
-    .map_err(|e| {
-        let error_message = format!("Failed to build HTTP client: {}", e);
-        error!("{}", error_message);
-        error_message
-    })?;
+    let client = shared_client(settings).map_err(ApiError::Other)?;
 
-    let mut request_builder = match request_type {
-        "GET" => client.get(url),
-        "POST" => client.post(url),
-        "PATCH" => client.patch(url),
-        "PUT" => client.put(url),
-        _ => {
-            let error_message = "Invalid request type".to_string();
-            error!("{}", error_message);
-            return Err(error_message);
-        }
-    };
+    let mut request_builder = request_builder(&client, request_type, url)?;
+
+    if let Some(timeout_sec) = timeout_seconds {
+        request_builder = request_builder.timeout(Duration::from_secs(timeout_sec));
+    }
 
     if let (Some(key), Some(secret)) = (api_key, api_secret) {
         let auth_string = format!("{}:{}", key, secret);
         let auth = general_purpose::STANDARD.encode(auth_string.as_bytes());
         request_builder = request_builder.header(AUTHORIZATION, format!("Basic {}", auth));
 
-        info!(
-            "Using auth header: Basic {}...{}",
-            &auth[..min(6, auth.len())],
-            &auth[auth.len().saturating_sub(4)..]
-        );
+        log_auth();
     }
 
-    if let Some(headers) = headers {
-        request_builder = request_builder.headers(headers);
-    }
+    let headers = headers.unwrap_or_default();
+    log_request(
+        request_type,
+        url,
+        &headers,
+        payload
+            .as_ref()
+            .map(|payload| redact_json(payload).to_string()),
+    );
+    request_builder = request_builder.headers(headers);
 
     if let Some(payload) = payload {
         request_builder = request_builder.json(&payload);
     }
 
-    info!("Request build is finalized: {:?}", &request_builder);
-
-    match request_builder.send().await {
-        Ok(response) => {
-            if response.status().is_success() {
-                info!("Request to {} successful", url);
-                Ok(response)
-            } else {
-                let status = response.status();
-                let body = response.text().await.unwrap_or_else(|_| "".to_string());
-                let error_message = match status.as_u16() {
-                    401 => "Authentication failed (HTTP 401): Your API key or secret is incorrect".to_string(),
-                    403 => "Permission denied (HTTP 403): Your API credentials don't have sufficient permissions".to_string(),
-                    404 => {
-                        if url.contains("/api/core/tunables/") {
-                            "API endpoint not found (HTTP 404): Tunables API requires OPNsense 25.x or newer".to_string()
-                        } else {
-                            "API endpoint not found (HTTP 404): Check your firewall URL and port".to_string()
-                        }
-                    },
-                    _ => format!("Request to {} failed with status {}: {}", url, status, body)
-                };
-
-                error!("{}", error_message);
-                Err(error_message)
-            }
-        }
-        Err(e) => {
-            let error_message = if e.is_timeout() {
-                format!(
-                    "Connection timed out: Server at {} is unreachable or not responding. This may be due to high load on the firewall or network congestion.",
-                    url
-                )
-            } else if e.is_connect() {
-                // More detailed connection error message
-                if e.to_string().contains("proxy") {
-                    format!("Proxy connection error: Unable to connect through proxy to {}. Check your proxy settings.", url)
-                } else if e.to_string().contains("refused") {
-                    format!("Connection refused: The server at {} actively refused the connection. Please verify the port is correct and any firewall rules allow this connection.", url)
-                } else if e.to_string().contains("reset") {
-                    format!("Connection reset: The connection to {} was reset. This may indicate network instability or an intermediate firewall blocking the connection.", url)
-                } else {
-                    format!("Connection error: Unable to connect to server at {}. Check your network connectivity, firewall settings, and verify the server is running.", url)
-                }
-            } else if e.is_status() {
-                format!(
-                    "Invalid status: The server at {} returned an unexpected response. This may indicate API changes or incompatibility.",
-                    url
-                )
-            } else if e.to_string().contains("dns error") || e.to_string().contains("not resolve") {
-                format!(
-                    "DNS resolution error: Could not resolve hostname in URL {}. Please check your DNS settings and verify the hostname is correct.",
-                    url
-                )
-            } else if e.to_string().contains("certificate")
-                || e.to_string().contains("SSL")
-                || e.to_string().contains("TLS")
-            {
-                format!("SSL/TLS error: There was a problem with the server's security certificate at {}. This is expected for self-signed certificates and doesn't affect functionality.", url)
-            } else if e.to_string().contains("handshake") {
-                format!("TLS handshake error: Failed to establish secure connection to {}. This may be due to protocol incompatibility or firewall restrictions.", url)
-            } else {
-                format!("Request to {} failed: {} - Please check your network connectivity and firewall configuration.", url, e)
-            };
-
-            error!("{}", error_message);
-            Err(error_message)
-        }
-    }
+    send_request(request_builder, url, settings).await
 }
 
 /// Makes an HTTP request with form data
 /// This is used for endpoints that expect application/x-www-form-urlencoded content
 /// instead of JSON
+#[allow(clippy::too_many_arguments)]
 pub async fn make_http_request_with_form_data(
     request_type: &str,
     url: &str,
@@ -148,125 +358,98 @@ pub async fn make_http_request_with_form_data(
     timeout_seconds: Option<u64>,
     api_key: Option<&str>,
     api_secret: Option<&str>,
-) -> Result<Response, String> {
+    settings: Option<&ClientSettings>,
+) -> Result<Response, ApiError> {
     info!("Making a {} form data request to {}", request_type, url);
 
-    let client_builder = Client::builder().danger_accept_invalid_certs(true);
-    let client = if let Some(timeout_sec) = timeout_seconds {
-        client_builder
-            .timeout(Duration::from_secs(timeout_sec))
-            .build()
-    } else {
-        client_builder.build()
-    }
-    .map_err(|e| {
-        let error_message = format!("Failed to build HTTP client: {}", e);
-        error!("{}", error_message);
-        error_message
-    })?;
+    let client = shared_client(settings).map_err(ApiError::Other)?;
 
-    let mut request_builder = match request_type {
-        "GET" => client.get(url),
-        "POST" => client.post(url),
-        "PATCH" => client.patch(url),
-        "PUT" => client.put(url),
-        _ => {
-            let error_message = "Invalid request type".to_string();
-            error!("{}", error_message);
-            return Err(error_message);
-        }
-    };
+    let mut request_builder = request_builder(&client, request_type, url)?;
+
+    if let Some(timeout_sec) = timeout_seconds {
+        request_builder = request_builder.timeout(Duration::from_secs(timeout_sec));
+    }
 
     if let (Some(key), Some(secret)) = (api_key, api_secret) {
         let auth_string = format!("{}:{}", key, secret);
         let auth = general_purpose::STANDARD.encode(auth_string.as_bytes());
         request_builder = request_builder.header(AUTHORIZATION, format!("Basic {}", auth));
 
-        info!(
-            "Using auth header: Basic {}...{}",
-            &auth[..min(6, auth.len())],
-            &auth[auth.len().saturating_sub(4)..]
-        );
+        log_auth();
     }
 
-    // Set the Content-Type header for form data
+    // Default to url-encoded form data unless the caller set its own
+    // Content-Type (e.g. a multipart boundary)
     let mut request_headers = headers.unwrap_or_default();
-    request_headers.insert(
-        CONTENT_TYPE,
-        HeaderValue::from_static("application/x-www-form-urlencoded; charset=UTF-8"),
+    if !request_headers.contains_key(CONTENT_TYPE) {
+        request_headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-www-form-urlencoded; charset=UTF-8"),
+        );
+    }
+    log_request(
+        request_type,
+        url,
+        &request_headers,
+        Some(format!("<{} bytes of form data>", form_data.len())),
     );
     request_builder = request_builder.headers(request_headers);
 
     // Set the form data as a raw string in the request body
     request_builder = request_builder.body(form_data);
 
-    info!("Form data request build is finalized: {:?}", &request_builder);
+    send_request(request_builder, url, settings).await
+}
 
-    match request_builder.send().await {
-        Ok(response) => {
-            if response.status().is_success() {
-                info!("Request to {} successful", url);
-                Ok(response)
-            } else {
-                let status = response.status();
-                let body = response.text().await.unwrap_or_else(|_| "".to_string());
-                let error_message = match status.as_u16() {
-                    401 => "Authentication failed (HTTP 401): Your API key or secret is incorrect".to_string(),
-                    403 => "Permission denied (HTTP 403): Your API credentials don't have sufficient permissions".to_string(),
-                    404 => {
-                        if url.contains("/api/core/tunables/") {
-                            "API endpoint not found (HTTP 404): Tunables API requires OPNsense 25.x or newer".to_string()
-                        } else {
-                            "API endpoint not found (HTTP 404): Check your firewall URL and port".to_string()
-                        }
-                    },
-                    _ => format!("Request to {} failed with status {}: {}", url, status, body)
-                };
-
-                error!("{}", error_message);
-                Err(error_message)
-            }
-        }
-        Err(e) => {
-            let error_message = if e.is_timeout() {
-                format!(
-                    "Connection timed out: Server at {} is unreachable or not responding. This may be due to high load on the firewall or network congestion.",
-                    url
-                )
-            } else if e.is_connect() {
-                // More detailed connection error message
-                if e.to_string().contains("proxy") {
-                    format!("Proxy connection error: Unable to connect through proxy to {}. Check your proxy settings.", url)
-                } else if e.to_string().contains("refused") {
-                    format!("Connection refused: The server at {} actively refused the connection. Please verify the port is correct and any firewall rules allow this connection.", url)
-                } else if e.to_string().contains("reset") {
-                    format!("Connection reset: The connection to {} was reset. This may indicate network instability or an intermediate firewall blocking the connection.", url)
-                } else {
-                    format!("Connection error: Unable to connect to server at {}. Check your network connectivity, firewall settings, and verify the server is running.", url)
-                }
-            } else if e.is_status() {
-                format!(
-                    "Invalid status: The server at {} returned an unexpected response. This may indicate API changes or incompatibility.",
-                    url
-                )
-            } else if e.to_string().contains("dns error") || e.to_string().contains("not resolve") {
-                format!(
-                    "DNS resolution error: Could not resolve hostname in URL {}. Please check your DNS settings and verify the hostname is correct.",
-                    url
-                )
-            } else if e.to_string().contains("certificate")
-                || e.to_string().contains("SSL")
-                || e.to_string().contains("TLS")
-            {
-                format!("SSL/TLS error: There was a problem with the server's security certificate at {}. This is expected for self-signed certificates and doesn't affect functionality.", url)
-            } else if e.to_string().contains("handshake") {
-                format!("TLS handshake error: Failed to establish secure connection to {}. This may be due to protocol incompatibility or firewall restrictions.", url)
-            } else {
-                format!("Request to {} failed: {} - Please check your network connectivity and firewall configuration.", url, e)
-            };
+/// Number of retries used when a profile doesn't override it
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
 
-            error!("{}", error_message);
-            Err(error_message)
+const RETRY_BASE_DELAY_MS: u64 = 250;
+
+/// Makes an HTTP request with a JSON payload, retrying transient failures
+/// (timeouts, connection errors and HTTP 502/503/504) with exponential backoff
+#[allow(clippy::too_many_arguments)]
+pub async fn make_http_request_with_retry(
+    request_type: &str,
+    url: &str,
+    payload: Option<Value>,
+    headers: Option<HeaderMap>,
+    timeout_seconds: Option<u64>,
+    api_key: Option<&str>,
+    api_secret: Option<&str>,
+    settings: Option<&ClientSettings>,
+    max_retries: u32,
+) -> Result<Response, ApiError> {
+    let mut attempt = 0;
+
+    loop {
+        match make_http_request(
+            request_type,
+            url,
+            payload.clone(),
+            headers.clone(),
+            timeout_seconds,
+            api_key,
+            api_secret,
+            settings,
+        )
+        .await
+        {
+            Ok(response) => return Ok(response),
+            Err(e) if attempt < max_retries && e.is_retryable() => {
+                let delay = Duration::from_millis(RETRY_BASE_DELAY_MS << attempt.min(10));
+                attempt += 1;
+                warn!(
+                    "Retry {}/{} for {} in {}ms after error: {}",
+                    attempt,
+                    max_retries,
+                    url,
+                    delay.as_millis(),
+                    e
+                );
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => return Err(e),
         }
     }
 }
@@ -0,0 +1,172 @@
+use crate::db::Database;
+use crate::error::ApiError;
+use crate::http_client::make_http_request;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{json, Value};
+use tauri::State;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IdsAlert {
+    #[serde(default)]
+    timestamp: String,
+    #[serde(default)]
+    src_ip: String,
+    #[serde(default, deserialize_with = "string_or_number")]
+    src_port: String,
+    #[serde(default)]
+    dest_ip: String,
+    #[serde(default, deserialize_with = "string_or_number")]
+    dest_port: String,
+    #[serde(default)]
+    proto: String,
+    #[serde(rename = "alert", default)]
+    signature: String,
+    #[serde(rename = "alert_sid", default, deserialize_with = "string_or_number")]
+    signature_id: String,
+    #[serde(
+        rename = "alert_severity",
+        default,
+        deserialize_with = "string_or_number"
+    )]
+    severity: String,
+    #[serde(rename = "alert_action", default)]
+    action: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IdsAlertsResponse {
+    total: u32,
+    #[serde(rename = "rowCount")]
+    row_count: u32,
+    current: u32,
+    rows: Vec<IdsAlert>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IdsStatus {
+    enabled: bool,
+    running: bool,
+    status: String,
+}
+
+fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
+    format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
+}
+
+fn ids_error(e: ApiError) -> ApiError {
+    e.plugin_missing(
+        "Intrusion detection is not available on this firewall: the IDS API endpoint was not found",
+    )
+}
+
+fn string_or_number<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(s) => s,
+        Value::Number(n) => n.to_string(),
+        _ => String::new(),
+    })
+}
+
+#[tauri::command]
+pub async fn get_ids_alerts(
+    database: State<'_, Database>,
+    limit: Option<u32>,
+    search_phrase: Option<String>,
+) -> Result<IdsAlertsResponse, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, "/api/ids/service/queryAlerts/");
+
+    let payload = json!({
+        "current": 1,
+        "rowCount": limit.unwrap_or(500),
+        "sort": {},
+        "searchPhrase": search_phrase.unwrap_or_default(),
+        "fileid": ""
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await
+    .map_err(ids_error)?;
+
+    response
+        .json::<IdsAlertsResponse>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse IDS alerts: {}", e)))
+}
+
+#[tauri::command]
+pub async fn get_ids_status(database: State<'_, Database>) -> Result<IdsStatus, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let settings_url = build_api_url(&api_info, "/api/ids/settings/get");
+
+    let settings_response = make_http_request(
+        "GET",
+        &settings_url,
+        None,
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await
+    .map_err(ids_error)?;
+
+    let settings = settings_response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse IDS settings: {}", e))?;
+
+    let enabled = settings["ids"]["general"]["enabled"].as_str() == Some("1");
+
+    let status_url = build_api_url(&api_info, "/api/ids/service/status");
+
+    let status_response = make_http_request(
+        "GET",
+        &status_url,
+        None,
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await
+    .map_err(ids_error)?;
+
+    let status_result = status_response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse IDS status: {}", e))?;
+
+    let status = status_result["status"]
+        .as_str()
+        .unwrap_or("unknown")
+        .to_string();
+
+    Ok(IdsStatus {
+        enabled,
+        running: status == "running",
+        status,
+    })
+}
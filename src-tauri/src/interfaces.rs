@@ -1,7 +1,11 @@
-use crate::db::Database;
+use crate::db::{ApiInfo, Database};
+use crate::error::ApiError;
 use crate::http_client::make_http_request;
 use log::{error, info, warn};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
 use tauri::State;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -31,7 +35,7 @@ pub struct Interface {
     is_physical: bool,
     #[serde(default)]
     device: String,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "string_or_number")]
     mtu: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     macaddr_hw: Option<String>,
@@ -65,17 +69,41 @@ pub struct Interface {
     ipv4: Vec<IpAddress>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     ipv6: Vec<IpAddress>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "optional_string_or_number"
+    )]
     vlan_tag: Option<String>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     gateways: Vec<String>,
+    // The fields below are derived by `classify_interface` after parsing
+    #[serde(rename = "type", skip_deserializing)]
+    interface_type: InterfaceType,
+    /// Ports of a LAGG or bridge
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        default,
+        alias = "laggport",
+        deserialize_with = "member_list"
+    )]
+    members: Vec<String>,
+    /// Device a VLAN is tagged on
+    #[serde(skip_serializing_if = "Option::is_none", skip_deserializing)]
+    parent: Option<String>,
 }
 
+// Older firmware leaves out VLAN fields and newer firmware reports the tag
+// as a number, neither of which should fail the whole interface list
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VlanInfo {
+    #[serde(default, deserialize_with = "string_or_number")]
     tag: String,
+    #[serde(default)]
     proto: String,
+    #[serde(default, deserialize_with = "string_or_number")]
     pcp: String,
+    #[serde(default)]
     parent: String,
 }
 
@@ -84,8 +112,250 @@ pub struct IpAddress {
     ipaddr: String,
 }
 
+#[derive(Serialize, Debug)]
+pub struct InterfaceSummary {
+    name: String,
+    device: String,
+    description: String,
+    enabled: bool,
+    addresses: Vec<String>,
+    /// True when this interface holds the address the app talks to, so
+    /// disabling it would cut off the API connection
+    carries_api_connection: bool,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum InterfaceType {
+    Physical,
+    Vlan,
+    Lagg,
+    Bridge,
+    #[default]
+    Other,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TopologyGateway {
+    name: String,
+    address: String,
+    ip_version: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TopologyNode {
+    device: String,
+    /// Assignment such as "lan" or "opt2"; unassigned devices have none
+    identifier: Option<String>,
+    description: String,
+    #[serde(rename = "type")]
+    interface_type: InterfaceType,
+    status: String,
+    addresses: Vec<String>,
+    /// Device a VLAN is tagged on
+    parent: Option<String>,
+    vlan_tag: Option<u16>,
+    /// Ports of a LAGG or bridge
+    members: Vec<String>,
+    /// LAGG or bridge this device is a port of
+    member_of: Option<String>,
+    /// VLANs tagged on this device
+    children: Vec<String>,
+    gateways: Vec<TopologyGateway>,
+}
+
+/// Interfaces and how they relate, from one call. Each source is fetched on
+/// its own; one that failed is listed under its name in `errors` and the
+/// nodes are built from the rest.
+#[derive(Serialize, Debug)]
+pub struct NetworkTopology {
+    nodes: Vec<TopologyNode>,
+    errors: HashMap<String, String>,
+}
+
+fn string_or_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(s) => s,
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => String::new(),
+    })
+}
+
+fn optional_string_or_number<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Ok(Some(string_or_number(deserializer)?).filter(|s| !s.is_empty()))
+}
+
+// Members come as a list, a comma separated string, or an object keyed by
+// port name with the port flags as values (ifconfig's laggport lines)
+fn member_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    let members: Vec<String> = match Value::deserialize(deserializer)? {
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| match item {
+                Value::String(s) => Some(s.clone()),
+                other => get_string_value(other, &["name", "laggport", "member"]),
+            })
+            .collect(),
+        Value::Object(ports) => ports.keys().cloned().collect(),
+        Value::String(s) => s.split([',', ' ']).map(str::to_string).collect(),
+        _ => Vec::new(),
+    };
+
+    Ok(members
+        .into_iter()
+        .map(|member| member.trim().to_string())
+        .filter(|member| !member.is_empty())
+        .collect())
+}
+
+fn guess_interface_type(device: &str, is_physical: bool) -> InterfaceType {
+    if device.starts_with("vlan") || device.contains('.') {
+        InterfaceType::Vlan
+    } else if device.starts_with("lagg") {
+        InterfaceType::Lagg
+    } else if device.starts_with("bridge") {
+        InterfaceType::Bridge
+    } else if is_physical {
+        InterfaceType::Physical
+    } else {
+        InterfaceType::Other
+    }
+}
+
+/// Fills in the type, VLAN parent and tag from what the API reported
+fn classify_interface(interface: &mut Interface) {
+    interface.interface_type = guess_interface_type(&interface.device, interface.is_physical);
+
+    if let Some(vlan) = &interface.vlan {
+        interface.interface_type = InterfaceType::Vlan;
+        interface.parent = Some(vlan.parent.clone()).filter(|parent| !parent.is_empty());
+        if interface.vlan_tag.is_none() && !vlan.tag.is_empty() {
+            interface.vlan_tag = Some(vlan.tag.clone());
+        }
+    }
+
+    // "igb0.100" style names carry the parent and tag; OPNsense's own
+    // "vlan0.N" names are only a sequence number
+    if interface.interface_type == InterfaceType::Vlan
+        && interface.parent.is_none()
+        && !interface.device.starts_with("vlan")
+    {
+        if let Some((parent, tag)) = interface.device.rsplit_once('.') {
+            interface.parent = Some(parent.to_string());
+            if interface.vlan_tag.is_none() {
+                interface.vlan_tag = Some(tag.to_string());
+            }
+        }
+    }
+}
+
+fn classify_interfaces(mut interfaces: Vec<Interface>) -> Vec<Interface> {
+    interfaces.iter_mut().for_each(classify_interface);
+    interfaces
+}
+
+fn interface_addresses(interface: &Interface) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    interface
+        .ipv4
+        .iter()
+        .chain(interface.ipv6.iter())
+        .map(|address| address.ipaddr.clone())
+        .chain(interface.addr4.clone())
+        .chain(interface.addr6.clone())
+        .filter(|address| !address.is_empty() && seen.insert(address.clone()))
+        .collect()
+}
+
+/// IPv4 networks configured on an interface, e.g. "192.0.2.1/24"
+pub(crate) async fn interface_ipv4_networks(
+    database: State<'_, Database>,
+    name: &str,
+) -> Result<Vec<String>, ApiError> {
+    let interfaces = get_interfaces(database).await?;
+    let interface = interfaces
+        .iter()
+        .find(|interface| interface.identifier == name || interface.device == name)
+        .ok_or_else(|| format!("Interface {} not found", name))?;
+
+    Ok(interface_addresses(interface)
+        .into_iter()
+        .filter(|address| !address.contains(':') && address.contains('/'))
+        .collect())
+}
+
+fn api_host(api_url: &str) -> String {
+    reqwest::Url::parse(api_url)
+        .ok()
+        .and_then(|url| {
+            url.host_str()
+                .map(|host| host.trim_matches(['[', ']']).to_string())
+        })
+        .unwrap_or_default()
+}
+
+// The API URL may name the firewall by hostname, so it is resolved to the
+// addresses the interfaces are compared against
+async fn api_addresses(api_info: &ApiInfo) -> Vec<IpAddr> {
+    let host = api_host(&api_info.api_url);
+    if host.is_empty() {
+        return Vec::new();
+    }
+
+    let resolved = tokio::net::lookup_host((host.as_str(), api_info.port)).await;
+    match resolved {
+        Ok(addresses) => addresses.map(|address| address.ip()).collect(),
+        Err(e) => {
+            warn!("Failed to resolve API host {}: {}", host, e);
+            Vec::new()
+        }
+    }
+}
+
+// Addresses are reported as "192.0.2.1/24", so compare without the prefix
+fn carries_api_connection(addresses: &[String], api_addresses: &[IpAddr]) -> bool {
+    addresses
+        .iter()
+        .filter_map(|address| address.split('/').next()?.parse::<IpAddr>().ok())
+        .any(|address| api_addresses.contains(&address))
+}
+
+/// Compact view of the assigned interfaces. OPNsense has no API for enabling
+/// or disabling interface assignments, so this is read-only.
 #[tauri::command]
-pub async fn get_interfaces(database: State<'_, Database>) -> Result<Vec<Interface>, String> {
+pub async fn list_interfaces(
+    database: State<'_, Database>,
+) -> Result<Vec<InterfaceSummary>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+    let api_addresses = api_addresses(&api_info).await;
+
+    let interfaces = get_interfaces(database).await?;
+
+    Ok(interfaces
+        .iter()
+        .map(|interface| {
+            let addresses = interface_addresses(interface);
+
+            InterfaceSummary {
+                name: interface.identifier.clone(),
+                device: interface.device.clone(),
+                description: interface.description.clone(),
+                enabled: interface.enabled,
+                carries_api_connection: carries_api_connection(&addresses, &api_addresses),
+                addresses,
+            }
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_interfaces(database: State<'_, Database>) -> Result<Vec<Interface>, ApiError> {
     info!("Fetching interface information");
 
     // Track the start time for performance measurements
@@ -137,6 +407,7 @@ pub async fn get_interfaces(database: State<'_, Database>) -> Result<Vec<Interfa
                         Some(current_timeout),
                         Some(&api_info.api_key),
                         Some(&api_info.api_secret),
+                        Some(&api_info.client_settings()),
                     ).await {
                         Ok(response) => {
                             match response.text().await {
@@ -250,12 +521,13 @@ pub async fn get_interfaces(database: State<'_, Database>) -> Result<Vec<Interfa
                         },
                         Err(e) => {
                             error!("Request error for interface page {}: {}", current_page, e);
-                            last_error = e;
+                            let is_timeout = matches!(e, ApiError::Timeout(_));
+                            last_error = e.into();
                             retry_count += 1;
                             current_timeout += 5;
 
                             // Special handling for common errors
-                            if last_error.contains("timeout") || last_error.contains("timed out") {
+                            if is_timeout {
                                 warn!("Timeout detected, increasing timeout for retry");
                                 current_timeout += 10; // Add extra time for timeout errors
                             }
@@ -292,7 +564,8 @@ pub async fn get_interfaces(database: State<'_, Database>) -> Result<Vec<Interfa
             // If no interfaces were retrieved, try our fallback approach
             info!("Attempting fallback interface retrieval method...");
             return try_alternative_interface_fetch(&api_info).await
-                .map_err(|e| format!("All interface fetch methods failed. Primary: timeout after 45s. Fallback: {}", e));
+                .map(classify_interfaces)
+                .map_err(|e| ApiError::Other(format!("All interface fetch methods failed. Primary: timeout after 45s. Fallback: {}", e)));
         } else {
             // Return what we have so far with a warning
             info!(
@@ -309,11 +582,12 @@ pub async fn get_interfaces(database: State<'_, Database>) -> Result<Vec<Interfa
             );
             return try_alternative_interface_fetch(&api_info)
                 .await
+                .map(classify_interfaces)
                 .map_err(|e2| {
-                    format!(
+                    ApiError::Other(format!(
                         "All interface fetch methods failed. Primary: {}. Fallback: {}",
                         e, e2
-                    )
+                    ))
                 });
         } else {
             // We have partial data, log the error but return what we have
@@ -428,7 +702,7 @@ pub async fn get_interfaces(database: State<'_, Database>) -> Result<Vec<Interfa
     }
 
     // Replace original with filtered
-    all_interfaces = filtered_interfaces;
+    all_interfaces = classify_interfaces(filtered_interfaces);
 
     // Sort interfaces - prioritize active and assigned interfaces
     all_interfaces.sort_by(|a, b| {
@@ -496,7 +770,7 @@ pub async fn get_interfaces(database: State<'_, Database>) -> Result<Vec<Interfa
 // Alternative interface fetch method that tries a different API endpoint
 async fn try_alternative_interface_fetch(
     api_info: &crate::db::ApiInfo,
-) -> Result<Vec<Interface>, String> {
+) -> Result<Vec<Interface>, ApiError> {
     info!("Using alternative interface fetch method");
 
     // Try multiple alternative endpoints (in order of preference)
@@ -547,7 +821,8 @@ async fn try_alternative_interface_fetch(
                 "All alternative methods failed. Errors: {}. Last error: {}",
                 endpoint_errors.join("; "),
                 e
-            ))
+            )
+            .into())
         }
     }
 }
@@ -556,7 +831,7 @@ async fn try_alternative_interface_fetch(
 async fn fetch_interfaces_from_endpoint(
     url: &str,
     api_info: &crate::db::ApiInfo,
-) -> Result<Vec<Interface>, String> {
+) -> Result<Vec<Interface>, ApiError> {
     // Try both GET and POST methods
     let methods = ["GET", "POST"];
     let mut last_error = String::new();
@@ -582,6 +857,7 @@ async fn fetch_interfaces_from_endpoint(
             Some(20), // 20 second timeout
             Some(&api_info.api_key),
             Some(&api_info.api_secret),
+            Some(&api_info.client_settings()),
         )
         .await
         {
@@ -609,7 +885,7 @@ async fn fetch_interfaces_from_endpoint(
         }
     }
 
-    Err(last_error)
+    Err(last_error.into())
 }
 
 // Parse interface response based on its format
@@ -707,6 +983,9 @@ fn convert_json_to_interfaces(json_array: &[serde_json::Value]) -> Vec<Interface
             ipv6: Vec::new(),
             vlan_tag: None,
             gateways: Vec::new(),
+            interface_type: InterfaceType::Other,
+            members: Vec::new(),
+            parent: None,
         };
 
         interfaces.push(interface);
@@ -734,7 +1013,7 @@ fn get_string_value(json: &serde_json::Value, possible_fields: &[&str]) -> Optio
 // Last-resort attempt to get any interface data
 async fn try_extract_any_interfaces(
     api_info: &crate::db::ApiInfo,
-) -> Result<Vec<Interface>, String> {
+) -> Result<Vec<Interface>, ApiError> {
     // Try to get status page data which usually contains interface information
     let url = format!(
         "{}:{}/api/core/system/status",
@@ -749,6 +1028,7 @@ async fn try_extract_any_interfaces(
         Some(15),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await {
         if let Ok(text) = response.text().await {
@@ -764,7 +1044,7 @@ async fn try_extract_any_interfaces(
         }
     }
 
-    Err("Unable to retrieve any interface data from the firewall. The API may not be compatible or accessible.".to_string())
+    Err("Unable to retrieve any interface data from the firewall. The API may not be compatible or accessible.".to_string().into())
 }
 
 // Recursively search JSON for interface data
@@ -823,7 +1103,7 @@ fn extract_interfaces_from_json(json: &serde_json::Value) -> Option<Vec<Interfac
 pub async fn get_interface_details(
     device: String,
     database: State<'_, Database>,
-) -> Result<Interface, String> {
+) -> Result<Interface, ApiError> {
     info!("Getting details for interface: {}", device);
 
     // Get all interfaces and filter for the requested one
@@ -832,5 +1112,319 @@ pub async fn get_interface_details(
     interfaces
         .into_iter()
         .find(|iface| iface.device == device)
-        .ok_or_else(|| format!("Interface '{}' not found", device))
+        .ok_or_else(|| ApiError::NotFound(format!("Interface '{}' not found", device)))
+}
+
+async fn search_rows(api_info: &ApiInfo, endpoint: &str) -> Result<Vec<Value>, ApiError> {
+    let url = format!("{}:{}{}", api_info.api_url, api_info.port, endpoint);
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(serde_json::json!({
+            "current": 1,
+            "rowCount": -1,
+            "sort": {},
+            "searchPhrase": ""
+        })),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let body = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    Ok(body["rows"].as_array().cloned().unwrap_or_default())
+}
+
+// A single unpaged request; rows that don't parse are skipped rather than
+// failing the whole overview
+async fn fetch_interface_overview(api_info: &ApiInfo) -> Result<Vec<Interface>, ApiError> {
+    let rows = search_rows(api_info, "/api/interfaces/overview/interfacesInfo").await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            serde_json::from_value::<Interface>(row)
+                .map_err(|e| warn!("Skipping unparsable interface: {}", e))
+                .ok()
+        })
+        .map(|mut interface| {
+            classify_interface(&mut interface);
+            interface
+        })
+        .collect())
+}
+
+// LAGG and bridge members are a comma separated list
+fn split_members(row: &Value) -> Vec<String> {
+    get_string_value(row, &["members"])
+        .unwrap_or_default()
+        .split(',')
+        .map(|member| member.trim().to_string())
+        .filter(|member| !member.is_empty())
+        .collect()
+}
+
+fn topology_node(device: &str) -> TopologyNode {
+    TopologyNode {
+        device: device.to_string(),
+        identifier: None,
+        description: String::new(),
+        interface_type: guess_interface_type(device, false),
+        status: String::new(),
+        addresses: Vec::new(),
+        parent: None,
+        vlan_tag: None,
+        members: Vec::new(),
+        member_of: None,
+        children: Vec::new(),
+        gateways: Vec::new(),
+    }
+}
+
+fn topology_section<T>(
+    errors: &mut HashMap<String, String>,
+    name: &str,
+    result: Result<T, ApiError>,
+) -> T
+where
+    T: Default,
+{
+    result.unwrap_or_else(|e| {
+        errors.insert(name.to_string(), e.to_string());
+        T::default()
+    })
+}
+
+#[tauri::command]
+pub async fn get_network_topology(
+    database: State<'_, Database>,
+) -> Result<NetworkTopology, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    // join! rather than try_join! so one failing source does not discard the rest
+    let (overview, vlans, laggs, bridges, gateways) = tokio::join!(
+        fetch_interface_overview(&api_info),
+        search_rows(&api_info, "/api/interfaces/vlan_settings/searchItem"),
+        search_rows(&api_info, "/api/interfaces/lagg_settings/searchItem"),
+        search_rows(&api_info, "/api/interfaces/bridge_settings/searchItem"),
+        search_rows(&api_info, "/api/routing/settings/searchGateway"),
+    );
+
+    let mut errors = HashMap::new();
+    let overview = topology_section(&mut errors, "interfaces", overview);
+    let vlans = topology_section(&mut errors, "vlans", vlans);
+    let laggs = topology_section(&mut errors, "laggs", laggs);
+    let bridges = topology_section(&mut errors, "bridges", bridges);
+    let gateways = topology_section(&mut errors, "gateways", gateways);
+
+    // Sorted by device so the response is stable between calls
+    let mut nodes: BTreeMap<String, TopologyNode> = BTreeMap::new();
+
+    for interface in &overview {
+        if interface.device.is_empty() {
+            continue;
+        }
+        let node = nodes
+            .entry(interface.device.clone())
+            .or_insert_with(|| topology_node(&interface.device));
+        node.identifier = Some(interface.identifier.clone()).filter(|id| !id.is_empty());
+        node.description = interface.description.clone();
+        node.interface_type = interface.interface_type;
+        node.status = interface.status.clone();
+        node.addresses = interface_addresses(interface);
+        node.parent = interface.parent.clone();
+        node.vlan_tag = interface
+            .vlan_tag
+            .as_deref()
+            .and_then(|tag| tag.parse().ok());
+        node.members = interface.members.clone();
+    }
+
+    // Aggregates seen in the overview may have no LAGG or bridge config entry
+    let overview_members: Vec<(String, String)> = nodes
+        .values()
+        .flat_map(|node| {
+            node.members
+                .iter()
+                .map(|member| (member.clone(), node.device.clone()))
+        })
+        .collect();
+    for (member, aggregate) in overview_members {
+        nodes
+            .entry(member.clone())
+            .or_insert_with(|| topology_node(&member))
+            .member_of = Some(aggregate);
+    }
+
+    for row in &vlans {
+        let Some(device) = get_string_value(row, &["vlanif"]) else {
+            continue;
+        };
+        let node = nodes
+            .entry(device.clone())
+            .or_insert_with(|| topology_node(&device));
+        node.interface_type = InterfaceType::Vlan;
+        if let Some(parent) = get_string_value(row, &["if"]).filter(|p| !p.is_empty()) {
+            node.parent = Some(parent);
+        }
+        if let Some(tag) = get_string_value(row, &["tag"]).and_then(|tag| tag.parse().ok()) {
+            node.vlan_tag = Some(tag);
+        }
+        if node.description.is_empty() {
+            node.description = get_string_value(row, &["descr"]).unwrap_or_default();
+        }
+    }
+
+    for (rows, device_field, interface_type) in [
+        (&laggs, "laggif", InterfaceType::Lagg),
+        (&bridges, "bridgeif", InterfaceType::Bridge),
+    ] {
+        for row in rows {
+            let Some(device) = get_string_value(row, &[device_field]) else {
+                continue;
+            };
+            let members = split_members(row);
+            for member in &members {
+                nodes
+                    .entry(member.clone())
+                    .or_insert_with(|| topology_node(member))
+                    .member_of = Some(device.clone());
+            }
+
+            let node = nodes
+                .entry(device.clone())
+                .or_insert_with(|| topology_node(&device));
+            node.interface_type = interface_type;
+            node.members = members;
+            if node.description.is_empty() {
+                node.description = get_string_value(row, &["descr"]).unwrap_or_default();
+            }
+        }
+    }
+
+    // VLAN parents may only be known from the VLAN config
+    let vlan_parents: Vec<(String, String)> = nodes
+        .values()
+        .filter_map(|node| Some((node.parent.clone()?, node.device.clone())))
+        .collect();
+    for (parent, vlan) in vlan_parents {
+        nodes
+            .entry(parent.clone())
+            .or_insert_with(|| topology_node(&parent))
+            .children
+            .push(vlan);
+    }
+
+    // Gateways reference the assignment ("wan"), not the device
+    for row in &gateways {
+        if get_string_value(row, &["disabled"]).as_deref() == Some("1") {
+            continue;
+        }
+        let Some(assignment) = get_string_value(row, &["interface"]) else {
+            continue;
+        };
+        if let Some(node) = nodes
+            .values_mut()
+            .find(|node| node.identifier.as_deref() == Some(assignment.as_str()))
+        {
+            node.gateways.push(TopologyGateway {
+                name: get_string_value(row, &["name"]).unwrap_or_default(),
+                address: get_string_value(row, &["gateway"]).unwrap_or_default(),
+                ip_version: get_string_value(row, &["ipprotocol"]).unwrap_or_default(),
+            });
+        }
+    }
+
+    Ok(NetworkTopology {
+        nodes: nodes.into_values().collect(),
+        errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Four of six NICs in a LAGG with VLANs on top, as reported by
+    // interfacesInfo on 24.x
+    const LAGG_VLAN_FIXTURE: &str = r#"{
+        "total": 8, "rowCount": 8, "current": 1,
+        "rows": [
+            {"device": "igb0", "is_physical": true, "status": "up", "macaddr": "00:0d:b9:00:00:01"},
+            {"device": "igb1", "is_physical": true, "status": "up", "macaddr": "00:0d:b9:00:00:02"},
+            {"device": "igb2", "is_physical": true, "status": "up", "macaddr": "00:0d:b9:00:00:03"},
+            {"device": "igb3", "is_physical": true, "status": "up", "macaddr": "00:0d:b9:00:00:04"},
+            {"device": "igb4", "is_physical": true, "status": "up", "identifier": "wan", "mtu": 1500},
+            {
+                "device": "lagg0", "is_physical": false, "status": "up", "identifier": "lan",
+                "laggport": {
+                    "igb0": "flags=1c<ACTIVE,COLLECTING,DISTRIBUTING>",
+                    "igb1": "flags=1c<ACTIVE,COLLECTING,DISTRIBUTING>",
+                    "igb2": "flags=1c<ACTIVE,COLLECTING,DISTRIBUTING>",
+                    "igb3": "flags=1c<ACTIVE,COLLECTING,DISTRIBUTING>"
+                }
+            },
+            {
+                "device": "vlan0.10", "is_physical": false, "status": "up", "identifier": "opt1",
+                "vlan": {"tag": 10, "proto": "802.1q", "pcp": 0, "parent": "lagg0"}
+            },
+            {"device": "lagg0.20", "is_physical": false, "status": "up", "identifier": "opt2"}
+        ]
+    }"#;
+
+    fn find<'a>(interfaces: &'a [Interface], device: &str) -> &'a Interface {
+        interfaces
+            .iter()
+            .find(|interface| interface.device == device)
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_lagg_and_vlan_layout() {
+        let interfaces =
+            classify_interfaces(try_parse_interface_response(LAGG_VLAN_FIXTURE).unwrap());
+        assert_eq!(interfaces.len(), 8);
+
+        let lagg = find(&interfaces, "lagg0");
+        assert_eq!(lagg.interface_type, InterfaceType::Lagg);
+        assert_eq!(lagg.members, ["igb0", "igb1", "igb2", "igb3"]);
+
+        let nic = find(&interfaces, "igb4");
+        assert_eq!(nic.interface_type, InterfaceType::Physical);
+        assert_eq!(nic.mtu, "1500");
+
+        // Tag and parent from the vlan object
+        let vlan = find(&interfaces, "vlan0.10");
+        assert_eq!(vlan.interface_type, InterfaceType::Vlan);
+        assert_eq!(vlan.parent.as_deref(), Some("lagg0"));
+        assert_eq!(vlan.vlan_tag.as_deref(), Some("10"));
+
+        // Tag and parent from the device name
+        let vlan = find(&interfaces, "lagg0.20");
+        assert_eq!(vlan.interface_type, InterfaceType::Vlan);
+        assert_eq!(vlan.parent.as_deref(), Some("lagg0"));
+        assert_eq!(vlan.vlan_tag.as_deref(), Some("20"));
+    }
+
+    #[test]
+    fn matches_api_address_without_prefix() {
+        let addresses = vec!["192.168.1.1/24".to_string(), "fd00::1/64".to_string()];
+        let ip = |address: &str| -> IpAddr { address.parse().unwrap() };
+
+        assert!(carries_api_connection(&addresses, &[ip("192.168.1.1")]));
+        assert!(carries_api_connection(&addresses, &[ip("fd00::1")]));
+        assert!(!carries_api_connection(&addresses, &[ip("192.168.1.10")]));
+        assert!(!carries_api_connection(&addresses, &[]));
+    }
 }
@@ -0,0 +1,244 @@
+use crate::db::Database;
+use crate::error::ApiError;
+use crate::http_client::make_http_request;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::State;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IpsecChild {
+    name: String,
+    description: String,
+    local_subnets: String,
+    remote_subnets: String,
+    state: String,
+    bytes_in: u64,
+    bytes_out: u64,
+    install_time: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IpsecTunnel {
+    id: String,
+    description: String,
+    remote_gateway: String,
+    connected: bool,
+    state: String,
+    bytes_in: u64,
+    bytes_out: u64,
+    established: String,
+    phase2: Vec<IpsecChild>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IpsecStatusResponse {
+    total: u32,
+    #[serde(rename = "rowCount")]
+    row_count: u32,
+    current: u32,
+    rows: Vec<IpsecTunnel>,
+}
+
+fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
+    format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
+}
+
+/// Returns the first non-empty value among the given keys, since the legacy
+/// and current session APIs name the same attributes differently
+fn first_field(row: &Value, keys: &[&str]) -> String {
+    keys.iter()
+        .filter_map(|key| match row.get(*key) {
+            Some(Value::String(s)) => Some(s.clone()),
+            Some(Value::Number(n)) => Some(n.to_string()),
+            _ => None,
+        })
+        .find(|v| !v.is_empty())
+        .unwrap_or_default()
+}
+
+fn first_u64(row: &Value, keys: &[&str]) -> u64 {
+    first_field(row, keys).parse().unwrap_or(0)
+}
+
+fn parse_child(row: &Value) -> IpsecChild {
+    IpsecChild {
+        name: first_field(row, &["name", "reqid"]),
+        description: first_field(row, &["phase2desc", "description"]),
+        local_subnets: first_field(row, &["local-ts", "local_ts"]),
+        remote_subnets: first_field(row, &["remote-ts", "remote_ts"]),
+        state: first_field(row, &["state"]),
+        bytes_in: first_u64(row, &["bytes-in", "bytes_in"]),
+        bytes_out: first_u64(row, &["bytes-out", "bytes_out"]),
+        install_time: first_field(row, &["install-time", "install_time"]),
+    }
+}
+
+fn parse_tunnel(row: &Value, phase2: Vec<IpsecChild>) -> IpsecTunnel {
+    let connected = match &row["connected"] {
+        Value::Bool(connected) => *connected,
+        other => other.as_str() == Some("1") || other.as_str() == Some("true"),
+    };
+
+    IpsecTunnel {
+        id: first_field(row, &["name", "id"]),
+        description: first_field(row, &["phase1desc", "description"]),
+        remote_gateway: first_field(row, &["remote-addrs", "remote_addrs", "remote-host"]),
+        connected,
+        state: first_field(row, &["state"]),
+        bytes_in: first_u64(row, &["bytes-in", "bytes_in"]),
+        bytes_out: first_u64(row, &["bytes-out", "bytes_out"]),
+        established: first_field(row, &["established", "install-time"]),
+        phase2,
+    }
+}
+
+async fn post_search(
+    api_info: &crate::db::ApiInfo,
+    endpoint: &str,
+    payload: Value,
+) -> Result<Value, ApiError> {
+    let url = build_api_url(api_info, endpoint);
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    response
+        .json::<Value>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse IPsec sessions: {}", e)))
+}
+
+#[tauri::command]
+pub async fn get_ipsec_status(
+    database: State<'_, Database>,
+) -> Result<IpsecStatusResponse, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let search_payload = json!({
+        "current": 1,
+        "rowCount": -1,
+        "sort": {},
+        "searchPhrase": ""
+    });
+
+    // 23.x and later (including the 24.x connections rework) use snake_case
+    // session endpoints, older firmware only knows the camelCase ones
+    let (phase1, phase2_endpoint) = match post_search(
+        &api_info,
+        "/api/ipsec/sessions/search_phase1",
+        search_payload.clone(),
+    )
+    .await
+    {
+        Ok(result) => (result, "/api/ipsec/sessions/search_phase2"),
+        Err(ApiError::NotFound(_)) => {
+            let result = post_search(
+                &api_info,
+                "/api/ipsec/sessions/searchPhase1",
+                search_payload,
+            )
+            .await?;
+            (result, "/api/ipsec/sessions/searchPhase2")
+        }
+        Err(e) => return Err(e),
+    };
+
+    let mut rows = Vec::new();
+    for row in phase1["rows"].as_array().cloned().unwrap_or_default() {
+        let id = first_field(&row, &["name", "id"]);
+
+        let phase2 = post_search(&api_info, phase2_endpoint, json!({ "id": id }))
+            .await
+            .map(|result| {
+                result["rows"]
+                    .as_array()
+                    .map(|children| children.iter().map(parse_child).collect())
+                    .unwrap_or_default()
+            })
+            .unwrap_or_default();
+
+        rows.push(parse_tunnel(&row, phase2));
+    }
+
+    let total = phase1["total"].as_u64().unwrap_or(rows.len() as u64) as u32;
+
+    Ok(IpsecStatusResponse {
+        total,
+        row_count: rows.len() as u32,
+        current: 1,
+        rows,
+    })
+}
+
+async fn change_connection(
+    database: State<'_, Database>,
+    action: &str,
+    connection_id: &str,
+) -> Result<Value, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(
+        &api_info,
+        &format!("/api/ipsec/sessions/{}/{}", action, connection_id),
+    );
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(json!({})),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    response
+        .json::<Value>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
+}
+
+#[tauri::command]
+pub async fn ipsec_connect(
+    database: State<'_, Database>,
+    connection_id: String,
+) -> Result<Value, ApiError> {
+    crate::audit::audited(
+        &database,
+        "ipsec_connect",
+        &connection_id,
+        change_connection(database.clone(), "connect", &connection_id),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn ipsec_disconnect(
+    database: State<'_, Database>,
+    connection_id: String,
+) -> Result<Value, ApiError> {
+    crate::audit::audited(
+        &database,
+        "ipsec_disconnect",
+        &connection_id,
+        change_connection(database.clone(), "disconnect", &connection_id),
+    )
+    .await
+}
@@ -1,26 +1,61 @@
+mod alerts;
 mod alias;
+mod app_logs;
+mod audit;
+mod backup;
+mod captiveportal;
+mod certificates;
 mod commands;
+mod connection_monitor;
+mod cron;
 mod dashboard;
 mod db;
 mod devices;
+mod dhcp;
+mod diagnostics;
+mod dns;
+mod error;
 mod firewall;
 mod firewall_logs;
+mod ha;
 mod http_client;
+mod ids;
 mod interfaces;
+mod ipsec;
+mod openvpn;
+mod oui;
+mod pending;
 mod pin_cache;
 mod power;
+mod privileges;
+mod reporting;
+mod resolve;
+mod response_cache;
 mod routes;
+mod shaper;
 mod snapshots;
+mod states;
+mod system_logs;
 mod system_resources;
+mod timeouts;
 mod traffic;
 mod tunables;
 mod unbound;
 mod update_checker;
+mod users;
+mod wireguard;
 mod wol;
 
+use alerts::register_alert_monitor;
+use app_logs::apply_log_level;
+use connection_monitor::register_connection_monitor;
 use db::Database;
 use firewall_logs::register_log_cache;
+use pending::register_pending_changes;
 use pin_cache::PinCache;
+use power::register_reboot_scheduler;
+use resolve::register_ptr_cache;
+use response_cache::register_response_cache;
 use tauri::Manager;
 use traffic::register_traffic_cache;
 
@@ -28,7 +63,14 @@ use traffic::register_traffic_cache;
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
-        .plugin(tauri_plugin_log::Builder::new().build())
+        .plugin(tauri_plugin_notification::init())
+        // Everything is passed through here and narrowed to the stored
+        // level once the database is open in setup
+        .plugin(
+            tauri_plugin_log::Builder::new()
+                .level(log::LevelFilter::Trace)
+                .build(),
+        )
         .setup(|app| {
             let pin_cache = PinCache::new();
             app.manage(pin_cache);
@@ -36,8 +78,16 @@ pub fn run() {
             let db = Database::new(app.handle()).expect("Failed to initialize database");
             app.manage(db);
 
+            apply_log_level(app).expect("Failed to apply log level");
+
             register_log_cache(app).expect("Failed to register log cache");
             register_traffic_cache(app).expect("Failed to register traffic cache");
+            register_response_cache(app).expect("Failed to register response cache");
+            register_ptr_cache(app).expect("Failed to register PTR cache");
+            register_pending_changes(app).expect("Failed to register pending changes");
+            register_connection_monitor(app).expect("Failed to register connection monitor");
+            register_alert_monitor(app).expect("Failed to register alert monitor");
+            register_reboot_scheduler(app).expect("Failed to register reboot scheduler");
 
             Ok(())
         })
@@ -55,6 +105,17 @@ pub fn run() {
             commands::test_api_connection,
             commands::get_dashboard_preferences,
             commands::save_dashboard_preferences,
+            commands::test_connection,
+            commands::detect_api_endpoint,
+            commands::get_current_privileges,
+            commands::get_profile_headers,
+            commands::save_profile_headers,
+            commands::list_favorites,
+            commands::add_favorite,
+            commands::remove_favorite,
+            commands::export_profiles,
+            commands::import_profiles,
+            commands::clone_api_profile,
             pin_cache::set_pin,
             pin_cache::clear_pin,
             pin_cache::verify_pin,
@@ -62,6 +123,7 @@ pub fn run() {
             devices::get_ndp_devices,
             devices::get_combined_devices,
             devices::flush_arp_table,
+            devices::get_device_counts,
             alias::list_network_aliases,
             alias::remove_ip_from_alias,
             alias::add_ip_to_alias,
@@ -71,10 +133,22 @@ pub fn run() {
             alias::delete_alias,
             alias::apply_alias_changes,
             alias::add_alias,
+            alias::bulk_add_ips_to_alias,
+            alias::bulk_remove_ips_from_alias,
+            alias::get_alias_references,
+            alias::block_ip_from_log,
             dashboard::get_gateway_status,
             dashboard::get_services,
             dashboard::restart_service,
             dashboard::get_system_time,
+            dashboard::get_gateway_metrics,
+            dashboard::reset_gateway_monitor,
+            dashboard::start_service,
+            dashboard::stop_service,
+            dashboard::get_all_profiles_gateway_status,
+            dashboard::get_system_health,
+            dashboard::get_gateway_groups,
+            dashboard::restart_stopped_services,
             firewall::get_firewall_rules,
             firewall::check_api_version,
             firewall::get_interface_list,
@@ -86,6 +160,12 @@ pub fn run() {
             firewall::list_network_select_options,
             firewall::set_rule,
             firewall::get_rule,
+            firewall::get_nat_rules,
+            firewall::toggle_nat_rule,
+            firewall::add_basic_firewall_rule,
+            firewall::move_firewall_rule,
+            firewall::get_pending_firewall_changes,
+            firewall::find_port_usage,
             firewall_logs::get_log_filters,
             firewall_logs::get_interface_names,
             firewall_logs::get_firewall_logs,
@@ -93,6 +173,10 @@ pub fn run() {
             firewall_logs::start_log_polling,
             firewall_logs::stop_log_polling,
             firewall_logs::clear_log_cache,
+            firewall_logs::export_firewall_logs,
+            firewall_logs::start_firewall_log_stream,
+            firewall_logs::stop_firewall_log_stream,
+            firewall_logs::resolve_log_geoip,
             routes::get_routes,
             routes::get_route_info,
             routes::add_route,
@@ -101,6 +185,10 @@ pub fn run() {
             routes::apply_changes,
             routes::get_route_table,
             power::reboot_firewall,
+            power::schedule_reboot,
+            power::get_scheduled_reboot,
+            power::cancel_scheduled_reboot,
+            power::halt_firewall,
             snapshots::is_snapshots_supported,
             snapshots::get_snapshots,
             snapshots::get_new_snapshot,
@@ -113,28 +201,43 @@ pub fn run() {
             traffic::get_traffic_graph_data,
             traffic::update_traffic_data,
             traffic::clear_traffic_cache,
+            traffic::get_interface_traffic_history,
+            traffic::get_interface_stats,
+            traffic::get_top_talkers,
             update_checker::get_current_firmware_status,
             update_checker::check_for_updates,
             update_checker::get_changelog,
             update_checker::start_update,
+            update_checker::get_update_status,
+            update_checker::start_update_with_snapshot,
+            update_checker::get_changelog_diff,
+            update_checker::list_plugins,
+            update_checker::get_plugin_updates,
+            update_checker::install_plugin,
+            update_checker::reinstall_plugin,
             system_resources::get_system_resources,
             system_resources::get_system_disk,
             system_resources::get_system_temperature,
+            system_resources::get_time_status,
+            system_resources::get_system_activity,
             unbound::get_unbound_settings,
             unbound::set_dnsbl_settings,
             unbound::apply_dnsbl_settings,
             unbound::get_dnsbl_cron_job,
             unbound::add_dnsbl_cron_job,
             unbound::delete_dnsbl_cron_job,
-            unbound::apply_cron_changes,
             interfaces::get_interfaces,
             interfaces::get_interface_details,
+            interfaces::list_interfaces,
+            interfaces::get_network_topology,
             wol::check_wol_plugin_installed,
             wol::get_wol_interfaces,
             wol::search_wol_hosts,
             wol::get_arp_devices,
             wol::wake_device,
             wol::wake_mac_address,
+            wol::wake_on_lan,
+            wol::list_wol_hosts,
             wol::add_wol_host,
             wol::delete_wol_host,
             wol::install_wol_plugin,
@@ -146,6 +249,72 @@ pub fn run() {
             tunables::save_and_apply_tunable,
             tunables::add_tunable,
             tunables::delete_tunable,
+            wireguard::get_wireguard_status,
+            openvpn::get_openvpn_sessions,
+            openvpn::kill_openvpn_session,
+            dhcp::get_dhcpv4_leases,
+            dhcp::get_dhcpv6_leases,
+            dhcp::add_static_mapping,
+            dhcp::create_dhcp_reservation,
+            dns::list_host_overrides,
+            dns::add_host_override,
+            dns::delete_host_override,
+            dns::apply_unbound_changes,
+            dns::get_dns_blocklist_stats,
+            dns::get_dns_query_stats,
+            dns::list_unbound_acls,
+            dns::add_unbound_acl,
+            dns::delete_unbound_acl,
+            ha::get_carp_status,
+            ha::carp_maintenance_mode,
+            ipsec::get_ipsec_status,
+            ipsec::ipsec_connect,
+            ipsec::ipsec_disconnect,
+            backup::download_config_backup,
+            backup::list_backups,
+            backup::restore_config_backup,
+            shaper::list_shaper_pipes,
+            shaper::list_shaper_queues,
+            shaper::list_shaper_rules,
+            shaper::toggle_shaper_rule,
+            shaper::apply_shaper_changes,
+            ids::get_ids_alerts,
+            ids::get_ids_status,
+            certificates::list_certificates,
+            users::list_users,
+            users::list_api_keys_for_user,
+            audit::get_audit_log,
+            response_cache::set_response_cache_ttl,
+            response_cache::clear_response_cache,
+            resolve::resolve_ptr,
+            diagnostics::ping_host,
+            diagnostics::traceroute_host,
+            diagnostics::dns_lookup,
+            system_logs::list_log_modules,
+            system_logs::get_system_logs,
+            privileges::check_feature_access,
+            timeouts::get_timeout_settings,
+            timeouts::save_timeout_settings,
+            connection_monitor::get_connection_state,
+            alerts::scan_for_alerts,
+            alerts::get_alert_monitor_settings,
+            alerts::save_alert_monitor_settings,
+            app_logs::get_log_level,
+            app_logs::set_log_level,
+            app_logs::get_app_logs,
+            states::get_firewall_states,
+            states::kill_states_for_ip,
+            reporting::get_traffic_report,
+            reporting::get_top_protocols,
+            reporting::get_top_destinations,
+            pending::get_pending_subsystems,
+            pending::apply_all_pending_changes,
+            captiveportal::list_portal_zones,
+            captiveportal::list_portal_sessions,
+            captiveportal::disconnect_portal_session,
+            cron::list_cron_jobs,
+            cron::toggle_cron_job,
+            cron::apply_cron_changes,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
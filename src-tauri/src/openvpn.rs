@@ -0,0 +1,163 @@
+use crate::db::Database;
+use crate::error::ApiError;
+use crate::http_client::make_http_request;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{json, Value};
+use tauri::State;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OpenVpnSession {
+    #[serde(default, deserialize_with = "string_or_number")]
+    id: String,
+    #[serde(rename = "type", default)]
+    session_type: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    common_name: String,
+    #[serde(default)]
+    real_address: String,
+    #[serde(default)]
+    virtual_address: String,
+    #[serde(default, deserialize_with = "string_or_number")]
+    bytes_received: String,
+    #[serde(default, deserialize_with = "string_or_number")]
+    bytes_sent: String,
+    #[serde(default)]
+    connected_since: String,
+    #[serde(default)]
+    status: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OpenVpnSessionsResponse {
+    total: u32,
+    #[serde(rename = "rowCount")]
+    row_count: u32,
+    current: u32,
+    rows: Vec<OpenVpnSession>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KillSessionResponse {
+    status: String,
+    message: String,
+}
+
+fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
+    format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
+}
+
+fn string_or_number<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(s) => s,
+        Value::Number(n) => n.to_string(),
+        _ => String::new(),
+    })
+}
+
+#[tauri::command]
+pub async fn get_openvpn_sessions(
+    database: State<'_, Database>,
+) -> Result<OpenVpnSessionsResponse, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, "/api/openvpn/service/searchSessions/");
+
+    let payload = json!({
+        "current": 1,
+        "rowCount": -1,
+        "sort": {},
+        "searchPhrase": ""
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    response
+        .json::<OpenVpnSessionsResponse>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse OpenVPN sessions: {}", e)))
+}
+
+#[tauri::command]
+pub async fn kill_openvpn_session(
+    database: State<'_, Database>,
+    server_id: String,
+    session_id: String,
+) -> Result<KillSessionResponse, ApiError> {
+    let target = format!("{}/{}", server_id, session_id);
+    crate::audit::audited(
+        &database,
+        "kill_openvpn_session",
+        &target,
+        kill_openvpn_session_request(database.clone(), server_id, session_id),
+    )
+    .await
+}
+
+async fn kill_openvpn_session_request(
+    database: State<'_, Database>,
+    server_id: String,
+    session_id: String,
+) -> Result<KillSessionResponse, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, "/api/openvpn/service/killSession/");
+
+    let payload = json!({
+        "server_id": server_id,
+        "session_id": session_id
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let response_text = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read response body: {}", e))?;
+
+    // An empty or non-JSON body means there was nothing left to kill
+    let result = serde_json::from_str::<Value>(&response_text).unwrap_or(Value::Null);
+    let status = result["status"]
+        .as_str()
+        .or_else(|| result["result"].as_str())
+        .unwrap_or("not_found")
+        .to_string();
+
+    let message = match status.as_str() {
+        "killed" | "ok" => format!("Session {} disconnected", session_id),
+        "not_found" => format!("Session {} is no longer connected", session_id),
+        other => format!("Failed to disconnect session {}: {}", session_id, other),
+    };
+
+    Ok(KillSessionResponse { status, message })
+}
@@ -0,0 +1,80 @@
+// Fallback vendor lookup for devices the firewall's OUI database doesn't
+// recognize. Generated by build.rs from the IEEE registry in data/oui.csv,
+// sorted by prefix for the binary search in `lookup`.
+const OUI_VENDORS: &[(&str, &str)] = include!(concat!(env!("OUT_DIR"), "/oui_table.rs"));
+
+// Locally administered prefixes that still identify a hypervisor or runtime
+const LOCAL_VENDORS: &[(&str, &str)] = &[("525400", "QEMU/KVM"), ("0242", "Docker")];
+
+const RANDOMIZED_MAC: &str = "Randomized MAC";
+
+// "aa:bb:cc:dd:ee:ff", "aa-bb-cc-dd-ee-ff" and "aabb.ccdd.eeff" all become
+// "AABBCCDDEEFF"
+fn normalize(mac: &str) -> Option<String> {
+    let hex: String = mac
+        .chars()
+        .filter(|c| c.is_ascii_hexdigit())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    (hex.len() == 12).then_some(hex)
+}
+
+/// Vendor for a MAC address, or `RANDOMIZED_MAC` for locally administered
+/// addresses such as the private addresses phones use per network
+pub(crate) fn lookup(mac: &str) -> Option<&'static str> {
+    let hex = normalize(mac)?;
+
+    if let Some((_, vendor)) = LOCAL_VENDORS
+        .iter()
+        .find(|(prefix, _)| hex.starts_with(prefix))
+    {
+        return Some(vendor);
+    }
+
+    let first_octet = u8::from_str_radix(&hex[..2], 16).ok()?;
+    if first_octet & 0x02 != 0 {
+        return Some(RANDOMIZED_MAC);
+    }
+
+    OUI_VENDORS
+        .binary_search_by(|(prefix, _)| (*prefix).cmp(&hex[..6]))
+        .ok()
+        .map(|index| OUI_VENDORS[index].1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_is_sorted_for_binary_search() {
+        assert!(!OUI_VENDORS.is_empty());
+        assert!(OUI_VENDORS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    // IEEE names carry suffixes such as ", Inc." that change between exports
+    fn vendor_starts_with(mac: &str, name: &str) -> bool {
+        lookup(mac).is_some_and(|vendor| vendor.starts_with(name))
+    }
+
+    #[test]
+    fn looks_up_vendor_by_prefix() {
+        assert!(vendor_starts_with("00:0c:29:12:34:56", "VMware"));
+        assert!(vendor_starts_with("B8-27-EB-00-00-01", "Raspberry Pi"));
+        assert!(vendor_starts_with("0011.32aa.bbcc", "Synology"));
+    }
+
+    #[test]
+    fn labels_locally_administered_addresses() {
+        assert_eq!(lookup("52:54:00:12:34:56"), Some("QEMU/KVM"));
+        assert_eq!(lookup("02:42:ac:11:00:02"), Some("Docker"));
+        assert_eq!(lookup("da:a1:19:00:00:01"), Some(RANDOMIZED_MAC));
+    }
+
+    #[test]
+    fn malformed_addresses_have_no_vendor() {
+        assert_eq!(lookup("00:0c:29"), None);
+        assert_eq!(lookup("00:0c:29:12:34:56:78"), None);
+        assert_eq!(lookup(""), None);
+    }
+}
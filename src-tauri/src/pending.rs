@@ -0,0 +1,252 @@
+use crate::db::Database;
+use crate::error::ApiError;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Mutex;
+use tauri::{Manager, State};
+
+/// Areas with their own apply step. Declared in the order they are applied
+/// so aliases are live before the routes and rules that reference them.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    Aliases,
+    Routes,
+    Firewall,
+    Shaper,
+    Unbound,
+    Cron,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApplyStatus {
+    Applied,
+    Failed,
+    /// Not attempted because an earlier subsystem failed
+    Skipped,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SubsystemApplyResult {
+    subsystem: Subsystem,
+    status: ApplyStatus,
+    error: Option<String>,
+}
+
+/// A firewall rule toggled since the last apply. The API has no dirty state
+/// per rule, so toggles are tracked here until apply_firewall_changes
+/// commits them.
+#[derive(Serialize, Clone, Debug)]
+pub struct PendingRuleChange {
+    pub(crate) uuid: String,
+    /// "filter" or "nat"
+    pub(crate) kind: String,
+    pub(crate) description: String,
+    /// State the rule will have once applied
+    enabled: bool,
+    #[serde(skip)]
+    toggles: u32,
+}
+
+/// Changes staged from this app that are not yet applied, keyed by profile
+/// id. Staging a change marks its subsystem and a successful apply clears
+/// it; an apply marks first so a failed one leaves the subsystem pending.
+pub struct PendingChanges {
+    subsystems: Mutex<HashMap<i64, BTreeSet<Subsystem>>>,
+    // Keyed by profile id, then rule uuid
+    rules: Mutex<HashMap<i64, HashMap<String, PendingRuleChange>>>,
+}
+
+impl PendingChanges {
+    pub fn new() -> Self {
+        Self {
+            subsystems: Mutex::new(HashMap::new()),
+            rules: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn mark(&self, profile_id: i64, subsystem: Subsystem) {
+        self.subsystems
+            .lock()
+            .unwrap()
+            .entry(profile_id)
+            .or_default()
+            .insert(subsystem);
+    }
+
+    /// Clearing the firewall also drops its tracked rule toggles, since one
+    /// apply commits all of them
+    pub(crate) fn clear(&self, profile_id: i64, subsystem: Subsystem) {
+        if let Some(subsystems) = self.subsystems.lock().unwrap().get_mut(&profile_id) {
+            subsystems.remove(&subsystem);
+        }
+        if subsystem == Subsystem::Firewall {
+            self.rules.lock().unwrap().remove(&profile_id);
+        }
+    }
+
+    pub(crate) fn record_rule_toggle(
+        &self,
+        profile_id: i64,
+        uuid: &str,
+        kind: &str,
+        enabled: bool,
+    ) {
+        let mut rules = self.rules.lock().unwrap();
+        let changes = rules.entry(profile_id).or_default();
+
+        let change = changes
+            .entry(uuid.to_string())
+            .or_insert_with(|| PendingRuleChange {
+                uuid: uuid.to_string(),
+                kind: kind.to_string(),
+                description: String::new(),
+                enabled: false,
+                toggles: 0,
+            });
+        change.toggles += 1;
+        change.enabled = enabled;
+
+        // Toggled back to where it started, nothing left to apply
+        if change.toggles.is_multiple_of(2) {
+            changes.remove(uuid);
+        }
+    }
+
+    pub(crate) fn rule_changes(&self, profile_id: i64) -> Vec<PendingRuleChange> {
+        self.rules
+            .lock()
+            .unwrap()
+            .get(&profile_id)
+            .map(|changes| changes.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn subsystems(&self, profile_id: i64) -> BTreeSet<Subsystem> {
+        let mut subsystems = self
+            .subsystems
+            .lock()
+            .unwrap()
+            .get(&profile_id)
+            .cloned()
+            .unwrap_or_default();
+
+        // Rule toggles are pending even if nothing marked the firewall
+        if self
+            .rules
+            .lock()
+            .unwrap()
+            .get(&profile_id)
+            .is_some_and(|changes| !changes.is_empty())
+        {
+            subsystems.insert(Subsystem::Firewall);
+        }
+
+        subsystems
+    }
+}
+
+pub fn register_pending_changes(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(PendingChanges::new());
+    Ok(())
+}
+
+async fn apply(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    subsystem: Subsystem,
+) -> Result<(), ApiError> {
+    match subsystem {
+        Subsystem::Aliases => crate::alias::apply_alias_changes(database, pending)
+            .await
+            .map(|_| ()),
+        Subsystem::Routes => crate::routes::apply_changes(database, pending)
+            .await
+            .map(|_| ()),
+        Subsystem::Firewall => crate::firewall::apply_firewall_changes(database, pending)
+            .await
+            .map(|_| ()),
+        Subsystem::Shaper => crate::shaper::apply_shaper_changes(database, pending)
+            .await
+            .map(|_| ()),
+        Subsystem::Unbound => crate::dns::apply_unbound_changes(database, pending)
+            .await
+            .map(|_| ()),
+        Subsystem::Cron => crate::cron::apply_cron_changes(database, pending)
+            .await
+            .map(|_| ()),
+    }
+}
+
+/// Subsystems with changes staged from this app that are not yet applied
+#[tauri::command]
+pub fn get_pending_subsystems(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+) -> Result<Vec<Subsystem>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    Ok(pending.subsystems(api_info.id).into_iter().collect())
+}
+
+/// Applies every subsystem with pending changes in dependency order. After
+/// a failure the remaining subsystems are skipped, since they may depend on
+/// what failed to apply.
+#[tauri::command]
+pub async fn apply_all_pending_changes(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+) -> Result<Vec<SubsystemApplyResult>, ApiError> {
+    crate::audit::audited(
+        &database,
+        "apply_all_pending_changes",
+        "",
+        apply_all_pending_changes_request(database.clone(), pending),
+    )
+    .await
+}
+
+async fn apply_all_pending_changes_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+) -> Result<Vec<SubsystemApplyResult>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let mut results = Vec::new();
+    let mut failed: Option<Subsystem> = None;
+
+    for subsystem in pending.subsystems(api_info.id) {
+        if let Some(failed) = failed {
+            results.push(SubsystemApplyResult {
+                subsystem,
+                status: ApplyStatus::Skipped,
+                error: Some(format!("Skipped because {:?} failed to apply", failed)),
+            });
+            continue;
+        }
+
+        let result = apply(database.clone(), pending.clone(), subsystem).await;
+        let (status, error) = match result {
+            Ok(()) => (ApplyStatus::Applied, None),
+            Err(e) => {
+                failed = Some(subsystem);
+                (ApplyStatus::Failed, Some(e.to_string()))
+            }
+        };
+
+        results.push(SubsystemApplyResult {
+            subsystem,
+            status,
+            error,
+        });
+    }
+
+    Ok(results)
+}
@@ -1,8 +1,26 @@
-use crate::db::Database;
+use crate::db::{ApiInfo, Database, ScheduledReboot};
+use crate::error::ApiError;
 use crate::http_client::make_http_request;
+use log::{info, warn};
 use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::Notify;
+
+// A reboot the app was not running for is dropped once it is this late,
+// rather than rebooting at an unplanned time
+const MISSED_REBOOT_GRACE: i64 = 600;
+
+// How often a due reboot is retried while credentials are unavailable,
+// and the longest the scheduler sleeps between checks
+const SCHEDULER_POLL_SECONDS: i64 = 60;
+
+/// Wakes the reboot scheduler when a schedule is added or cancelled
+#[derive(Default)]
+pub struct RebootScheduler {
+    wake: Notify,
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct RebootResponse {
@@ -14,13 +32,82 @@ fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
 }
 
 #[tauri::command]
-pub async fn reboot_firewall(database: State<'_, Database>) -> Result<RebootResponse, String> {
+pub async fn reboot_firewall(database: State<'_, Database>) -> Result<RebootResponse, ApiError> {
+    crate::audit::audited(
+        &database,
+        "reboot_firewall",
+        "",
+        reboot_firewall_request(database.clone()),
+    )
+    .await
+}
+
+async fn reboot_firewall_request(
+    database: State<'_, Database>,
+) -> Result<RebootResponse, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    send_reboot(&api_info).await
+}
+
+/// Powers the firewall off. It stays off until someone powers it on at the
+/// appliance, so `confirm` must be true.
+#[tauri::command]
+pub async fn halt_firewall(
+    database: State<'_, Database>,
+    confirm: bool,
+) -> Result<RebootResponse, ApiError> {
+    if !confirm {
+        return Err(
+            "Halting requires confirmation: the firewall must be powered on again at the device"
+                .to_string()
+                .into(),
+        );
+    }
+
+    crate::audit::audited(
+        &database,
+        "halt_firewall",
+        "",
+        halt_firewall_request(database.clone()),
+    )
+    .await
+}
+
+async fn halt_firewall_request(database: State<'_, Database>) -> Result<RebootResponse, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
         .ok_or_else(|| "API info not found".to_string())?;
 
-    let url = build_api_url(&api_info, "/api/core/system/reboot");
+    let url = build_api_url(&api_info, "/api/core/system/halt");
+
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(serde_json::json!({})),
+        Some(headers),
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    response
+        .json::<RebootResponse>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
+}
+
+async fn send_reboot(api_info: &ApiInfo) -> Result<RebootResponse, ApiError> {
+    let url = build_api_url(api_info, "/api/core/system/reboot");
 
     let mut headers = HeaderMap::new();
     headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
@@ -30,14 +117,172 @@ pub async fn reboot_firewall(database: State<'_, Database>) -> Result<RebootResp
         &url,
         Some(serde_json::json!({})),
         Some(headers),
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<RebootResponse>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
+}
+
+/// Reboots the default profile's firewall at `at`, a unix timestamp in
+/// seconds. The schedule is kept by the app, so it only fires while the app
+/// is running and unlocked; a reboot missed by more than ten minutes is
+/// dropped.
+#[tauri::command]
+pub fn schedule_reboot(
+    database: State<'_, Database>,
+    scheduler: State<'_, RebootScheduler>,
+    at: i64,
+) -> Result<ScheduledReboot, ApiError> {
+    if at <= chrono::Utc::now().timestamp() {
+        return Err("The reboot must be scheduled in the future"
+            .to_string()
+            .into());
+    }
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let reboot = ScheduledReboot {
+        profile_name: api_info.profile_name,
+        reboot_at: at,
+    };
+
+    let result = database
+        .save_scheduled_reboot(&reboot)
+        .map_err(|e| format!("Failed to save scheduled reboot: {}", e));
+    crate::audit::record(&database, "schedule_reboot", &at.to_string(), &result);
+    result?;
+
+    scheduler.wake.notify_one();
+    Ok(reboot)
+}
+
+#[tauri::command]
+pub fn get_scheduled_reboot(
+    database: State<'_, Database>,
+) -> Result<Option<ScheduledReboot>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let reboots = database
+        .get_scheduled_reboots()
+        .map_err(|e| format!("Failed to read scheduled reboots: {}", e))?;
+
+    Ok(reboots
+        .into_iter()
+        .find(|reboot| reboot.profile_name == api_info.profile_name))
+}
+
+/// Returns whether a scheduled reboot was cancelled
+#[tauri::command]
+pub fn cancel_scheduled_reboot(
+    database: State<'_, Database>,
+    scheduler: State<'_, RebootScheduler>,
+) -> Result<bool, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let result = database
+        .delete_scheduled_reboot(&api_info.profile_name)
+        .map_err(|e| ApiError::Other(format!("Failed to cancel scheduled reboot: {}", e)));
+    crate::audit::record(&database, "cancel_scheduled_reboot", "", &result);
+
+    scheduler.wake.notify_one();
+    result
+}
+
+async fn run_due_reboot(database: &Database, reboot: &ScheduledReboot) {
+    let now = chrono::Utc::now().timestamp();
+
+    if now - reboot.reboot_at > MISSED_REBOOT_GRACE {
+        warn!(
+            "Dropping reboot of '{}' scheduled for {}, it was missed",
+            reboot.profile_name, reboot.reboot_at
+        );
+        let _ = database.delete_scheduled_reboot(&reboot.profile_name);
+        return;
+    }
+
+    // Credentials are only readable while the PIN is cached, otherwise the
+    // reboot is retried on the next check
+    let api_info = match database.get_api_info(Some(&reboot.profile_name)) {
+        Ok(Some(api_info)) if !api_info.api_key.is_empty() => api_info,
+        Ok(Some(_)) => {
+            warn!(
+                "Scheduled reboot of '{}' is waiting for the app to be unlocked",
+                reboot.profile_name
+            );
+            return;
+        }
+        // The profile was deleted after the reboot was scheduled
+        Ok(None) => {
+            let _ = database.delete_scheduled_reboot(&reboot.profile_name);
+            return;
+        }
+        Err(e) => {
+            warn!("Failed to load profile '{}': {}", reboot.profile_name, e);
+            return;
+        }
+    };
+
+    info!("Running scheduled reboot of '{}'", reboot.profile_name);
+    let result = send_reboot(&api_info).await;
+    crate::audit::record(database, "scheduled_reboot", &reboot.profile_name, &result);
+
+    // Not retried on failure, a late reboot is worse than a missed one
+    if let Err(e) = result {
+        warn!(
+            "Scheduled reboot of '{}' failed: {}",
+            reboot.profile_name, e
+        );
+    }
+    let _ = database.delete_scheduled_reboot(&reboot.profile_name);
+}
+
+async fn run_reboot_scheduler(app: AppHandle) {
+    loop {
+        let database = app.state::<Database>();
+        let reboots = database.get_scheduled_reboots().unwrap_or_else(|e| {
+            warn!("Failed to read scheduled reboots: {}", e);
+            Vec::new()
+        });
+
+        let now = chrono::Utc::now().timestamp();
+        for reboot in reboots.iter().filter(|reboot| reboot.reboot_at <= now) {
+            run_due_reboot(&database, reboot).await;
+        }
+
+        let next = reboots
+            .iter()
+            .map(|reboot| reboot.reboot_at - now)
+            .filter(|seconds| *seconds > 0)
+            .min()
+            .unwrap_or(SCHEDULER_POLL_SECONDS)
+            .min(SCHEDULER_POLL_SECONDS);
+
+        let scheduler = app.state::<RebootScheduler>();
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(next as u64)) => {}
+            _ = scheduler.wake.notified() => {}
+        }
+    }
+}
+
+pub fn register_reboot_scheduler(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(RebootScheduler::default());
+    tauri::async_runtime::spawn(run_reboot_scheduler(app.handle().clone()));
+    Ok(())
 }
@@ -0,0 +1,71 @@
+use crate::commands::current_privileges;
+use crate::db::Database;
+use crate::error::ApiError;
+use serde::Serialize;
+use tauri::State;
+
+/// OPNsense privileges each app feature needs, by feature id. Names match
+/// the entries under System > Access > Groups/Users.
+const FEATURE_PRIVILEGES: &[(&str, &[&str])] = &[
+    ("dashboard", &["System: Status"]),
+    ("gateways", &["System: Gateways"]),
+    ("services", &["Status: Services"]),
+    ("firmware", &["System: Firmware"]),
+    ("routes", &["System: Routes"]),
+    ("backups", &["System: Configuration: Backups"]),
+    ("snapshots", &["System: Configuration: Snapshots"]),
+    ("users", &["System: User Manager"]),
+    ("aliases", &["Firewall: Alias: Edit"]),
+    ("firewall_rules", &["Firewall: Automation: Filter"]),
+    ("port_forwards", &["Firewall: NAT: Port Forward"]),
+    ("firewall_logs", &["Diagnostics: Logs: Firewall: Live View"]),
+    (
+        "devices",
+        &["Diagnostics: ARP Table", "Status: DHCP leases"],
+    ),
+    ("system_activity", &["Diagnostics: System Activity"]),
+];
+
+#[derive(Serialize, Debug)]
+pub struct FeatureAccess {
+    feature: String,
+    allowed: bool,
+    required: Vec<String>,
+    missing: Vec<String>,
+}
+
+pub(crate) fn required_privileges(feature: &str) -> Option<&'static [&'static str]> {
+    FEATURE_PRIVILEGES
+        .iter()
+        .find(|(name, _)| *name == feature)
+        .map(|(_, privileges)| *privileges)
+}
+
+#[tauri::command]
+pub async fn check_feature_access(
+    database: State<'_, Database>,
+    feature: String,
+) -> Result<FeatureAccess, ApiError> {
+    let required =
+        required_privileges(&feature).ok_or_else(|| format!("Unknown feature: {}", feature))?;
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let granted = current_privileges(&api_info).await?;
+
+    let missing = required
+        .iter()
+        .filter(|privilege| !granted.iter().any(|g| g == *privilege))
+        .map(|privilege| privilege.to_string())
+        .collect::<Vec<_>>();
+
+    Ok(FeatureAccess {
+        feature,
+        allowed: missing.is_empty(),
+        required: required.iter().map(|p| p.to_string()).collect(),
+        missing,
+    })
+}
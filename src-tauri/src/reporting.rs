@@ -0,0 +1,290 @@
+use crate::db::{ApiInfo, Database};
+use crate::error::ApiError;
+use crate::http_client::make_http_request;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use tauri::State;
+
+// Bucket sizes Insight aggregates into, in seconds
+const RESOLUTIONS: [u64; 4] = [30, 300, 3600, 86400];
+const DEFAULT_TOP_HITS: u32 = 25;
+const MAX_TOP_HITS: u32 = 500;
+
+#[derive(Serialize, Debug)]
+pub struct TrafficBucket {
+    /// Start of the bucket as a unix timestamp in seconds
+    timestamp: i64,
+    octets: u64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TrafficSeries {
+    interface: String,
+    direction: String,
+    buckets: Vec<TrafficBucket>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TrafficReport {
+    from: i64,
+    to: i64,
+    resolution: u64,
+    series: Vec<TrafficSeries>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ProtocolUsage {
+    protocol: String,
+    octets: u64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DestinationUsage {
+    port: u16,
+    protocol: String,
+    octets: u64,
+}
+
+fn build_api_url(api_info: &ApiInfo, endpoint: &str) -> String {
+    format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
+}
+
+fn value_as_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64().or_else(|| n.as_f64().map(|f| f as u64)),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+// Insight stores IANA protocol numbers
+fn protocol_name(value: &Value) -> String {
+    let name = match value_as_u64(value) {
+        Some(1) => "icmp",
+        Some(6) => "tcp",
+        Some(17) => "udp",
+        Some(47) => "gre",
+        Some(50) => "esp",
+        Some(58) => "ipv6-icmp",
+        Some(112) => "carp",
+        Some(number) => return number.to_string(),
+        None => return value.as_str().unwrap_or_default().to_string(),
+    };
+    name.to_string()
+}
+
+fn validate_range(from: i64, to: i64) -> Result<(), ApiError> {
+    if from < 0 || to <= from {
+        return Err("The start of the report must be before its end"
+            .to_string()
+            .into());
+    }
+    Ok(())
+}
+
+async fn get_json(api_info: &ApiInfo, endpoint: &str) -> Result<Value, ApiError> {
+    let response = make_http_request(
+        "GET",
+        &build_api_url(api_info, endpoint),
+        None,
+        None,
+        api_info.long_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    response
+        .json::<Value>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse Insight response: {}", e)))
+}
+
+/// Insight only has data when NetFlow is capturing locally, otherwise its
+/// endpoints answer with empty results that look like an idle network
+async fn ensure_insight_enabled(api_info: &ApiInfo) -> Result<(), ApiError> {
+    let status = get_json(api_info, "/api/diagnostics/netflow/isEnabled").await?;
+
+    if value_as_u64(&status["netflow"]) != Some(1) {
+        return Err(
+            "NetFlow is not enabled. Enable it under Reporting > NetFlow to collect traffic reports"
+                .to_string().into(),
+        );
+    }
+    if value_as_u64(&status["local"]) != Some(1) {
+        return Err(
+            "NetFlow is not capturing locally. Enable \"Capture local\" under Reporting > NetFlow for Insight reports"
+                .to_string().into(),
+        );
+    }
+
+    Ok(())
+}
+
+async fn fetch_top(
+    api_info: &ApiInfo,
+    fields: &str,
+    from: i64,
+    to: i64,
+    max_hits: u32,
+) -> Result<Vec<Value>, ApiError> {
+    let body = get_json(
+        api_info,
+        &format!(
+            "/api/diagnostics/networkinsight/top/FlowDstPortTotals/{}/{}/{}/octets/{}",
+            from, to, fields, max_hits
+        ),
+    )
+    .await?;
+
+    Ok(body.as_array().cloned().unwrap_or_default())
+}
+
+/// Historical traffic per interface and direction from Insight, bucketed
+/// by `resolution` seconds (30, 300, 3600 or 86400) between the unix
+/// timestamps `from` and `to`
+#[tauri::command]
+pub async fn get_traffic_report(
+    database: State<'_, Database>,
+    from: i64,
+    to: i64,
+    resolution: u64,
+) -> Result<TrafficReport, ApiError> {
+    validate_range(from, to)?;
+    if !RESOLUTIONS.contains(&resolution) {
+        return Err(format!(
+            "Invalid resolution {}. Expected one of: {:?}",
+            resolution, RESOLUTIONS
+        )
+        .into());
+    }
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    ensure_insight_enabled(&api_info).await?;
+
+    let body = get_json(
+        &api_info,
+        &format!(
+            "/api/diagnostics/networkinsight/timeserie/FlowInterfaceTotals/octets/{}/{}/{}/if,direction",
+            from, to, resolution
+        ),
+    )
+    .await?;
+
+    // Each series is keyed "<interface>,<direction>" with [timestamp_ms, value] pairs
+    let series = body
+        .as_array()
+        .map(|series| {
+            series
+                .iter()
+                .map(|entry| {
+                    let key = entry["key"].as_str().unwrap_or_default();
+                    let (interface, direction) = key.split_once(',').unwrap_or((key, ""));
+                    let buckets = entry["values"]
+                        .as_array()
+                        .map(|values| {
+                            values
+                                .iter()
+                                .filter_map(|point| {
+                                    Some(TrafficBucket {
+                                        timestamp: point[0].as_i64()? / 1000,
+                                        octets: value_as_u64(&point[1]).unwrap_or(0),
+                                    })
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    TrafficSeries {
+                        interface: interface.to_string(),
+                        direction: direction.to_string(),
+                        buckets,
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(TrafficReport {
+        from,
+        to,
+        resolution,
+        series,
+    })
+}
+
+/// Traffic per IP protocol between `from` and `to`, largest first
+#[tauri::command]
+pub async fn get_top_protocols(
+    database: State<'_, Database>,
+    from: i64,
+    to: i64,
+) -> Result<Vec<ProtocolUsage>, ApiError> {
+    validate_range(from, to)?;
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    ensure_insight_enabled(&api_info).await?;
+
+    let rows = fetch_top(&api_info, "protocol", from, to, MAX_TOP_HITS).await?;
+
+    // Rows may repeat a protocol, so sum them up
+    let mut totals: HashMap<String, u64> = HashMap::new();
+    for row in &rows {
+        *totals.entry(protocol_name(&row["protocol"])).or_default() +=
+            value_as_u64(&row["total"]).unwrap_or(0);
+    }
+
+    let mut protocols: Vec<ProtocolUsage> = totals
+        .into_iter()
+        .map(|(protocol, octets)| ProtocolUsage { protocol, octets })
+        .collect();
+    protocols.sort_by_key(|usage| std::cmp::Reverse(usage.octets));
+
+    Ok(protocols)
+}
+
+/// Traffic per destination port and protocol between `from` and `to`,
+/// largest first. Insight aggregates destinations by service rather than
+/// by address.
+#[tauri::command]
+pub async fn get_top_destinations(
+    database: State<'_, Database>,
+    from: i64,
+    to: i64,
+    limit: Option<u32>,
+) -> Result<Vec<DestinationUsage>, ApiError> {
+    validate_range(from, to)?;
+    let limit = limit.unwrap_or(DEFAULT_TOP_HITS).clamp(1, MAX_TOP_HITS);
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    ensure_insight_enabled(&api_info).await?;
+
+    let rows = fetch_top(&api_info, "dst_port,protocol", from, to, limit).await?;
+
+    let mut destinations: Vec<DestinationUsage> = rows
+        .iter()
+        .filter_map(|row| {
+            Some(DestinationUsage {
+                port: value_as_u64(&row["dst_port"])?.try_into().ok()?,
+                protocol: protocol_name(&row["protocol"]),
+                octets: value_as_u64(&row["total"]).unwrap_or(0),
+            })
+        })
+        .collect();
+    destinations.sort_by_key(|usage| std::cmp::Reverse(usage.octets));
+
+    Ok(destinations)
+}
@@ -0,0 +1,120 @@
+use crate::db::{ApiInfo, Database};
+use crate::error::ApiError;
+use crate::http_client::make_http_request;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{Manager, State};
+
+const PTR_TTL: Duration = Duration::from_secs(600);
+
+/// Hostnames from reverse lookups, kept for `PTR_TTL` so scrolling through
+/// logs or devices does not repeat the same queries
+pub struct PtrCache {
+    entries: Mutex<HashMap<String, (Instant, String)>>,
+}
+
+impl PtrCache {
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+pub fn register_ptr_cache(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(PtrCache::new());
+    Ok(())
+}
+
+fn build_api_url(api_info: &ApiInfo, endpoint: &str) -> String {
+    format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
+}
+
+async fn reverse_lookup(api_info: &ApiInfo, ips: &[String]) -> Result<Value, ApiError> {
+    let query = ips
+        .iter()
+        .map(|ip| format!("address%5B%5D={}", ip))
+        .collect::<Vec<_>>()
+        .join("&");
+    let url = build_api_url(
+        api_info,
+        &format!("/api/diagnostics/dns/reverse_lookup?{}", query),
+    );
+
+    let response = make_http_request(
+        "GET",
+        &url,
+        None,
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    response
+        .json::<Value>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse reverse lookup response: {}", e)))
+}
+
+/// Resolves IPs to hostnames through the firewall's resolver. Addresses
+/// without a PTR record (or that are not IPs at all) map to themselves.
+#[tauri::command]
+pub async fn resolve_ptr(
+    database: State<'_, Database>,
+    cache: State<'_, PtrCache>,
+    ips: Vec<String>,
+) -> Result<HashMap<String, String>, ApiError> {
+    let mut resolved = HashMap::new();
+    let mut pending = Vec::new();
+
+    {
+        let entries = cache.entries.lock().unwrap();
+        for ip in ips {
+            if resolved.contains_key(&ip) || pending.contains(&ip) {
+                continue;
+            }
+            match entries.get(&ip) {
+                Some((stored_at, hostname)) if stored_at.elapsed() < PTR_TTL => {
+                    resolved.insert(ip, hostname.clone());
+                }
+                _ if ip.parse::<IpAddr>().is_ok() => pending.push(ip),
+                _ => {
+                    resolved.insert(ip.clone(), ip);
+                }
+            }
+        }
+    }
+
+    if pending.is_empty() {
+        return Ok(resolved);
+    }
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let lookup = reverse_lookup(&api_info, &pending).await?;
+
+    let mut entries = cache.entries.lock().unwrap();
+    for ip in pending {
+        // NXDOMAIN comes back as an empty or missing entry
+        let hostname = lookup
+            .get(&ip)
+            .and_then(|v| v.as_str())
+            .map(|h| h.trim_end_matches('.'))
+            .filter(|h| !h.is_empty())
+            .unwrap_or(&ip)
+            .to_string();
+        entries.insert(ip.clone(), (Instant::now(), hostname.clone()));
+        resolved.insert(ip, hostname);
+    }
+
+    Ok(resolved)
+}
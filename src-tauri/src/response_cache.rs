@@ -0,0 +1,175 @@
+use crate::error::ApiError;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{Manager, State};
+use tokio::sync::OnceCell;
+
+const DEFAULT_TTL: Duration = Duration::from_secs(5);
+
+// Serialized result of a request, set once by whichever caller runs it
+type InflightRequest = Arc<OnceCell<Result<Value, ApiError>>>;
+
+/// Short-lived cache for read commands that several dashboard widgets poll,
+/// so overlapping requests within the TTL are served without hitting the
+/// firewall again
+pub struct ResponseCache {
+    entries: Mutex<HashMap<String, (Instant, Value)>>,
+    ttl: Mutex<Duration>,
+    // Requests currently running, shared by every caller asking for the
+    // same key until the first one completes
+    inflight: Mutex<HashMap<String, InflightRequest>>,
+}
+
+/// Identifies a request by method, URL and payload for `single_flight`
+pub fn request_key(
+    api_info: &crate::db::ApiInfo,
+    method: &str,
+    endpoint: &str,
+    payload: Option<&Value>,
+) -> String {
+    format!(
+        "{} {}:{}{} {}",
+        method,
+        api_info.api_url,
+        api_info.port,
+        endpoint,
+        payload.map(Value::to_string).unwrap_or_default()
+    )
+}
+
+impl ResponseCache {
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl: Mutex::new(DEFAULT_TTL),
+            inflight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<Value> {
+        let ttl = *self.ttl.lock().unwrap();
+        let entries = self.entries.lock().unwrap();
+
+        entries
+            .get(key)
+            .filter(|(stored_at, _)| stored_at.elapsed() < ttl)
+            .map(|(_, value)| value.clone())
+    }
+
+    fn insert(&self, key: &str, value: Value) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(key.to_string(), (Instant::now(), value));
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Drops the cached value for `key` so the next read fetches it again
+    pub fn remove(&self, key: &str) {
+        self.entries.lock().unwrap().remove(key);
+    }
+
+    /// Returns the cached value for `key` while it is fresh, otherwise runs
+    /// `fetch` and caches its result. `force` always fetches.
+    pub async fn get_or_fetch<T, E, F>(
+        &self,
+        key: &str,
+        force: bool,
+        fetch: F,
+    ) -> Result<T, ApiError>
+    where
+        T: Serialize + DeserializeOwned,
+        E: Into<ApiError>,
+        F: Future<Output = Result<T, E>>,
+    {
+        if !force {
+            if let Some(cached) = self.get(key) {
+                if let Ok(value) = serde_json::from_value(cached) {
+                    return Ok(value);
+                }
+            }
+        }
+
+        let value = self.shared(key, fetch).await?;
+
+        // Errors are never cached, so a failed request is retried on the next call
+        self.insert(key, value.clone());
+
+        serde_json::from_value(value)
+            .map_err(|e| ApiError::Parse(format!("Failed to read response: {}", e)))
+    }
+
+    /// Runs `fetch` unless a request with the same key is already running,
+    /// in which case its result is awaited and shared instead. Nothing is
+    /// kept once the request completes.
+    pub async fn single_flight<T, E, F>(&self, key: &str, fetch: F) -> Result<T, ApiError>
+    where
+        T: Serialize + DeserializeOwned,
+        E: Into<ApiError>,
+        F: Future<Output = Result<T, E>>,
+    {
+        let value = self.shared(key, fetch).await?;
+        serde_json::from_value(value)
+            .map_err(|e| ApiError::Parse(format!("Failed to read response: {}", e)))
+    }
+
+    async fn shared<T, E, F>(&self, key: &str, fetch: F) -> Result<Value, ApiError>
+    where
+        T: Serialize,
+        E: Into<ApiError>,
+        F: Future<Output = Result<T, E>>,
+    {
+        let cell = self
+            .inflight
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_default()
+            .clone();
+
+        // If the caller running the fetch is cancelled, a waiting caller
+        // runs its own fetch instead
+        let result = cell
+            .get_or_init(|| async {
+                let value = fetch.await.map_err(Into::into)?;
+                serde_json::to_value(&value)
+                    .map_err(|e| ApiError::Parse(format!("Failed to read response: {}", e)))
+            })
+            .await
+            .clone();
+
+        // Callers arriving from now on start a fresh request
+        let mut inflight = self.inflight.lock().unwrap();
+        if inflight
+            .get(key)
+            .is_some_and(|current| Arc::ptr_eq(current, &cell))
+        {
+            inflight.remove(key);
+        }
+
+        result
+    }
+}
+
+pub fn register_response_cache(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(ResponseCache::new());
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_response_cache_ttl(cache: State<'_, ResponseCache>, seconds: u64) -> Result<(), String> {
+    *cache.ttl.lock().unwrap() = Duration::from_secs(seconds);
+    cache.clear();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn clear_response_cache(cache: State<'_, ResponseCache>) -> Result<(), String> {
+    cache.clear();
+    Ok(())
+}
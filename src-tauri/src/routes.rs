@@ -1,7 +1,12 @@
-use crate::db::Database;
+use crate::db::{ApiInfo, Database};
+use crate::error::ApiError;
 use crate::http_client::make_http_request;
+use crate::pending::{PendingChanges, Subsystem};
+use log::warn;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::IpAddr;
 use tauri::State;
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -11,6 +16,16 @@ pub struct Route {
     network: String,
     gateway: String,
     descr: String,
+    // Filled in from the routing table and gateway settings, which the
+    // static route search does not return
+    #[serde(default)]
+    interface: Option<String>,
+    #[serde(default)]
+    next_hop: Option<String>,
+    #[serde(default)]
+    metric: Option<String>,
+    #[serde(default)]
+    ip_version: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -71,12 +86,122 @@ pub struct RouteTableEntry {
     intf_description: String,
 }
 
-fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
+fn build_api_url(api_info: &ApiInfo, endpoint: &str) -> String {
     format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
 }
 
+async fn fetch_route_table(api_info: &ApiInfo) -> Result<Vec<RouteTableEntry>, ApiError> {
+    let url = build_api_url(api_info, "/api/diagnostics/interface/getRoutes");
+
+    let response = make_http_request(
+        "GET",
+        &url,
+        None,
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    response
+        .json::<Vec<RouteTableEntry>>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
+}
+
+/// Gateway name to priority, which is what OPNsense uses as the route metric
+async fn fetch_gateway_priorities(api_info: &ApiInfo) -> Result<HashMap<String, String>, ApiError> {
+    let url = build_api_url(api_info, "/api/routing/settings/searchGateway");
+
+    let payload = json!({
+        "current": 1,
+        "rowCount": -1,
+        "sort": {},
+        "searchPhrase": ""
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let body = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse gateways: {}", e))?;
+
+    let field = |row: &Value, key: &str| match &row[key] {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    };
+
+    Ok(body["rows"]
+        .as_array()
+        .map(|rows| {
+            rows.iter()
+                .filter_map(|row| Some((field(row, "name")?, field(row, "priority")?)))
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+fn ip_version(network: &str) -> &'static str {
+    if network.contains(':') {
+        "ipv6"
+    } else {
+        "ipv4"
+    }
+}
+
+/// The route search shows gateways as "NAME - address", so match on the name
+fn gateway_matches(route_gateway: &str, name: &str) -> bool {
+    route_gateway == name || route_gateway.starts_with(&format!("{} ", name))
+}
+
+/// Merges interface, next hop and metric into the configured routes. Both
+/// sources are best effort so the route list still loads without them.
+async fn enrich_routes(api_info: &ApiInfo, routes: &mut [Route]) {
+    let table = fetch_route_table(api_info).await.unwrap_or_else(|e| {
+        warn!("Failed to fetch routing table: {}", e);
+        Vec::new()
+    });
+    let priorities = fetch_gateway_priorities(api_info)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Failed to fetch gateway priorities: {}", e);
+            HashMap::new()
+        });
+
+    for route in routes.iter_mut() {
+        route.ip_version = ip_version(&route.network).to_string();
+
+        if let Some(entry) = table
+            .iter()
+            .find(|entry| entry.destination == route.network)
+        {
+            route.interface = Some(entry.netif.clone());
+            route.next_hop = Some(entry.gateway.clone());
+        }
+
+        route.metric = priorities
+            .iter()
+            .find(|(name, _)| gateway_matches(&route.gateway, name))
+            .map(|(_, priority)| priority.clone());
+    }
+}
+
 #[tauri::command]
-pub async fn get_routes(database: State<'_, Database>) -> Result<RoutesResponse, String> {
+pub async fn get_routes(database: State<'_, Database>) -> Result<RoutesResponse, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -96,57 +221,173 @@ pub async fn get_routes(database: State<'_, Database>) -> Result<RoutesResponse,
         &url,
         Some(payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
-    response
+    let mut routes = response
         .json::<RoutesResponse>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    enrich_routes(&api_info, &mut routes.rows).await;
+
+    Ok(routes)
 }
 
 #[tauri::command]
-pub async fn get_route_info(database: State<'_, Database>) -> Result<RouteInfoResponse, String> {
+pub async fn get_route_info(database: State<'_, Database>) -> Result<RouteInfoResponse, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
         .ok_or_else(|| "API info not found".to_string())?;
 
-    let url = build_api_url(&api_info, "/api/routes/routes/getroute");
+    fetch_route_info(&api_info).await
+}
+
+async fn fetch_route_info(api_info: &ApiInfo) -> Result<RouteInfoResponse, ApiError> {
+    let url = build_api_url(api_info, "/api/routes/routes/getroute");
 
     let response = make_http_request(
         "GET",
         &url,
         None,
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<RouteInfoResponse>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
+}
+
+fn parse_network(network: &str) -> Result<IpAddr, ApiError> {
+    let (address, prefix) = network.trim().split_once('/').ok_or_else(|| {
+        format!(
+            "Network {} must be in CIDR notation, e.g. 10.0.0.0/24",
+            network
+        )
+    })?;
+
+    let address = address
+        .parse::<IpAddr>()
+        .map_err(|_| format!("Invalid network address: {}", address))?;
+    let max_prefix = if address.is_ipv4() { 32 } else { 128 };
+    match prefix.parse::<u8>() {
+        Ok(prefix) if prefix <= max_prefix => Ok(address),
+        _ => Err(format!(
+            "Invalid prefix length /{}: must be between 0 and {}",
+            prefix, max_prefix
+        )
+        .into()),
+    }
+}
+
+/// Checks the route before it is sent, so typos get a specific message
+/// instead of a generic validation error from the API. The gateway may be
+/// an IP or the name of a configured gateway.
+fn validate_route(
+    network: &str,
+    gateway: &str,
+    gateways: &HashMap<String, GatewayOption>,
+) -> Result<(), ApiError> {
+    let network_address = parse_network(network)?;
+
+    let gateway_address = match gateway.trim().parse::<IpAddr>() {
+        Ok(address) => Some(address),
+        Err(_) => {
+            let option = gateways.get(gateway.trim()).ok_or_else(|| {
+                let mut known: Vec<&str> = gateways
+                    .keys()
+                    .map(String::as_str)
+                    .filter(|name| !name.is_empty())
+                    .collect();
+                known.sort();
+                format!(
+                    "Unknown gateway {}, expected an IP address or one of: {}",
+                    gateway,
+                    known.join(", ")
+                )
+            })?;
+            // Options are labelled "NAME - address"
+            option
+                .value
+                .rsplit(" - ")
+                .next()
+                .and_then(|address| address.trim().parse::<IpAddr>().ok())
+        }
+    };
+
+    if let Some(address) = gateway_address {
+        if address.is_ipv4() != network_address.is_ipv4() {
+            return Err(format!(
+                "Gateway {} is {} but network {} is {}",
+                gateway,
+                if address.is_ipv4() { "IPv4" } else { "IPv6" },
+                network,
+                if network_address.is_ipv4() {
+                    "IPv4"
+                } else {
+                    "IPv6"
+                }
+            )
+            .into());
+        }
+    }
+
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn add_route(
     database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
     network: String,
     gateway: String,
     description: String,
     disabled: bool,
-) -> Result<AddRouteResponse, String> {
+) -> Result<AddRouteResponse, ApiError> {
+    let target = network.clone();
+    crate::audit::audited(
+        &database,
+        "add_route",
+        &target,
+        add_route_request(
+            database.clone(),
+            pending,
+            network,
+            gateway,
+            description,
+            disabled,
+        ),
+    )
+    .await
+}
+
+async fn add_route_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    network: String,
+    gateway: String,
+    description: String,
+    disabled: bool,
+) -> Result<AddRouteResponse, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
         .ok_or_else(|| "API info not found".to_string())?;
 
+    let route_info = fetch_route_info(&api_info).await?;
+    validate_route(&network, &gateway, &route_info.route.gateway)?;
+
     let url = build_api_url(&api_info, "/api/routes/routes/addroute");
 
     let payload = json!({
@@ -165,9 +406,10 @@ pub async fn add_route(
         &url,
         Some(payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -185,13 +427,32 @@ pub async fn add_route(
         )
     })?;
 
-    apply_changes(database).await?;
+    apply_changes(database, pending).await?;
 
     Ok(result)
 }
 
 #[tauri::command]
-pub async fn delete_route(database: State<'_, Database>, uuid: String) -> Result<(), String> {
+pub async fn delete_route(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+) -> Result<(), ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "delete_route",
+        &target,
+        delete_route_request(database.clone(), pending, uuid),
+    )
+    .await
+}
+
+async fn delete_route_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+) -> Result<(), ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -204,17 +465,18 @@ pub async fn delete_route(database: State<'_, Database>, uuid: String) -> Result
         &url,
         Some(json!({})),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     if !response.status().is_success() {
-        return Err(format!("Failed to delete route: {}", response.status()));
+        return Err(format!("Failed to delete route: {}", response.status()).into());
     }
 
-    apply_changes(database).await?;
+    apply_changes(database, pending).await?;
 
     Ok(())
 }
@@ -222,8 +484,24 @@ pub async fn delete_route(database: State<'_, Database>, uuid: String) -> Result
 #[tauri::command]
 pub async fn toggle_route(
     database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+) -> Result<ToggleResponse, ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "toggle_route",
+        &target,
+        toggle_route_request(database.clone(), pending, uuid),
+    )
+    .await
+}
+
+async fn toggle_route_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
     uuid: String,
-) -> Result<ToggleResponse, String> {
+) -> Result<ToggleResponse, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -239,9 +517,10 @@ pub async fn toggle_route(
         &url,
         Some(json!({})),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -250,18 +529,37 @@ pub async fn toggle_route(
         .await
         .map_err(|e| format!("Failed to parse response: {}", e))?;
 
-    apply_changes(database).await?;
+    apply_changes(database, pending).await?;
 
     Ok(result)
 }
 
 #[tauri::command]
-pub async fn apply_changes(database: State<'_, Database>) -> Result<ReconfigureResponse, String> {
+pub async fn apply_changes(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+) -> Result<ReconfigureResponse, ApiError> {
+    crate::audit::audited(
+        &database,
+        "apply_changes",
+        "",
+        apply_changes_request(database.clone(), pending),
+    )
+    .await
+}
+
+async fn apply_changes_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+) -> Result<ReconfigureResponse, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
         .ok_or_else(|| "API info not found".to_string())?;
 
+    // Stays pending unless the reconfigure succeeds
+    pending.mark(api_info.id, Subsystem::Routes);
+
     let url = build_api_url(&api_info, "/api/routes/routes/reconfigure");
 
     let response = make_http_request(
@@ -269,42 +567,30 @@ pub async fn apply_changes(database: State<'_, Database>) -> Result<ReconfigureR
         &url,
         Some(json!({})),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
-    response
+    let result = response
         .json::<ReconfigureResponse>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    pending.clear(api_info.id, Subsystem::Routes);
+    Ok(result)
 }
 
 #[tauri::command]
 pub async fn get_route_table(
     database: State<'_, Database>,
-) -> Result<Vec<RouteTableEntry>, String> {
+) -> Result<Vec<RouteTableEntry>, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
         .ok_or_else(|| "API info not found".to_string())?;
 
-    let url = build_api_url(&api_info, "/api/diagnostics/interface/getRoutes");
-
-    let response = make_http_request(
-        "GET",
-        &url,
-        None,
-        None,
-        Some(30),
-        Some(&api_info.api_key),
-        Some(&api_info.api_secret),
-    )
-    .await?;
-
-    response
-        .json::<Vec<RouteTableEntry>>()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+    fetch_route_table(&api_info).await
 }
@@ -0,0 +1,263 @@
+use crate::db::Database;
+use crate::error::ApiError;
+use crate::http_client::make_http_request;
+use crate::pending::{PendingChanges, Subsystem};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{json, Value};
+use tauri::State;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ShaperPipe {
+    #[serde(default)]
+    uuid: String,
+    #[serde(default, deserialize_with = "flag")]
+    enabled: bool,
+    #[serde(default)]
+    number: String,
+    #[serde(default)]
+    bandwidth: String,
+    #[serde(rename = "bandwidthMetric", default)]
+    bandwidth_metric: String,
+    #[serde(default)]
+    scheduler: String,
+    #[serde(default)]
+    mask: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ShaperQueue {
+    #[serde(default)]
+    uuid: String,
+    #[serde(default, deserialize_with = "flag")]
+    enabled: bool,
+    #[serde(default)]
+    number: String,
+    #[serde(default)]
+    pipe: String,
+    #[serde(default)]
+    weight: String,
+    #[serde(default)]
+    mask: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ShaperRule {
+    #[serde(default)]
+    uuid: String,
+    #[serde(default, deserialize_with = "flag")]
+    enabled: bool,
+    #[serde(default)]
+    sequence: String,
+    #[serde(default)]
+    interface: String,
+    #[serde(default)]
+    proto: String,
+    #[serde(default)]
+    source: String,
+    #[serde(default)]
+    destination: String,
+    #[serde(default)]
+    target: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ShaperSearchResponse<T> {
+    total: u32,
+    #[serde(rename = "rowCount")]
+    row_count: u32,
+    current: u32,
+    rows: Vec<T>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ToggleShaperRuleResponse {
+    result: String,
+    changed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ApplyShaperResponse {
+    status: String,
+}
+
+fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
+    format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
+}
+
+// The shaper model reports booleans as "1"/"0" strings
+fn flag<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Bool(b) => b,
+        Value::String(s) => s == "1",
+        Value::Number(n) => n.as_u64() == Some(1),
+        _ => false,
+    })
+}
+
+async fn search<T: DeserializeOwned>(
+    database: State<'_, Database>,
+    endpoint: &str,
+) -> Result<ShaperSearchResponse<T>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, endpoint);
+
+    let payload = json!({
+        "current": 1,
+        "rowCount": -1,
+        "sort": {},
+        "searchPhrase": ""
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    response
+        .json::<ShaperSearchResponse<T>>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse traffic shaper response: {}", e)))
+}
+
+#[tauri::command]
+pub async fn list_shaper_pipes(
+    database: State<'_, Database>,
+) -> Result<ShaperSearchResponse<ShaperPipe>, ApiError> {
+    search(database, "/api/trafficshaper/settings/searchPipes/").await
+}
+
+#[tauri::command]
+pub async fn list_shaper_queues(
+    database: State<'_, Database>,
+) -> Result<ShaperSearchResponse<ShaperQueue>, ApiError> {
+    search(database, "/api/trafficshaper/settings/searchQueues/").await
+}
+
+#[tauri::command]
+pub async fn list_shaper_rules(
+    database: State<'_, Database>,
+) -> Result<ShaperSearchResponse<ShaperRule>, ApiError> {
+    search(database, "/api/trafficshaper/settings/searchRules/").await
+}
+
+#[tauri::command]
+pub async fn toggle_shaper_rule(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+) -> Result<ToggleShaperRuleResponse, ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "toggle_shaper_rule",
+        &target,
+        toggle_shaper_rule_request(database.clone(), pending, uuid),
+    )
+    .await
+}
+
+async fn toggle_shaper_rule_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+) -> Result<ToggleShaperRuleResponse, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let toggle_url = build_api_url(
+        &api_info,
+        &format!("/api/trafficshaper/settings/toggleRule/{}", uuid),
+    );
+
+    let toggle_response = make_http_request(
+        "POST",
+        &toggle_url,
+        Some(json!({})),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let result = toggle_response
+        .json::<ToggleShaperRuleResponse>()
+        .await
+        .map_err(|e| format!("Failed to parse toggle response: {}", e))?;
+
+    pending.mark(api_info.id, Subsystem::Shaper);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn apply_shaper_changes(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+) -> Result<ApplyShaperResponse, ApiError> {
+    crate::audit::audited(
+        &database,
+        "apply_shaper_changes",
+        "",
+        apply_shaper_changes_request(database.clone(), pending),
+    )
+    .await
+}
+
+async fn apply_shaper_changes_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+) -> Result<ApplyShaperResponse, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    // Stays pending unless the reconfigure succeeds
+    pending.mark(api_info.id, Subsystem::Shaper);
+
+    let apply_url = build_api_url(&api_info, "/api/trafficshaper/service/reconfigure");
+
+    let apply_response = make_http_request(
+        "POST",
+        &apply_url,
+        Some(json!({})),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let result = apply_response
+        .json::<ApplyShaperResponse>()
+        .await
+        .map_err(|e| format!("Failed to parse apply response: {}", e))?;
+
+    pending.clear(api_info.id, Subsystem::Shaper);
+    Ok(result)
+}
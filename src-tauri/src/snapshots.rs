@@ -1,4 +1,5 @@
 use crate::db::Database;
+use crate::error::ApiError;
 use crate::http_client::make_http_request;
 use log::info;
 use serde::{Deserialize, Serialize};
@@ -35,7 +36,7 @@ fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
 }
 
 #[tauri::command]
-pub async fn is_snapshots_supported(database: State<'_, Database>) -> Result<bool, String> {
+pub async fn is_snapshots_supported(database: State<'_, Database>) -> Result<bool, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -48,9 +49,10 @@ pub async fn is_snapshots_supported(database: State<'_, Database>) -> Result<boo
         &url,
         None,
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -71,7 +73,7 @@ pub async fn get_snapshots(
     current_page: u32,
     rows_per_page: u32,
     database: State<'_, Database>,
-) -> Result<SnapshotSearchResponse, String> {
+) -> Result<SnapshotSearchResponse, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -91,22 +93,23 @@ pub async fn get_snapshots(
         &url,
         Some(payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<SnapshotSearchResponse>()
         .await
-        .map_err(|e| format!("Failed to parse snapshots: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse snapshots: {}", e)))
 }
 
 #[tauri::command]
 pub async fn get_new_snapshot(
     database: State<'_, Database>,
-) -> Result<NewSnapshotResponse, String> {
+) -> Result<NewSnapshotResponse, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -119,16 +122,17 @@ pub async fn get_new_snapshot(
         &url,
         None,
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<NewSnapshotResponse>()
         .await
-        .map_err(|e| format!("Failed to get new snapshot info: {}", e))
+        .map_err(|e| ApiError::Other(format!("Failed to get new snapshot info: {}", e)))
 }
 
 #[tauri::command]
@@ -136,7 +140,7 @@ pub async fn get_snapshot(
     uuid: String,
     fetch_mode: Option<String>,
     database: State<'_, Database>,
-) -> Result<Snapshot, String> {
+) -> Result<Snapshot, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -153,24 +157,79 @@ pub async fn get_snapshot(
         &url,
         None,
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Snapshot>()
         .await
-        .map_err(|e| format!("Failed to get snapshot: {}", e))
+        .map_err(|e| ApiError::Other(format!("Failed to get snapshot: {}", e)))
 }
 
+// Boot environment names only allow letters, digits, '-', '_' and '.'
+fn sanitize_snapshot_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join("_")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .collect()
+}
+
+/// Uses `name` when given, otherwise `OPNManager-YYYY-MM-DD-HHMMSS`, with the
+/// description appended so it shows up in the snapshot list
+pub(crate) fn snapshot_name(name: Option<&str>, description: Option<&str>) -> String {
+    let base = name
+        .map(sanitize_snapshot_name)
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| {
+            format!(
+                "OPNManager-{}",
+                chrono::Local::now().format("%Y-%m-%d-%H%M%S")
+            )
+        });
+
+    match description.map(sanitize_snapshot_name) {
+        Some(description) if !description.is_empty() => format!("{}-{}", base, description),
+        _ => base,
+    }
+}
+
+/// Creates a snapshot, or clones `uuid` when given. The response carries the
+/// final `name` so callers can show a generated one.
 #[tauri::command]
 pub async fn add_snapshot(
+    name: Option<String>,
+    uuid: Option<String>,
+    description: Option<String>,
+    database: State<'_, Database>,
+) -> Result<Value, ApiError> {
+    let name = snapshot_name(name.as_deref(), description.as_deref());
+    let target = name.clone();
+    let mut result = crate::audit::audited(
+        &database,
+        "add_snapshot",
+        &target,
+        add_snapshot_request(name.clone(), uuid, database.clone()),
+    )
+    .await?;
+
+    if let Some(object) = result.as_object_mut() {
+        object.insert("name".to_string(), json!(name));
+    }
+
+    Ok(result)
+}
+
+async fn add_snapshot_request(
     name: String,
     uuid: Option<String>,
     database: State<'_, Database>,
-) -> Result<Value, String> {
+) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -196,20 +255,38 @@ pub async fn add_snapshot(
         &url,
         Some(payload),
         None,
-        Some(30),
+        api_info.long_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to add snapshot: {}", e))
+        .map_err(|e| ApiError::Other(format!("Failed to add snapshot: {}", e)))
 }
 
 #[tauri::command]
-pub async fn delete_snapshot(uuid: String, database: State<'_, Database>) -> Result<Value, String> {
+pub async fn delete_snapshot(
+    uuid: String,
+    database: State<'_, Database>,
+) -> Result<Value, ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "delete_snapshot",
+        &target,
+        delete_snapshot_request(uuid, database.clone()),
+    )
+    .await
+}
+
+async fn delete_snapshot_request(
+    uuid: String,
+    database: State<'_, Database>,
+) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -222,23 +299,38 @@ pub async fn delete_snapshot(uuid: String, database: State<'_, Database>) -> Res
         &url,
         Some(json!({})),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to delete snapshot: {}", e))
+        .map_err(|e| ApiError::Other(format!("Failed to delete snapshot: {}", e)))
 }
 
 #[tauri::command]
 pub async fn activate_snapshot(
     uuid: String,
     database: State<'_, Database>,
-) -> Result<Value, String> {
+) -> Result<Value, ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "activate_snapshot",
+        &target,
+        activate_snapshot_request(uuid, database.clone()),
+    )
+    .await
+}
+
+async fn activate_snapshot_request(
+    uuid: String,
+    database: State<'_, Database>,
+) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -251,16 +343,17 @@ pub async fn activate_snapshot(
         &url,
         Some(json!({})),
         None,
-        Some(30),
+        api_info.long_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to activate snapshot: {}", e))
+        .map_err(|e| ApiError::Other(format!("Failed to activate snapshot: {}", e)))
 }
 
 #[tauri::command]
@@ -268,7 +361,22 @@ pub async fn update_snapshot(
     uuid: String,
     name: String,
     database: State<'_, Database>,
-) -> Result<Value, String> {
+) -> Result<Value, ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "update_snapshot",
+        &target,
+        update_snapshot_request(uuid, name, database.clone()),
+    )
+    .await
+}
+
+async fn update_snapshot_request(
+    uuid: String,
+    name: String,
+    database: State<'_, Database>,
+) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -288,14 +396,15 @@ pub async fn update_snapshot(
         &url,
         Some(payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to update snapshot: {}", e))
+        .map_err(|e| ApiError::Other(format!("Failed to update snapshot: {}", e)))
 }
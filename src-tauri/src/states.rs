@@ -0,0 +1,229 @@
+use crate::db::{ApiInfo, Database};
+use crate::error::ApiError;
+use crate::http_client::make_http_request;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::net::IpAddr;
+use tauri::State;
+
+const DEFAULT_STATE_LIMIT: u32 = 100;
+const MAX_STATE_LIMIT: u32 = 5000;
+
+#[derive(Serialize, Debug)]
+pub struct FirewallState {
+    /// Needed together with `creator_id` to identify a single state
+    id: String,
+    creator_id: String,
+    interface: String,
+    protocol: String,
+    direction: String,
+    source: String,
+    destination: String,
+    /// Translated address and port when the state was created by NAT
+    nat: Option<String>,
+    state: String,
+    /// As reported by pf, e.g. "00:05:12"
+    age: String,
+    age_seconds: Option<u64>,
+    packets: Option<u64>,
+    bytes: Option<u64>,
+    rule: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct FirewallStates {
+    /// States matching the search on the firewall, which may be more than
+    /// were returned
+    total: u64,
+    states: Vec<FirewallState>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct KillStatesResult {
+    ip: String,
+    dropped_states: Option<u64>,
+}
+
+fn build_api_url(api_info: &ApiInfo, endpoint: &str) -> String {
+    format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
+}
+
+fn value_as_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.trim().to_string(),
+        Value::Number(n) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn value_as_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+// pf prints ages as "HH:MM:SS", with hours growing past 24 for old states
+fn parse_age(age: &str) -> Option<u64> {
+    age.split(':').try_fold(0u64, |total, part| {
+        part.trim().parse::<u64>().ok().map(|n| total * 60 + n)
+    })
+}
+
+// IPv6 addresses are bracketed so the port stays unambiguous
+fn endpoint(address: &str, port: &str) -> String {
+    match (address.contains(':'), port.is_empty()) {
+        (_, true) => address.to_string(),
+        (true, false) => format!("[{}]:{}", address, port),
+        (false, false) => format!("{}:{}", address, port),
+    }
+}
+
+fn parse_state(row: &Value) -> FirewallState {
+    let nat_addr = value_as_string(&row["nat_addr"]);
+    let age = value_as_string(&row["age"]);
+
+    FirewallState {
+        id: value_as_string(&row["id"]),
+        creator_id: value_as_string(&row["creatorid"]),
+        interface: value_as_string(&row["iface"]),
+        protocol: value_as_string(&row["proto"]),
+        direction: value_as_string(&row["direction"]),
+        source: endpoint(
+            &value_as_string(&row["src_addr"]),
+            &value_as_string(&row["src_port"]),
+        ),
+        destination: endpoint(
+            &value_as_string(&row["dst_addr"]),
+            &value_as_string(&row["dst_port"]),
+        ),
+        nat: (!nat_addr.is_empty())
+            .then(|| endpoint(&nat_addr, &value_as_string(&row["nat_port"]))),
+        state: value_as_string(&row["state"]),
+        age_seconds: parse_age(&age),
+        age,
+        packets: value_as_u64(&row["pkts"]),
+        bytes: value_as_u64(&row["bytes"]),
+        // The rule description, falling back to its label hash
+        rule: [&row["descr"], &row["label"]]
+            .into_iter()
+            .map(value_as_string)
+            .find(|rule| !rule.is_empty()),
+    }
+}
+
+/// Entries of the pf state table, optionally filtered by a search phrase
+/// such as an address or port
+#[tauri::command]
+pub async fn get_firewall_states(
+    database: State<'_, Database>,
+    search: Option<String>,
+    limit: Option<u32>,
+) -> Result<FirewallStates, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let limit = limit
+        .unwrap_or(DEFAULT_STATE_LIMIT)
+        .clamp(1, MAX_STATE_LIMIT);
+
+    let url = build_api_url(&api_info, "/api/diagnostics/firewall/queryStates/");
+
+    let payload = json!({
+        "current": 1,
+        "rowCount": limit,
+        "sort": {},
+        "searchPhrase": search.unwrap_or_default().trim()
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let body = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse firewall states: {}", e))?;
+
+    let states: Vec<FirewallState> = body["rows"]
+        .as_array()
+        .map(|rows| rows.iter().map(parse_state).collect())
+        .unwrap_or_default();
+
+    Ok(FirewallStates {
+        total: value_as_u64(&body["total"]).unwrap_or(states.len() as u64),
+        states,
+    })
+}
+
+/// Drops every state to or from `ip`, e.g. to force a stuck connection to be
+/// re-established through new NAT or routing rules
+#[tauri::command]
+pub async fn kill_states_for_ip(
+    database: State<'_, Database>,
+    ip: String,
+) -> Result<KillStatesResult, ApiError> {
+    // Only a bare address, so a typo can't match far more states than meant
+    let ip = ip
+        .trim()
+        .parse::<IpAddr>()
+        .map_err(|_| format!("Invalid IP address: {}", ip.trim()))?
+        .to_string();
+
+    crate::audit::audited(
+        &database,
+        "kill_states_for_ip",
+        &ip,
+        kill_states_request(database.clone(), ip.clone()),
+    )
+    .await
+}
+
+async fn kill_states_request(
+    database: State<'_, Database>,
+    ip: String,
+) -> Result<KillStatesResult, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, "/api/diagnostics/firewall/killStates/");
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(json!({ "filter": ip })),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let body = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    if body["result"].as_str() != Some("ok") {
+        return Err(format!("Failed to kill states: {}", body).into());
+    }
+
+    Ok(KillStatesResult {
+        ip,
+        dropped_states: value_as_u64(&body["dropped_states"]),
+    })
+}
@@ -0,0 +1,126 @@
+use crate::db::{ApiInfo, Database};
+use crate::error::ApiError;
+use crate::http_client::make_http_request;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::State;
+
+const MAX_LOG_LIMIT: u32 = 5000;
+
+#[derive(Serialize, Debug)]
+pub struct LogModule {
+    name: &'static str,
+    description: &'static str,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SystemLogEntry {
+    timestamp: String,
+    severity: String,
+    process: String,
+    pid: Option<u32>,
+    message: String,
+}
+
+/// Log sources exposed under /api/diagnostics/log/core/<name>, matching the
+/// logs listed in the web UI. Unbound logs under "resolver".
+const LOG_MODULES: &[(&str, &str)] = &[
+    ("system", "General system log"),
+    ("audit", "Audit log"),
+    ("boot", "Boot messages"),
+    ("configd", "Backend (configd)"),
+    ("dhcpd", "DHCP server"),
+    ("resolver", "Unbound DNS"),
+    ("openvpn", "OpenVPN"),
+    ("ipsec", "IPsec"),
+    ("lighttpd", "Web GUI"),
+    ("ntpd", "NTP"),
+    ("pkg", "Package manager"),
+    ("routing", "Routing"),
+    ("gateways", "Gateway monitoring"),
+    ("portalauth", "Captive portal"),
+];
+
+fn build_api_url(api_info: &ApiInfo, endpoint: &str) -> String {
+    format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
+}
+
+fn field(row: &Value, keys: &[&str]) -> String {
+    keys.iter()
+        .find_map(|key| match &row[*key] {
+            Value::String(s) if !s.is_empty() => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+fn parse_log_entry(row: &Value) -> SystemLogEntry {
+    SystemLogEntry {
+        timestamp: field(row, &["timestamp"]),
+        severity: field(row, &["severity"]),
+        process: field(row, &["process_name"]),
+        pid: field(row, &["pid"]).parse().ok(),
+        message: field(row, &["line"]),
+    }
+}
+
+#[tauri::command]
+pub fn list_log_modules() -> Vec<LogModule> {
+    LOG_MODULES
+        .iter()
+        .map(|(name, description)| LogModule { name, description })
+        .collect()
+}
+
+/// Newest entries first, as returned by the firewall
+#[tauri::command]
+pub async fn get_system_logs(
+    database: State<'_, Database>,
+    module: String,
+    limit: u32,
+    search: Option<String>,
+) -> Result<Vec<SystemLogEntry>, ApiError> {
+    if !LOG_MODULES.iter().any(|(name, _)| *name == module) {
+        return Err(format!("Unknown log module: {}", module).into());
+    }
+    if limit == 0 || limit > MAX_LOG_LIMIT {
+        return Err(format!("Limit must be between 1 and {}", MAX_LOG_LIMIT).into());
+    }
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, &format!("/api/diagnostics/log/core/{}", module));
+
+    let payload = json!({
+        "current": 1,
+        "rowCount": limit,
+        "sort": {},
+        "searchPhrase": search.unwrap_or_default()
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let body = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse {} log: {}", module, e))?;
+
+    Ok(body["rows"]
+        .as_array()
+        .map(|rows| rows.iter().map(parse_log_entry).collect())
+        .unwrap_or_default())
+}
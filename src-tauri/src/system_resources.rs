@@ -1,19 +1,68 @@
 use crate::db::Database;
+use crate::error::ApiError;
 use crate::http_client::make_http_request;
+use crate::response_cache::{request_key, ResponseCache};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
 use tauri::State;
 
+const DISK_FULL_THRESHOLD: u8 = 90;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Memory {
     total: String,
     total_frmt: String,
     used: u64,
     used_frmt: String,
+    // The fields below come from top's summary and are missing when it
+    // can't be read
+    #[serde(default)]
+    free: Option<u64>,
+    /// Inactive and laundry pages, which FreeBSD reclaims under pressure
+    #[serde(default)]
+    cached: Option<u64>,
+    #[serde(default)]
+    wired: Option<u64>,
+    /// ZFS ARC size, only reported on ZFS installs
+    #[serde(default)]
+    arc: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SwapUsage {
+    total: u64,
+    used: u64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ProcessActivity {
+    pid: u32,
+    user: String,
+    command: String,
+    cpu_percent: f64,
+    /// Resident size as a share of physical memory
+    memory_percent: Option<f64>,
+    resident: Option<u64>,
+    state: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CpuUsage {
+    total_percent: f64,
+    /// Utilization indexed by core. Holds only the total when top doesn't
+    /// print a line per core.
+    cores: Vec<f64>,
+    per_core_available: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SystemResources {
     memory: Memory,
+    #[serde(default)]
+    swap: Option<SwapUsage>,
+    #[serde(default)]
+    cpu: Option<CpuUsage>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -24,13 +73,30 @@ pub struct DiskDevice {
     blocks: String,
     used: String,
     available: String,
-    used_pct: u8,
-    mountpoint: String,
+    pub(crate) used_pct: u8,
+    pub(crate) mountpoint: String,
+    // Byte counts parsed from the human readable sizes above
+    #[serde(default)]
+    total_bytes: Option<u64>,
+    #[serde(default)]
+    used_bytes: Option<u64>,
+    #[serde(default)]
+    available_bytes: Option<u64>,
+    /// Only present when the firmware reports inode counts
+    #[serde(default, alias = "used-inodes")]
+    inodes_used: Option<u64>,
+    #[serde(default, alias = "free-inodes")]
+    inodes_free: Option<u64>,
+    #[serde(default)]
+    pub(crate) inodes_used_pct: Option<u8>,
+    /// At or above `DISK_FULL_THRESHOLD` in space or inode usage
+    #[serde(default)]
+    pub(crate) nearly_full: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SystemDisk {
-    devices: Vec<DiskDevice>,
+    pub(crate) devices: Vec<DiskDevice>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -41,6 +107,11 @@ pub struct TemperatureSensor {
     #[serde(rename = "type")]
     sensor_type: String,
     type_translated: String,
+    /// e.g. "CPU 0" or "Zone 1", built from the type and sequence
+    #[serde(default)]
+    label: String,
+    #[serde(default)]
+    celsius: Option<f64>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -48,15 +119,202 @@ pub struct SystemTemperature {
     sensors: Vec<TemperatureSensor>,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NtpPeer {
+    server: String,
+    refid: String,
+    stratum: Option<u32>,
+    offset_ms: Option<f64>,
+    delay_ms: Option<f64>,
+    jitter_ms: Option<f64>,
+    status: String,
+    selected: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TimeStatus {
+    synchronized: bool,
+    stratum: Option<u32>,
+    offset_ms: Option<f64>,
+    local_time: String,
+    timezone: String,
+    peers: Vec<NtpPeer>,
+}
+
+fn value_as_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.trim().to_string(),
+        Value::Number(n) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+// Parses top sizes such as "512K", "140M" or "1024" into bytes
+fn parse_top_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let (number, multiplier) = match size.chars().last()? {
+        'K' => (&size[..size.len() - 1], 1u64 << 10),
+        'M' => (&size[..size.len() - 1], 1 << 20),
+        'G' => (&size[..size.len() - 1], 1 << 30),
+        'T' => (&size[..size.len() - 1], 1 << 40),
+        _ => (size, 1),
+    };
+    number
+        .parse::<f64>()
+        .ok()
+        .map(|value| (value * multiplier as f64) as u64)
+}
+
+/// Reads a top summary line like "Mem: 140M Active, 1G Inact, 5G Free" into
+/// a map of label to bytes
+fn parse_top_summary(headers: &[String], prefix: &str) -> HashMap<String, u64> {
+    headers
+        .iter()
+        .find_map(|line| line.trim().strip_prefix(prefix))
+        .map(|rest| {
+            rest.split(',')
+                .filter_map(|part| {
+                    let (size, label) = part.trim().split_once(' ')?;
+                    Some((label.trim().to_string(), parse_top_size(size)?))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Busy percentage from the states in a top CPU line such as
+// " 2.3% user,  0.0% nice,  1.2% system,  0.4% interrupt, 96.1% idle"
+fn parse_cpu_states(states: &str) -> Option<f64> {
+    let idle = states.split(',').find_map(|state| {
+        let (percent, name) = state.trim().split_once('%')?;
+        (name.trim() == "idle").then(|| percent.trim().parse::<f64>().ok())?
+    })?;
+    Some((100.0 - idle).clamp(0.0, 100.0))
+}
+
+/// top prints "CPU:" for the total, or "CPU 0:", "CPU 1:"... when run with
+/// per-CPU statistics
+fn parse_cpu_usage(headers: &[String]) -> Option<CpuUsage> {
+    let mut total = None;
+    let mut cores = Vec::new();
+
+    for line in headers {
+        let Some(rest) = line.trim().strip_prefix("CPU") else {
+            continue;
+        };
+        let Some((label, states)) = rest.split_once(':') else {
+            continue;
+        };
+        let Some(busy) = parse_cpu_states(states) else {
+            continue;
+        };
+        match label.trim() {
+            "" => total = Some(busy),
+            core => {
+                if let Ok(index) = core.parse::<usize>() {
+                    cores.push((index, busy));
+                }
+            }
+        }
+    }
+
+    cores.sort_by_key(|(index, _)| *index);
+    let cores: Vec<f64> = cores.into_iter().map(|(_, busy)| busy).collect();
+
+    if cores.is_empty() {
+        let total = total?;
+        return Some(CpuUsage {
+            total_percent: total,
+            cores: vec![total],
+            per_core_available: false,
+        });
+    }
+
+    Some(CpuUsage {
+        total_percent: total.unwrap_or_else(|| cores.iter().sum::<f64>() / cores.len() as f64),
+        cores,
+        per_core_available: true,
+    })
+}
+
+fn activity_headers(activity: &Value) -> Vec<String> {
+    activity["headers"]
+        .as_array()
+        .map(|headers| {
+            headers
+                .iter()
+                .filter_map(|line| line.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) async fn fetch_activity(api_info: &crate::db::ApiInfo) -> Result<Value, ApiError> {
+    let url = format!(
+        "{}:{}/api/diagnostics/activity/getActivity",
+        api_info.api_url, api_info.port
+    );
+
+    let response = make_http_request(
+        "GET",
+        &url,
+        None,
+        None,
+        api_info.dashboard_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    response
+        .json::<Value>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse activity: {}", e)))
+}
+
+fn parse_ntp_peer(row: &Value) -> NtpPeer {
+    let status = value_as_string(&row["status"]);
+    // ntpq marks the peer the clock is synced to with "*" (or "o" for PPS)
+    let selected = status.starts_with('*')
+        || status.starts_with('o')
+        || status.eq_ignore_ascii_case("active peer");
+
+    NtpPeer {
+        server: value_as_string(&row["server"]),
+        refid: value_as_string(&row["refid"]),
+        stratum: value_as_string(&row["stratum"]).parse().ok(),
+        offset_ms: value_as_string(&row["offset"]).parse().ok(),
+        delay_ms: value_as_string(&row["delay"]).parse().ok(),
+        jitter_ms: value_as_string(&row["jitter"]).parse().ok(),
+        status,
+        selected,
+    }
+}
+
 #[tauri::command]
 pub async fn get_system_resources(
     database: State<'_, Database>,
-) -> Result<SystemResources, String> {
+    cache: State<'_, ResponseCache>,
+    force: Option<bool>,
+) -> Result<SystemResources, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
         .ok_or_else(|| "API info not found".to_string())?;
 
+    cache
+        .get_or_fetch(
+            &format!("{}:system_resources", api_info.id),
+            force.unwrap_or(false),
+            fetch_system_resources(&api_info),
+        )
+        .await
+}
+
+pub(crate) async fn fetch_system_resources(
+    api_info: &crate::db::ApiInfo,
+) -> Result<SystemResources, ApiError> {
     let url = format!(
         "{}:{}/api/diagnostics/system/systemResources",
         api_info.api_url, api_info.port
@@ -67,25 +325,76 @@ pub async fn get_system_resources(
         &url,
         None,
         None,
-        Some(30),
+        api_info.dashboard_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
-    response
-        .json::<SystemResources>()
+    let body = response
+        .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    let mut resources = serde_json::from_value::<SystemResources>(body.clone())
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    resources.memory.arc = value_as_string(&body["memory"]["arc"]).parse().ok();
+
+    // The memory breakdown and CPU usage are a nice-to-have, so the basic
+    // figures are still returned when top's output can't be read
+    match fetch_activity(api_info).await {
+        Ok(activity) => {
+            let headers = activity_headers(&activity);
+            let memory = parse_top_summary(&headers, "Mem:");
+            let swap = parse_top_summary(&headers, "Swap:");
+
+            resources.cpu = parse_cpu_usage(&headers);
+            resources.memory.free = memory.get("Free").copied();
+            resources.memory.wired = memory.get("Wired").copied();
+            if memory.contains_key("Inact") || memory.contains_key("Laundry") {
+                resources.memory.cached = Some(
+                    memory.get("Inact").copied().unwrap_or(0)
+                        + memory.get("Laundry").copied().unwrap_or(0),
+                );
+            }
+
+            // top only prints "Used" once swap is actually in use
+            if let Some(total) = swap.get("Total").copied() {
+                let used = swap.get("Used").copied().unwrap_or_else(|| {
+                    total.saturating_sub(swap.get("Free").copied().unwrap_or(total))
+                });
+                resources.swap = Some(SwapUsage { total, used });
+            }
+        }
+        Err(e) => log::warn!("Failed to read memory breakdown and CPU usage: {}", e),
+    }
+
+    Ok(resources)
 }
 
 #[tauri::command]
-pub async fn get_system_disk(database: State<'_, Database>) -> Result<SystemDisk, String> {
+pub async fn get_system_disk(
+    database: State<'_, Database>,
+    cache: State<'_, ResponseCache>,
+) -> Result<SystemDisk, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
         .ok_or_else(|| "API info not found".to_string())?;
 
+    cache
+        .single_flight(
+            &request_key(&api_info, "GET", "/api/diagnostics/system/systemDisk", None),
+            fetch_system_disk(&api_info),
+        )
+        .await
+}
+
+pub(crate) async fn fetch_system_disk(
+    api_info: &crate::db::ApiInfo,
+) -> Result<SystemDisk, ApiError> {
     let url = format!(
         "{}:{}/api/diagnostics/system/systemDisk",
         api_info.api_url, api_info.port
@@ -96,27 +405,66 @@ pub async fn get_system_disk(database: State<'_, Database>) -> Result<SystemDisk
         &url,
         None,
         None,
-        Some(30),
+        api_info.dashboard_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
-    response
+    let mut disk = response
         .json::<SystemDisk>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    for device in disk.devices.iter_mut() {
+        device.total_bytes = parse_top_size(&device.blocks);
+        device.used_bytes = parse_top_size(&device.used);
+        device.available_bytes = parse_top_size(&device.available);
+
+        if let (Some(used), Some(free)) = (device.inodes_used, device.inodes_free) {
+            device.inodes_used_pct = (used * 100).checked_div(used + free).map(|pct| pct as u8);
+        }
+
+        device.nearly_full = device.used_pct >= DISK_FULL_THRESHOLD
+            || device
+                .inodes_used_pct
+                .is_some_and(|pct| pct >= DISK_FULL_THRESHOLD);
+    }
+
+    // Fullest filesystem first
+    disk.devices
+        .sort_by_key(|device| std::cmp::Reverse(device.used_pct));
+
+    Ok(disk)
 }
 
 #[tauri::command(rename_all = "snake_case")]
 pub async fn get_system_temperature(
     database: State<'_, Database>,
-) -> Result<SystemTemperature, String> {
+    cache: State<'_, ResponseCache>,
+) -> Result<SystemTemperature, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
         .ok_or_else(|| "API info not found".to_string())?;
 
+    cache
+        .single_flight(
+            &request_key(
+                &api_info,
+                "GET",
+                "/api/diagnostics/system/systemTemperature",
+                None,
+            ),
+            fetch_system_temperature(&api_info),
+        )
+        .await
+}
+
+async fn fetch_system_temperature(
+    api_info: &crate::db::ApiInfo,
+) -> Result<SystemTemperature, ApiError> {
     let url = format!(
         "{}:{}/api/diagnostics/system/systemTemperature",
         api_info.api_url, api_info.port
@@ -127,9 +475,10 @@ pub async fn get_system_temperature(
         &url,
         None,
         None,
-        Some(30),
+        api_info.dashboard_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -166,7 +515,7 @@ pub async fn get_system_temperature(
                         .unwrap_or_default();
                     let temperature = item
                         .get("temperature")
-                        .and_then(|v| v.as_str())
+                        .map(value_as_string)
                         .unwrap_or_default();
                     let sensor_type = item
                         .get("type")
@@ -177,12 +526,31 @@ pub async fn get_system_temperature(
                         .and_then(|v| v.as_str())
                         .unwrap_or_default();
 
+                    let kind = if sensor_type_translated.is_empty() {
+                        sensor_type
+                    } else {
+                        sensor_type_translated
+                    };
+                    let label = if kind.is_empty() {
+                        device.to_string()
+                    } else {
+                        format!("{} {}", kind, device_seq)
+                    };
+                    // Values may come with a unit suffix such as "45.0C"
+                    let celsius = temperature
+                        .trim()
+                        .trim_end_matches(['C', '°', ' '])
+                        .parse::<f64>()
+                        .ok();
+
                     sensors.push(TemperatureSensor {
                         device: device.to_string(),
                         device_seq: device_seq.to_string(),
-                        temperature: temperature.to_string(),
+                        temperature,
                         sensor_type: sensor_type.to_string(),
                         type_translated: sensor_type_translated.to_string(),
+                        label: label.trim().to_string(),
+                        celsius,
                     });
                 }
 
@@ -203,3 +571,142 @@ pub async fn get_system_temperature(
         }
     }
 }
+
+#[tauri::command]
+pub async fn get_time_status(database: State<'_, Database>) -> Result<TimeStatus, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let time_url = format!(
+        "{}:{}/api/diagnostics/system/systemTime",
+        api_info.api_url, api_info.port
+    );
+
+    let time_response = make_http_request(
+        "GET",
+        &time_url,
+        None,
+        None,
+        api_info.dashboard_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let time = time_response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    // datetime looks like "Tue Oct 15 12:00:00 CEST 2026"
+    let local_time = value_as_string(&time["datetime"]);
+    let timezone = local_time
+        .split_whitespace()
+        .rev()
+        .nth(1)
+        .filter(|token| {
+            token
+                .chars()
+                .all(|c| c.is_ascii_alphabetic() || c == '+' || c == '-')
+        })
+        .unwrap_or_default()
+        .to_string();
+
+    let ntp_url = format!(
+        "{}:{}/api/ntpd/service/meta",
+        api_info.api_url, api_info.port
+    );
+
+    // ntpd may be disabled, in which case there are simply no peers to report
+    let ntp_status = match make_http_request(
+        "GET",
+        &ntp_url,
+        None,
+        None,
+        api_info.dashboard_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await
+    {
+        Ok(response) => response
+            .json::<Value>()
+            .await
+            .map_err(|e| format!("Failed to parse NTP status: {}", e))?,
+        Err(ApiError::NotFound(_)) => Value::Null,
+        Err(e) => return Err(e),
+    };
+
+    let peers: Vec<NtpPeer> = ntp_status["rows"]
+        .as_array()
+        .map(|rows| rows.iter().map(parse_ntp_peer).collect())
+        .unwrap_or_default();
+
+    let system_peer = peers.iter().find(|peer| peer.selected);
+
+    Ok(TimeStatus {
+        synchronized: system_peer.is_some(),
+        stratum: system_peer
+            .and_then(|peer| peer.stratum)
+            .map(|stratum| stratum + 1),
+        offset_ms: system_peer.and_then(|peer| peer.offset_ms),
+        local_time,
+        timezone,
+        peers,
+    })
+}
+
+/// Processes as reported by top on the firewall, busiest first
+#[tauri::command]
+pub async fn get_system_activity(
+    database: State<'_, Database>,
+    limit: Option<usize>,
+) -> Result<Vec<ProcessActivity>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let activity = fetch_activity(&api_info).await?;
+
+    // top's Mem: categories add up to physical memory
+    let physical: u64 = parse_top_summary(&activity_headers(&activity), "Mem:")
+        .values()
+        .sum();
+
+    let mut processes: Vec<ProcessActivity> = activity["details"]
+        .as_array()
+        .map(|rows| {
+            rows.iter()
+                .filter_map(|row| {
+                    let resident = parse_top_size(&value_as_string(&row["RES"]));
+                    Some(ProcessActivity {
+                        pid: value_as_string(&row["PID"]).parse().ok()?,
+                        user: value_as_string(&row["USERNAME"]),
+                        command: value_as_string(&row["COMMAND"]),
+                        cpu_percent: value_as_string(&row["WCPU"])
+                            .trim_end_matches('%')
+                            .parse()
+                            .unwrap_or(0.0),
+                        memory_percent: resident
+                            .filter(|_| physical > 0)
+                            .map(|bytes| bytes as f64 * 100.0 / physical as f64),
+                        resident,
+                        state: value_as_string(&row["STATE"]),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    processes.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent));
+    if let Some(limit) = limit {
+        processes.truncate(limit);
+    }
+
+    Ok(processes)
+}
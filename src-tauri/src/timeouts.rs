@@ -0,0 +1,94 @@
+use crate::db::Database;
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::State;
+
+const MAX_TIMEOUT_SECONDS: u64 = 3600;
+
+/// Request timeout groups that can be overridden per profile
+#[derive(Clone, Copy, Debug)]
+pub enum TimeoutCategory {
+    /// Polled dashboard widgets, which should fail fast
+    Dashboard,
+    /// Ordinary reads and configuration changes
+    Standard,
+    /// Firmware updates, snapshots and backups
+    LongRunning,
+}
+
+impl TimeoutCategory {
+    const ALL: [TimeoutCategory; 3] = [
+        TimeoutCategory::Dashboard,
+        TimeoutCategory::Standard,
+        TimeoutCategory::LongRunning,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            TimeoutCategory::Dashboard => "dashboard",
+            TimeoutCategory::Standard => "standard",
+            TimeoutCategory::LongRunning => "long_running",
+        }
+    }
+
+    pub fn default_seconds(self) -> u64 {
+        match self {
+            TimeoutCategory::Dashboard => 10,
+            TimeoutCategory::Standard => 30,
+            TimeoutCategory::LongRunning => 300,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct TimeoutSetting {
+    category: &'static str,
+    seconds: u64,
+    default_seconds: u64,
+}
+
+#[tauri::command]
+pub fn get_timeout_settings(database: State<'_, Database>) -> Result<Vec<TimeoutSetting>, String> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    Ok(TimeoutCategory::ALL
+        .iter()
+        .map(|category| TimeoutSetting {
+            category: category.key(),
+            seconds: api_info.timeout(*category),
+            default_seconds: category.default_seconds(),
+        })
+        .collect())
+}
+
+/// Replaces the default profile's overrides; categories left out go back to
+/// their defaults
+#[tauri::command]
+pub fn save_timeout_settings(
+    database: State<'_, Database>,
+    settings: HashMap<String, u64>,
+) -> Result<(), String> {
+    for (category, seconds) in &settings {
+        if !TimeoutCategory::ALL.iter().any(|c| c.key() == category) {
+            return Err(format!("Unknown timeout category: {}", category));
+        }
+        if *seconds == 0 || *seconds > MAX_TIMEOUT_SECONDS {
+            return Err(format!(
+                "Timeout for {} must be between 1 and {} seconds",
+                category, MAX_TIMEOUT_SECONDS
+            ));
+        }
+    }
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    database
+        .save_timeout_overrides(api_info.id, &settings)
+        .map_err(|e| format!("Failed to save timeout settings: {}", e))
+}
@@ -1,8 +1,12 @@
-use crate::db::Database;
+use crate::db::{ApiInfo, Database};
+use crate::error::ApiError;
 use crate::http_client::make_http_request;
+use crate::response_cache::{request_key, ResponseCache};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Mutex;
+use std::time::Instant;
 use tauri::{Manager, State};
 
 const MAX_DATA_POINTS: usize = 120;
@@ -79,10 +83,57 @@ pub struct TrafficDataPoint {
     pub bits_per_second_out: u64,
 }
 
+/// Cumulative netstat counters for one interface
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct InterfaceCounters {
+    packets_in: u64,
+    packets_out: u64,
+    bytes_in: u64,
+    bytes_out: u64,
+    errors_in: u64,
+    errors_out: u64,
+    drops: u64,
+    collisions: u64,
+}
+
+/// Per-second change of each counter since the previous call
+#[derive(Serialize, Debug)]
+pub struct InterfaceCounterRates {
+    seconds: f64,
+    packets_in: f64,
+    packets_out: f64,
+    errors_in: f64,
+    errors_out: f64,
+    drops: f64,
+    collisions: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct InterfaceStats {
+    interface: String,
+    mtu: Option<u32>,
+    counters: InterfaceCounters,
+    /// `None` on the first call for an interface
+    rates: Option<InterfaceCounterRates>,
+}
+
+/// Current throughput of one host on an interface, as measured by iftop
+#[derive(Serialize, Debug)]
+pub struct TopTalker {
+    address: String,
+    /// Reverse DNS name when the firewall could resolve one
+    hostname: Option<String>,
+    bits_per_second_in: u64,
+    bits_per_second_out: u64,
+    bits_per_second_total: u64,
+}
+
 #[derive(Default)]
 pub struct TrafficCache {
     data_points: Mutex<Vec<TrafficDataPoint>>,
     last_update: Mutex<Option<InterfaceTraffic>>,
+    // Previous counters per interface for get_interface_stats rates
+    last_stats: Mutex<HashMap<String, (Instant, InterfaceCounters)>>,
 }
 
 impl TrafficCache {
@@ -90,6 +141,7 @@ impl TrafficCache {
         Self {
             data_points: Mutex::new(Vec::new()),
             last_update: Mutex::new(None),
+            last_stats: Mutex::new(HashMap::new()),
         }
     }
 
@@ -160,18 +212,30 @@ impl TrafficCache {
 
         let mut last_update = self.last_update.lock().unwrap();
         *last_update = None;
+
+        self.last_stats.lock().unwrap().clear();
     }
 }
 
 #[tauri::command]
 pub async fn get_interface_traffic(
     database: State<'_, Database>,
-) -> Result<InterfaceTraffic, String> {
+    cache: State<'_, ResponseCache>,
+) -> Result<InterfaceTraffic, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
         .ok_or_else(|| "API info not found".to_string())?;
 
+    cache
+        .single_flight(
+            &request_key(&api_info, "GET", "/api/diagnostics/traffic/interface", None),
+            fetch_interface_traffic(&api_info),
+        )
+        .await
+}
+
+async fn fetch_interface_traffic(api_info: &ApiInfo) -> Result<InterfaceTraffic, ApiError> {
     let url = format!(
         "{}:{}/api/diagnostics/traffic/interface",
         api_info.api_url, api_info.port
@@ -182,35 +246,305 @@ pub async fn get_interface_traffic(
         &url,
         None,
         None,
-        Some(30),
+        api_info.dashboard_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<InterfaceTraffic>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
 
 #[tauri::command]
 pub fn get_traffic_graph_data(
     traffic_cache: State<'_, TrafficCache>,
-) -> Result<Vec<TrafficDataPoint>, String> {
+) -> Result<Vec<TrafficDataPoint>, ApiError> {
     Ok(traffic_cache.get_data_points())
 }
 
 #[tauri::command]
 pub async fn update_traffic_data(
     database: State<'_, Database>,
+    cache: State<'_, ResponseCache>,
     traffic_cache: State<'_, TrafficCache>,
-) -> Result<(), String> {
-    let traffic = get_interface_traffic(database).await?;
+) -> Result<(), ApiError> {
+    let traffic = get_interface_traffic(database, cache).await?;
     traffic_cache.add_data_point(&traffic);
     Ok(())
 }
 
+/// Polls the traffic endpoint `samples + 1` times, `interval_ms` apart, and
+/// returns one rate sample per interval for the given interface
+#[tauri::command]
+pub async fn get_interface_traffic_history(
+    database: State<'_, Database>,
+    interface: String,
+    samples: u32,
+    interval_ms: u64,
+) -> Result<Vec<TrafficDataPoint>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let samples = (samples as usize).clamp(1, MAX_DATA_POINTS);
+    let interval = std::time::Duration::from_millis(interval_ms.max(100));
+
+    let find_interface = |traffic: &InterfaceTraffic| -> Option<InterfaceData> {
+        traffic
+            .interfaces
+            .iter()
+            .find(|(key, data)| {
+                *key == &interface || data.name == interface || data.device == interface
+            })
+            .map(|(_, data)| data.clone())
+    };
+
+    let mut history = Vec::with_capacity(samples);
+    let mut previous: Option<(f64, u64, u64)> = None;
+
+    for reading in 0..=samples {
+        if reading > 0 {
+            tokio::time::sleep(interval).await;
+        }
+
+        let traffic = fetch_interface_traffic(&api_info).await?;
+        let data = find_interface(&traffic)
+            .ok_or_else(|| format!("Interface {} not found in traffic data", interface))?;
+
+        let bytes_in = data.bytes_received.parse::<u64>().unwrap_or(0);
+        let bytes_out = data.bytes_transmitted.parse::<u64>().unwrap_or(0);
+
+        if let Some((previous_time, previous_in, previous_out)) = previous {
+            let time_diff = traffic.time - previous_time;
+            let seconds = if time_diff > 0.0 {
+                time_diff
+            } else {
+                interval.as_secs_f64()
+            };
+
+            // Counters restart when the interface bounces, so a negative delta counts as zero
+            let bits_per_second_in =
+                (bytes_in.saturating_sub(previous_in) as f64 * 8.0 / seconds) as u64;
+            let bits_per_second_out =
+                (bytes_out.saturating_sub(previous_out) as f64 * 8.0 / seconds) as u64;
+
+            history.push(TrafficDataPoint {
+                timestamp: traffic.time,
+                interface_name: data.name.clone(),
+                bytes_in,
+                bytes_out,
+                bits_per_second_in,
+                bits_per_second_out,
+            });
+        }
+
+        previous = Some((traffic.time, bytes_in, bytes_out));
+    }
+
+    Ok(history)
+}
+
+fn counter(row: &Value, key: &str) -> u64 {
+    match &row[key] {
+        Value::Number(n) => n.as_u64().unwrap_or(0),
+        Value::String(s) => s.trim().parse().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Error, drop and collision counters from netstat for one interface, by
+/// name (e.g. "igb0"). Rates are computed against the previous call.
+#[tauri::command]
+pub async fn get_interface_stats(
+    database: State<'_, Database>,
+    traffic_cache: State<'_, TrafficCache>,
+    interface: String,
+) -> Result<InterfaceStats, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = format!(
+        "{}:{}/api/diagnostics/interface/getInterfaceStatistics",
+        api_info.api_url, api_info.port
+    );
+
+    let response = make_http_request(
+        "GET",
+        &url,
+        None,
+        None,
+        api_info.dashboard_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let body = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse interface statistics: {}", e))?;
+
+    // One row per address; the link-level row ("<Link#n>") carries the
+    // counters for the whole interface
+    let rows: Vec<&Value> = body["statistics"]
+        .as_object()
+        .map(|rows| {
+            rows.values()
+                .filter(|row| row["name"].as_str() == Some(interface.as_str()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let row = rows
+        .iter()
+        .find(|row| {
+            row["network"]
+                .as_str()
+                .is_some_and(|network| network.starts_with("<Link"))
+        })
+        .or_else(|| rows.first())
+        .ok_or_else(|| format!("Interface {} not found in statistics", interface))?;
+
+    let counters = InterfaceCounters {
+        packets_in: counter(row, "received-packets"),
+        packets_out: counter(row, "sent-packets"),
+        bytes_in: counter(row, "received-bytes"),
+        bytes_out: counter(row, "sent-bytes"),
+        errors_in: counter(row, "received-errors"),
+        errors_out: counter(row, "send-errors"),
+        drops: counter(row, "dropped-packets"),
+        collisions: counter(row, "collisions"),
+    };
+    let mtu = Some(counter(row, "mtu") as u32).filter(|mtu| *mtu > 0);
+
+    let now = Instant::now();
+    let previous = traffic_cache
+        .last_stats
+        .lock()
+        .unwrap()
+        .insert(interface.clone(), (now, counters.clone()));
+
+    let rates = previous.and_then(|(then, before)| {
+        let seconds = now.duration_since(then).as_secs_f64();
+        if seconds <= 0.0 {
+            return None;
+        }
+        // Counters reset when the interface is reinitialized
+        let rate = |current: u64, previous: u64| current.saturating_sub(previous) as f64 / seconds;
+        Some(InterfaceCounterRates {
+            seconds,
+            packets_in: rate(counters.packets_in, before.packets_in),
+            packets_out: rate(counters.packets_out, before.packets_out),
+            errors_in: rate(counters.errors_in, before.errors_in),
+            errors_out: rate(counters.errors_out, before.errors_out),
+            drops: rate(counters.drops, before.drops),
+            collisions: rate(counters.collisions, before.collisions),
+        })
+    });
+
+    Ok(InterfaceStats {
+        interface,
+        mtu,
+        counters,
+        rates,
+    })
+}
+
+const DEFAULT_TOP_TALKERS: usize = 10;
+
+fn parse_top_talker(record: &Value) -> Option<TopTalker> {
+    let address = record["address"].as_str()?.trim().to_string();
+    let bits_per_second_in = counter(record, "rate_bits_in");
+    let bits_per_second_out = counter(record, "rate_bits_out");
+    let hostname = record["rname"]
+        .as_str()
+        .map(str::trim)
+        .filter(|name| !name.is_empty() && *name != address)
+        .map(str::to_string);
+
+    Some(TopTalker {
+        address,
+        hostname,
+        bits_per_second_in,
+        bits_per_second_out,
+        bits_per_second_total: bits_per_second_in + bits_per_second_out,
+    })
+}
+
+/// Hosts moving the most traffic through an interface (e.g. "lan") right
+/// now, busiest first. The firewall samples for a couple of seconds before
+/// answering.
+#[tauri::command]
+pub async fn get_top_talkers(
+    database: State<'_, Database>,
+    interface: String,
+    count: Option<usize>,
+) -> Result<Vec<TopTalker>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let interface = interface.trim();
+    if interface.is_empty()
+        || !interface
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.'))
+    {
+        return Err(format!("Invalid interface name: {}", interface).into());
+    }
+
+    let url = format!(
+        "{}:{}/api/diagnostics/traffic/top/{}",
+        api_info.api_url, api_info.port, interface
+    );
+
+    let response = make_http_request(
+        "GET",
+        &url,
+        None,
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await
+    .map_err(|e| {
+        e.plugin_missing("Per-host traffic is not available: it requires OPNsense 21.1 or newer")
+    })?;
+
+    let body = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse top talkers: {}", e))?;
+
+    let section = &body[interface];
+    if section.is_null() {
+        return Err(format!("Interface {} not found in traffic data", interface).into());
+    }
+    if let Some(status) = section["status"].as_str().filter(|status| *status != "ok") {
+        return Err(format!("Failed to measure traffic on {}: {}", interface, status).into());
+    }
+
+    let mut talkers: Vec<TopTalker> = section["records"]
+        .as_array()
+        .map(|records| records.iter().filter_map(parse_top_talker).collect())
+        .unwrap_or_default();
+
+    talkers.sort_by_key(|talker| std::cmp::Reverse(talker.bits_per_second_total));
+    talkers.truncate(count.unwrap_or(DEFAULT_TOP_TALKERS));
+
+    Ok(talkers)
+}
+
 pub fn register_traffic_cache(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     let traffic_cache = TrafficCache::new();
     app.manage(traffic_cache);
@@ -218,7 +552,7 @@ pub fn register_traffic_cache(app: &mut tauri::App) -> Result<(), Box<dyn std::e
 }
 
 #[tauri::command]
-pub fn clear_traffic_cache(traffic_cache: State<'_, TrafficCache>) -> Result<(), String> {
+pub fn clear_traffic_cache(traffic_cache: State<'_, TrafficCache>) -> Result<(), ApiError> {
     traffic_cache.clear();
     Ok(())
 }
@@ -1,4 +1,5 @@
 use crate::db::Database;
+use crate::error::ApiError;
 use crate::http_client::make_http_request;
 use serde_json::{json, Value};
 use tauri::State;
@@ -9,7 +10,7 @@ pub async fn search_tunables(
     current_page: u32,
     row_count: u32,
     search_phrase: String,
-) -> Result<Value, String> {
+) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -35,17 +36,18 @@ pub async fn search_tunables(
         Some(30),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
 
 #[tauri::command]
-pub async fn get_tunable(database: State<'_, Database>, uuid: String) -> Result<Value, String> {
+pub async fn get_tunable(database: State<'_, Database>, uuid: String) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -64,13 +66,14 @@ pub async fn get_tunable(database: State<'_, Database>, uuid: String) -> Result<
         Some(30),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
 
 #[tauri::command]
@@ -80,7 +83,24 @@ pub async fn set_tunable(
     tunable: String,
     value: String,
     description: String,
-) -> Result<Value, String> {
+) -> Result<Value, ApiError> {
+    let target = tunable.clone();
+    crate::audit::audited(
+        &database,
+        "set_tunable",
+        &target,
+        set_tunable_request(database.clone(), uuid, tunable, value, description),
+    )
+    .await
+}
+
+async fn set_tunable_request(
+    database: State<'_, Database>,
+    uuid: String,
+    tunable: String,
+    value: String,
+    description: String,
+) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -107,17 +127,28 @@ pub async fn set_tunable(
         Some(30),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
 
 #[tauri::command]
-pub async fn apply_tunables(database: State<'_, Database>) -> Result<Value, String> {
+pub async fn apply_tunables(database: State<'_, Database>) -> Result<Value, ApiError> {
+    crate::audit::audited(
+        &database,
+        "apply_tunables",
+        "",
+        apply_tunables_request(database.clone()),
+    )
+    .await
+}
+
+async fn apply_tunables_request(database: State<'_, Database>) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -136,13 +167,14 @@ pub async fn apply_tunables(database: State<'_, Database>) -> Result<Value, Stri
         Some(30),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
 
 
@@ -153,15 +185,33 @@ pub async fn save_and_apply_tunable(
     tunable: String,
     value: String,
     description: String,
-) -> Result<Value, String> {
+) -> Result<Value, ApiError> {
+    let target = tunable.clone();
+    crate::audit::audited(
+        &database,
+        "save_and_apply_tunable",
+        &target,
+        save_and_apply_tunable_request(database.clone(), uuid, tunable, value, description),
+    )
+    .await
+}
+
+async fn save_and_apply_tunable_request(
+    database: State<'_, Database>,
+    uuid: String,
+    tunable: String,
+    value: String,
+    description: String,
+) -> Result<Value, ApiError> {
     // First set the tunable
-    let set_result = set_tunable(database.clone(), uuid, tunable, value, description).await?;
+    let set_result =
+        set_tunable_request(database.clone(), uuid, tunable, value, description).await?;
 
     // Check if save was successful
     if let Some(result) = set_result.get("result") {
         if result.as_str() == Some("saved") {
             // Then apply changes
-            let apply_result = apply_tunables(database).await?;
+            let apply_result = apply_tunables_request(database).await?;
 
             // Return combined result
             return Ok(json!({
@@ -184,7 +234,23 @@ pub async fn add_tunable(
     tunable: String,
     value: String,
     description: String,
-) -> Result<Value, String> {
+) -> Result<Value, ApiError> {
+    let target = tunable.clone();
+    crate::audit::audited(
+        &database,
+        "add_tunable",
+        &target,
+        add_tunable_request(database.clone(), tunable, value, description),
+    )
+    .await
+}
+
+async fn add_tunable_request(
+    database: State<'_, Database>,
+    tunable: String,
+    value: String,
+    description: String,
+) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -211,18 +277,36 @@ pub async fn add_tunable(
         Some(30),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
 
 
 #[tauri::command]
-pub async fn delete_tunable(database: State<'_, Database>, uuid: String) -> Result<Value, String> {
+pub async fn delete_tunable(
+    database: State<'_, Database>,
+    uuid: String,
+) -> Result<Value, ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "delete_tunable",
+        &target,
+        delete_tunable_request(database.clone(), uuid),
+    )
+    .await
+}
+
+async fn delete_tunable_request(
+    database: State<'_, Database>,
+    uuid: String,
+) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -241,11 +325,12 @@ pub async fn delete_tunable(database: State<'_, Database>, uuid: String) -> Resu
         Some(30),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
@@ -1,5 +1,8 @@
+use crate::cron::apply_cron_changes;
 use crate::db::Database;
+use crate::error::ApiError;
 use crate::http_client::make_http_request;
+use crate::pending::PendingChanges;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use tauri::State;
@@ -69,7 +72,7 @@ fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
 }
 
 #[tauri::command]
-pub async fn get_unbound_settings(database: State<'_, Database>) -> Result<Value, String> {
+pub async fn get_unbound_settings(database: State<'_, Database>) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -82,9 +85,10 @@ pub async fn get_unbound_settings(database: State<'_, Database>) -> Result<Value
         &url,
         None,
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -105,7 +109,8 @@ pub async fn get_unbound_settings(database: State<'_, Database>) -> Result<Value
                 "Failed to parse response: {} - Response was: {}",
                 e,
                 &response_text.chars().take(500).collect::<String>()
-            ))
+            )
+            .into())
         }
     };
 
@@ -168,7 +173,40 @@ pub async fn set_dnsbl_settings(
     wildcards: Vec<String>,
     address: String,
     nxdomain: bool,
-) -> Result<Value, String> {
+) -> Result<Value, ApiError> {
+    crate::audit::audited(
+        &database,
+        "set_dnsbl_settings",
+        "",
+        set_dnsbl_settings_request(
+            database.clone(),
+            enabled,
+            safesearch,
+            blocklist_types,
+            lists,
+            whitelists,
+            blocklists,
+            wildcards,
+            address,
+            nxdomain,
+        ),
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn set_dnsbl_settings_request(
+    database: State<'_, Database>,
+    enabled: bool,
+    safesearch: bool,
+    blocklist_types: Vec<String>,
+    lists: Vec<String>,
+    whitelists: Vec<String>,
+    blocklists: Vec<String>,
+    wildcards: Vec<String>,
+    address: String,
+    nxdomain: bool,
+) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -205,9 +243,10 @@ pub async fn set_dnsbl_settings(
         &url,
         Some(payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -223,12 +262,23 @@ pub async fn set_dnsbl_settings(
         Err(e) => Err(format!(
             "Failed to parse response: {} - Response was: {}",
             e, response_text
-        )),
+        )
+        .into()),
     }
 }
 
 #[tauri::command]
-pub async fn apply_dnsbl_settings(database: State<'_, Database>) -> Result<Value, String> {
+pub async fn apply_dnsbl_settings(database: State<'_, Database>) -> Result<Value, ApiError> {
+    crate::audit::audited(
+        &database,
+        "apply_dnsbl_settings",
+        "",
+        apply_dnsbl_settings_request(database.clone()),
+    )
+    .await
+}
+
+async fn apply_dnsbl_settings_request(database: State<'_, Database>) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -241,22 +291,25 @@ pub async fn apply_dnsbl_settings(database: State<'_, Database>) -> Result<Value
         &url,
         Some(json!({})),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
 
 const UNBOUND_DNSBL_CRON_DESCRIPTION: &str = "OPNManager Unbound DNSBL Update";
 
 #[tauri::command]
-pub async fn get_dnsbl_cron_job(database: State<'_, Database>) -> Result<Option<CronJob>, String> {
+pub async fn get_dnsbl_cron_job(
+    database: State<'_, Database>,
+) -> Result<Option<CronJob>, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -276,9 +329,10 @@ pub async fn get_dnsbl_cron_job(database: State<'_, Database>) -> Result<Option<
         &url,
         Some(payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -299,12 +353,40 @@ pub async fn get_dnsbl_cron_job(database: State<'_, Database>) -> Result<Option<
 #[tauri::command]
 pub async fn add_dnsbl_cron_job(
     database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
     minutes: String,
     hours: String,
     days: String,
     months: String,
     weekdays: String,
-) -> Result<Value, String> {
+) -> Result<Value, ApiError> {
+    let target = format!("{} {} {} {} {}", minutes, hours, days, months, weekdays);
+    crate::audit::audited(
+        &database,
+        "add_dnsbl_cron_job",
+        &target,
+        add_dnsbl_cron_job_request(
+            database.clone(),
+            pending,
+            minutes,
+            hours,
+            days,
+            months,
+            weekdays,
+        ),
+    )
+    .await
+}
+
+async fn add_dnsbl_cron_job_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    minutes: String,
+    hours: String,
+    days: String,
+    months: String,
+    weekdays: String,
+) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -313,7 +395,7 @@ pub async fn add_dnsbl_cron_job(
     let existing_job = get_dnsbl_cron_job(database.clone()).await?;
     if let Some(job) = existing_job {
         if let Some(uuid) = job.uuid {
-            delete_dnsbl_cron_job(database.clone(), uuid).await?;
+            delete_dnsbl_cron_job_request(database.clone(), pending.clone(), uuid).await?;
         }
     }
 
@@ -338,9 +420,10 @@ pub async fn add_dnsbl_cron_job(
         &url,
         Some(payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -349,7 +432,7 @@ pub async fn add_dnsbl_cron_job(
         .await
         .map_err(|e| format!("Failed to parse response: {}", e))?;
 
-    apply_cron_changes(database).await?;
+    apply_cron_changes(database, pending).await?;
 
     Ok(result)
 }
@@ -357,8 +440,24 @@ pub async fn add_dnsbl_cron_job(
 #[tauri::command]
 pub async fn delete_dnsbl_cron_job(
     database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
     uuid: String,
-) -> Result<Value, String> {
+) -> Result<Value, ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "delete_dnsbl_cron_job",
+        &target,
+        delete_dnsbl_cron_job_request(database.clone(), pending, uuid),
+    )
+    .await
+}
+
+async fn delete_dnsbl_cron_job_request(
+    database: State<'_, Database>,
+    pending: State<'_, PendingChanges>,
+    uuid: String,
+) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -371,9 +470,10 @@ pub async fn delete_dnsbl_cron_job(
         &url,
         Some(json!({})),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -382,33 +482,7 @@ pub async fn delete_dnsbl_cron_job(
         .await
         .map_err(|e| format!("Failed to parse response: {}", e))?;
 
-    apply_cron_changes(database).await?;
+    apply_cron_changes(database, pending).await?;
 
     Ok(result)
 }
-
-#[tauri::command]
-pub async fn apply_cron_changes(database: State<'_, Database>) -> Result<Value, String> {
-    let api_info = database
-        .get_default_api_info()
-        .map_err(|e| format!("Failed to get API info: {}", e))?
-        .ok_or_else(|| "API info not found".to_string())?;
-
-    let url = build_api_url(&api_info, "/api/cron/service/reconfigure");
-
-    let response = make_http_request(
-        "POST",
-        &url,
-        Some(json!({})),
-        None,
-        Some(30),
-        Some(&api_info.api_key),
-        Some(&api_info.api_secret),
-    )
-    .await?;
-
-    response
-        .json::<Value>()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
-}
@@ -1,30 +1,100 @@
 use crate::db::Database;
+use crate::error::ApiError;
 use crate::http_client::make_http_request;
+use crate::snapshots;
+use serde::Serialize;
 use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::time::{Duration, Instant};
 use tauri::State;
 use tokio::time::sleep;
 
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateState {
+    Running,
+    Done,
+    RebootPending,
+    Error,
+}
+
+#[derive(Serialize, Debug)]
+pub struct UpdateStatus {
+    state: UpdateState,
+    /// Log lines added since the line offset passed by the caller
+    new_lines: Vec<String>,
+    /// Pass back as `since_line` on the next poll to receive only new output
+    next_line: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SnapshotUpdateResult {
+    snapshot_name: Option<String>,
+    snapshot_uuid: Option<String>,
+    /// Set when the snapshot was skipped, e.g. on a non-ZFS install
+    snapshot_warning: Option<String>,
+    update_status: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ChangelogSection {
+    version: String,
+    date: Option<String>,
+    html: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PluginInfo {
+    name: String,
+    comment: String,
+    installed: bool,
+    installed_version: Option<String>,
+    latest_version: Option<String>,
+    update_available: bool,
+    /// Locked packages are held at their version and skipped by updates
+    locked: bool,
+}
+
 fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
     format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
 }
 
+// The firmware script ends its log with ***DONE***, ***REBOOT*** or ***ERROR***,
+// which is more reliable than the status field while the log is still flushing
+fn parse_update_state(status: &str, log: &str) -> UpdateState {
+    if status == "error" || log.contains("***ERROR***") {
+        UpdateState::Error
+    } else if status == "reboot" || log.contains("***REBOOT***") {
+        UpdateState::RebootPending
+    } else if status == "done" || log.contains("***DONE***") {
+        UpdateState::Done
+    } else {
+        UpdateState::Running
+    }
+}
+
 #[tauri::command]
-pub async fn check_for_updates(database: State<'_, Database>) -> Result<Value, String> {
+pub async fn check_for_updates(database: State<'_, Database>) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
         .ok_or_else(|| "API info not found".to_string())?;
 
-    let check_url = build_api_url(&api_info, "/api/core/firmware/check");
+    check_updates(&api_info).await
+}
+
+pub(crate) async fn check_updates(api_info: &crate::db::ApiInfo) -> Result<Value, ApiError> {
+    let check_url = build_api_url(api_info, "/api/core/firmware/check");
     let check_response = make_http_request(
         "POST",
         &check_url,
         Some(serde_json::json!({})),
         None,
-        Some(30),
+        api_info.long_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -34,20 +104,21 @@ pub async fn check_for_updates(database: State<'_, Database>) -> Result<Value, S
         .map_err(|e| format!("Failed to parse check response: {}", e))?;
 
     if check_body["status"] != "ok" {
-        return Err(format!("Check failed: {:?}", check_body));
+        return Err(format!("Check failed: {:?}", check_body).into());
     }
 
     // Poll for check status
-    let status_url = build_api_url(&api_info, "/api/core/firmware/upgradestatus");
+    let status_url = build_api_url(api_info, "/api/core/firmware/upgradestatus");
     loop {
         let status_response = make_http_request(
             "GET",
             &status_url,
             None,
             None,
-            Some(30),
+            api_info.request_timeout(),
             Some(&api_info.api_key),
             Some(&api_info.api_secret),
+            Some(&api_info.client_settings()),
         )
         .await?;
 
@@ -63,15 +134,16 @@ pub async fn check_for_updates(database: State<'_, Database>) -> Result<Value, S
         sleep(Duration::from_secs(2)).await;
     }
 
-    let firmware_status_url = build_api_url(&api_info, "/api/core/firmware/status");
+    let firmware_status_url = build_api_url(api_info, "/api/core/firmware/status");
     let firmware_status_response = make_http_request(
         "GET",
         &firmware_status_url,
         None,
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -80,15 +152,16 @@ pub async fn check_for_updates(database: State<'_, Database>) -> Result<Value, S
         .await
         .map_err(|e| format!("Failed to parse firmware status: {}", e))?;
 
-    let firmware_info_url = build_api_url(&api_info, "/api/core/firmware/info");
+    let firmware_info_url = build_api_url(api_info, "/api/core/firmware/info");
     let firmware_info_response = make_http_request(
         "GET",
         &firmware_info_url,
         None,
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -139,7 +212,7 @@ pub async fn check_for_updates(database: State<'_, Database>) -> Result<Value, S
 pub async fn get_changelog(
     database: State<'_, Database>,
     version: String,
-) -> Result<String, String> {
+) -> Result<String, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -154,9 +227,10 @@ pub async fn get_changelog(
         &changelog_url,
         Some(serde_json::json!({})),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -169,7 +243,17 @@ pub async fn get_changelog(
 }
 
 #[tauri::command]
-pub async fn start_update(database: State<'_, Database>) -> Result<String, String> {
+pub async fn start_update(database: State<'_, Database>) -> Result<String, ApiError> {
+    crate::audit::audited(
+        &database,
+        "start_update",
+        "",
+        start_update_request(database.clone()),
+    )
+    .await
+}
+
+async fn start_update_request(database: State<'_, Database>) -> Result<String, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -181,9 +265,10 @@ pub async fn start_update(database: State<'_, Database>) -> Result<String, Strin
         &update_url,
         Some(serde_json::json!({})),
         None,
-        Some(30),
+        api_info.long_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -193,7 +278,7 @@ pub async fn start_update(database: State<'_, Database>) -> Result<String, Strin
         .map_err(|e| format!("Failed to parse update response: {}", e))?;
 
     if update_response["status"] != "ok" {
-        return Err(format!("Update failed: {:?}", update_response));
+        return Err(format!("Update failed: {:?}", update_response).into());
     }
 
     let status_url = build_api_url(&api_info, "/api/core/firmware/upgradestatus");
@@ -210,6 +295,7 @@ pub async fn start_update(database: State<'_, Database>) -> Result<String, Strin
             Some(5),
             Some(&api_info.api_key),
             Some(&api_info.api_secret),
+            Some(&api_info.client_settings()),
         )
         .await
         {
@@ -250,11 +336,73 @@ pub async fn start_update(database: State<'_, Database>) -> Result<String, Strin
         sleep(Duration::from_secs(10)).await;
     }
 
-    Err("Update timed out or failed to detect system coming back online".to_string())
+    Err(
+        "Update timed out or failed to detect system coming back online"
+            .to_string()
+            .into(),
+    )
+}
+
+/// Polls the progress of a running firmware update. Call it on an interval
+/// after `start_update`, passing the previous `next_line` as `since_line`.
+#[tauri::command]
+pub async fn get_update_status(
+    database: State<'_, Database>,
+    since_line: Option<usize>,
+) -> Result<UpdateStatus, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let status_url = build_api_url(&api_info, "/api/core/firmware/upgradestatus");
+    let response = make_http_request(
+        "GET",
+        &status_url,
+        None,
+        None,
+        Some(10),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let upgrade_status: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse upgrade status: {}", e))?;
+
+    let status = upgrade_status["status"].as_str().unwrap_or_default();
+    let log = upgrade_status["log"].as_str().unwrap_or_default();
+
+    // The log is returned in full on every poll; the last line may still be
+    // partial, so it is only reported once a newline follows it
+    let complete = match log.rfind('\n') {
+        Some(end) => &log[..end],
+        None => "",
+    };
+    let state = parse_update_state(status, log);
+    let lines: Vec<&str> = if state == UpdateState::Running {
+        complete.lines().collect()
+    } else {
+        log.lines().collect()
+    };
+
+    let since_line = since_line.unwrap_or(0).min(lines.len());
+
+    Ok(UpdateStatus {
+        state,
+        new_lines: lines[since_line..]
+            .iter()
+            .map(|line| line.to_string())
+            .collect(),
+        next_line: lines.len(),
+    })
 }
 
 #[tauri::command]
-pub async fn get_current_firmware_status(database: State<'_, Database>) -> Result<Value, String> {
+pub async fn get_current_firmware_status(database: State<'_, Database>) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -266,9 +414,10 @@ pub async fn get_current_firmware_status(database: State<'_, Database>) -> Resul
         &firmware_status_url,
         None,
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -325,3 +474,359 @@ pub async fn get_current_firmware_status(database: State<'_, Database>) -> Resul
 
     Ok(result)
 }
+
+/// Creates a `pre-update-<version>` snapshot and returns its name and uuid
+async fn create_pre_update_snapshot(
+    database: &State<'_, Database>,
+) -> Result<(String, Option<String>), ApiError> {
+    let firmware = get_current_firmware_status(database.clone()).await?;
+    let version = firmware["target_version"]
+        .as_str()
+        .or_else(|| firmware["product"]["product_version"].as_str())
+        .unwrap_or("unknown");
+    let name = snapshots::snapshot_name(Some(&format!("pre-update-{}", version)), None);
+
+    let result = snapshots::add_snapshot(Some(name.clone()), None, None, database.clone()).await?;
+    if result["status"] != "ok" {
+        return Err(format!("Failed to create pre-update snapshot: {}", result).into());
+    }
+
+    // The add endpoint does not return the new uuid, so look it up by name
+    let uuid = snapshots::get_snapshots(1, 1000, database.clone())
+        .await?
+        .rows
+        .into_iter()
+        .find(|snapshot| snapshot.name == name)
+        .map(|snapshot| snapshot.uuid);
+
+    Ok((name, uuid))
+}
+
+/// Runs `start_update`, optionally taking a snapshot first so the update can be
+/// rolled back. Without snapshot support the update proceeds with a warning;
+/// a failed snapshot on a supported system stops the update.
+#[tauri::command]
+pub async fn start_update_with_snapshot(
+    database: State<'_, Database>,
+    auto_snapshot: bool,
+) -> Result<SnapshotUpdateResult, ApiError> {
+    crate::audit::audited(
+        &database,
+        "start_update_with_snapshot",
+        "",
+        start_update_with_snapshot_request(database.clone(), auto_snapshot),
+    )
+    .await
+}
+
+async fn start_update_with_snapshot_request(
+    database: State<'_, Database>,
+    auto_snapshot: bool,
+) -> Result<SnapshotUpdateResult, ApiError> {
+    let mut snapshot_name = None;
+    let mut snapshot_uuid = None;
+    let mut snapshot_warning = None;
+
+    if auto_snapshot {
+        if snapshots::is_snapshots_supported(database.clone()).await? {
+            let (name, uuid) = create_pre_update_snapshot(&database).await?;
+            snapshot_name = Some(name);
+            snapshot_uuid = uuid;
+        } else {
+            let warning = "Snapshots are not supported on this firewall, updating without one";
+            log::warn!("{}", warning);
+            snapshot_warning = Some(warning.to_string());
+        }
+    }
+
+    let update_status = start_update_request(database).await?;
+
+    Ok(SnapshotUpdateResult {
+        snapshot_name,
+        snapshot_uuid,
+        snapshot_warning,
+        update_status,
+    })
+}
+
+// Compares firmware versions such as "24.7.5" and "24.7.5_1" numerically
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |version: &str| -> Vec<u64> {
+        version
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|part| !part.is_empty())
+            .filter_map(|part| part.parse().ok())
+            .collect()
+    };
+    parts(a).cmp(&parts(b))
+}
+
+fn is_heading(line: &str) -> bool {
+    let line = line.trim_start().to_lowercase();
+    ["<h1", "<h2", "<h3", "<h4"]
+        .iter()
+        .any(|tag| line.starts_with(tag))
+}
+
+/// Returns the changelog of every release after the installed version up to
+/// `target_version` (or the latest available), oldest first. Headings already
+/// shown in an earlier section are dropped.
+#[tauri::command]
+pub async fn get_changelog_diff(
+    database: State<'_, Database>,
+    target_version: Option<String>,
+) -> Result<Vec<ChangelogSection>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let firmware = get_current_firmware_status(database.clone()).await?;
+    let current = firmware["product"]["product_version"]
+        .as_str()
+        .or_else(|| firmware["minor_upgrade_from"].as_str())
+        .ok_or_else(|| "Installed firmware version not found".to_string())?
+        .to_string();
+    let target = target_version
+        .or_else(|| firmware["target_version"].as_str().map(str::to_string))
+        .or_else(|| {
+            firmware["product"]["product_latest"]
+                .as_str()
+                .map(str::to_string)
+        })
+        .ok_or_else(|| "Target firmware version not found".to_string())?;
+
+    let info_url = build_api_url(&api_info, "/api/core/firmware/info");
+    let info_response = make_http_request(
+        "GET",
+        &info_url,
+        None,
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let info: Value = info_response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse firmware info: {}", e))?;
+
+    let mut releases = info["changelog"]
+        .as_array()
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|entry| {
+                    let version = entry["version"].as_str()?.to_string();
+                    let date = entry["date"].as_str().map(str::to_string);
+                    Some((version, date))
+                })
+                .filter(|(version, _)| {
+                    compare_versions(version, &current) == Ordering::Greater
+                        && compare_versions(version, &target) != Ordering::Greater
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    releases.sort_by(|(a, _), (b, _)| compare_versions(a, b));
+    releases.dedup_by(|(a, _), (b, _)| a == b);
+
+    let mut seen_headings = HashSet::new();
+    let mut sections = Vec::with_capacity(releases.len());
+
+    for (version, date) in releases {
+        let html = get_changelog(database.clone(), version.clone()).await?;
+        let html = html
+            .lines()
+            .filter(|line| !is_heading(line) || seen_headings.insert(line.trim().to_string()))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        sections.push(ChangelogSection {
+            version,
+            date,
+            html,
+        });
+    }
+
+    Ok(sections)
+}
+
+async fn get_firmware_json(
+    api_info: &crate::db::ApiInfo,
+    endpoint: &str,
+) -> Result<Value, ApiError> {
+    let url = build_api_url(api_info, endpoint);
+    let response = make_http_request(
+        "GET",
+        &url,
+        None,
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    response
+        .json()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse {} response: {}", endpoint, e)))
+}
+
+fn is_flag_set(value: &Value) -> bool {
+    value == "1" || value == &Value::Bool(true)
+}
+
+async fn fetch_plugins(database: &State<'_, Database>) -> Result<Vec<PluginInfo>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let info = get_firmware_json(&api_info, "/api/core/firmware/info").await?;
+    // Pending upgrades are only known after a check, so this may be empty
+    let status = get_firmware_json(&api_info, "/api/core/firmware/status").await?;
+
+    let upgrades = status["upgrade_packages"]
+        .as_array()
+        .map(|packages| {
+            packages
+                .iter()
+                .filter_map(|package| {
+                    Some((
+                        package["name"].as_str()?.to_string(),
+                        package["new_version"].as_str()?.to_string(),
+                    ))
+                })
+                .collect::<std::collections::HashMap<_, _>>()
+        })
+        .unwrap_or_default();
+
+    let plugins = info["plugin"]
+        .as_array()
+        .ok_or_else(|| "Firmware info did not include a plugin list".to_string())?;
+
+    Ok(plugins
+        .iter()
+        .filter_map(|plugin| {
+            let name = plugin["name"].as_str()?.to_string();
+            let installed = is_flag_set(&plugin["installed"]);
+            let version = plugin["version"].as_str().map(str::to_string);
+            let upgrade = upgrades.get(&name).cloned();
+
+            Some(PluginInfo {
+                comment: plugin["comment"].as_str().unwrap_or("").to_string(),
+                installed,
+                installed_version: if installed { version.clone() } else { None },
+                update_available: installed && upgrade.is_some(),
+                latest_version: upgrade.or(version),
+                locked: is_flag_set(&plugin["locked"]),
+                name,
+            })
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn list_plugins(database: State<'_, Database>) -> Result<Vec<PluginInfo>, ApiError> {
+    fetch_plugins(&database).await
+}
+
+/// Installed plugins with a newer version available, based on the last
+/// firmware check
+#[tauri::command]
+pub async fn get_plugin_updates(
+    database: State<'_, Database>,
+) -> Result<Vec<PluginInfo>, ApiError> {
+    Ok(fetch_plugins(&database)
+        .await?
+        .into_iter()
+        .filter(|plugin| plugin.update_available)
+        .collect())
+}
+
+// Package names are passed in the URL path, so keep them to pkg's charset
+fn validate_package_name(name: &str) -> Result<(), ApiError> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '+'))
+    {
+        return Err(format!("Invalid package name: {}", name).into());
+    }
+    Ok(())
+}
+
+async fn package_action(
+    database: &State<'_, Database>,
+    action: &str,
+    name: &str,
+) -> Result<Value, ApiError> {
+    validate_package_name(name)?;
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(
+        &api_info,
+        &format!("/api/core/firmware/{}/{}", action, name),
+    );
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(serde_json::json!({})),
+        None,
+        api_info.long_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await?;
+
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse {} response: {}", action, e))?;
+
+    if body["status"] != "ok" {
+        return Err(format!("Failed to {} {}: {:?}", action, name, body).into());
+    }
+
+    Ok(body)
+}
+
+/// Starts installing a plugin; progress is reported by `get_update_status`
+#[tauri::command]
+pub async fn install_plugin(
+    database: State<'_, Database>,
+    name: String,
+) -> Result<Value, ApiError> {
+    crate::audit::audited(
+        &database,
+        "install_plugin",
+        &name,
+        package_action(&database, "install", &name),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn reinstall_plugin(
+    database: State<'_, Database>,
+    name: String,
+) -> Result<Value, ApiError> {
+    crate::audit::audited(
+        &database,
+        "reinstall_plugin",
+        &name,
+        package_action(&database, "reinstall", &name),
+    )
+    .await
+}
@@ -0,0 +1,118 @@
+use crate::db::Database;
+use crate::error::ApiError;
+use crate::http_client::make_http_request;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::State;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LocalUser {
+    uuid: String,
+    username: String,
+    description: String,
+    groups: Vec<String>,
+    disabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ApiKey {
+    id: String,
+    username: String,
+    key: String,
+}
+
+fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
+    format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
+}
+
+async fn search_auth(
+    database: State<'_, Database>,
+    endpoint: &str,
+    search_phrase: &str,
+) -> Result<Vec<Value>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = build_api_url(&api_info, endpoint);
+
+    let payload = json!({
+        "current": 1,
+        "rowCount": -1,
+        "sort": {},
+        "searchPhrase": search_phrase
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await
+    .map_err(|e| e.missing_privilege("users"))?;
+
+    let result = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    Ok(result["rows"].as_array().cloned().unwrap_or_default())
+}
+
+fn field(row: &Value, key: &str) -> String {
+    match &row[key] {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+#[tauri::command]
+pub async fn list_users(database: State<'_, Database>) -> Result<Vec<LocalUser>, ApiError> {
+    let rows = search_auth(database, "/api/auth/user/search/", "").await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            // Memberships come back as a comma separated list of group names
+            let groups = field(row, "group_memberships")
+                .split(',')
+                .map(|group| group.trim().to_string())
+                .filter(|group| !group.is_empty())
+                .collect();
+
+            LocalUser {
+                uuid: field(row, "uuid"),
+                username: field(row, "name"),
+                description: field(row, "descr"),
+                groups,
+                disabled: field(row, "disabled") == "1",
+            }
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn list_api_keys_for_user(
+    database: State<'_, Database>,
+    username: String,
+) -> Result<Vec<ApiKey>, ApiError> {
+    let rows = search_auth(database, "/api/auth/user/search_api_key/", &username).await?;
+
+    // The search phrase also matches key fragments, so filter on the owner
+    Ok(rows
+        .iter()
+        .filter(|row| field(row, "username") == username)
+        .map(|row| ApiKey {
+            id: field(row, "id"),
+            username: field(row, "username"),
+            key: field(row, "key"),
+        })
+        .collect())
+}
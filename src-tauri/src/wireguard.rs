@@ -0,0 +1,139 @@
+use crate::db::Database;
+use crate::error::ApiError;
+use crate::http_client::make_http_request;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::State;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WireGuardPeer {
+    name: String,
+    interface: String,
+    public_key: String,
+    endpoint: String,
+    allowed_ips: Vec<String>,
+    latest_handshake: Option<i64>,
+    bytes_received: u64,
+    bytes_sent: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WireGuardStatus {
+    enabled: bool,
+    peers: Vec<WireGuardPeer>,
+}
+
+fn build_api_url(api_info: &crate::db::ApiInfo, endpoint: &str) -> String {
+    format!("{}:{}{}", api_info.api_url, api_info.port, endpoint)
+}
+
+fn plugin_error(e: ApiError) -> ApiError {
+    e.plugin_missing(
+        "WireGuard plugin not available: install os-wireguard or upgrade OPNsense to enable VPN status",
+    )
+}
+
+fn value_as_string(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Number(n)) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn value_as_u64(value: Option<&Value>) -> u64 {
+    match value {
+        Some(Value::Number(n)) => n.as_u64().unwrap_or(0),
+        Some(Value::String(s)) => s.parse().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+fn parse_peer(row: &Value) -> WireGuardPeer {
+    let allowed_ips = value_as_string(row.get("allowed-ips"))
+        .split(',')
+        .map(|ip| ip.trim().to_string())
+        .filter(|ip| !ip.is_empty() && ip != "(none)")
+        .collect();
+
+    // A handshake timestamp of 0 means the peer never connected
+    let latest_handshake = match value_as_u64(row.get("latest-handshake")) {
+        0 => None,
+        timestamp => Some(timestamp as i64),
+    };
+
+    WireGuardPeer {
+        name: value_as_string(row.get("name")),
+        interface: value_as_string(row.get("if")),
+        public_key: value_as_string(row.get("public-key")),
+        endpoint: value_as_string(row.get("endpoint")),
+        allowed_ips,
+        latest_handshake,
+        bytes_received: value_as_u64(row.get("transfer-rx")),
+        bytes_sent: value_as_u64(row.get("transfer-tx")),
+    }
+}
+
+#[tauri::command]
+pub async fn get_wireguard_status(
+    database: State<'_, Database>,
+) -> Result<WireGuardStatus, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let general_url = build_api_url(&api_info, "/api/wireguard/general/get");
+
+    let general_response = make_http_request(
+        "GET",
+        &general_url,
+        None,
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await
+    .map_err(plugin_error)?;
+
+    let general = general_response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse WireGuard settings: {}", e))?;
+
+    let enabled = general["general"]["enabled"].as_str() == Some("1");
+
+    let show_url = build_api_url(&api_info, "/api/wireguard/service/show");
+
+    let show_response = make_http_request(
+        "GET",
+        &show_url,
+        None,
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await
+    .map_err(plugin_error)?;
+
+    let show = show_response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse WireGuard status: {}", e))?;
+
+    let peers = show["rows"]
+        .as_array()
+        .map(|rows| {
+            rows.iter()
+                .filter(|row| row["type"].as_str() == Some("peer"))
+                .map(parse_peer)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(WireGuardStatus { enabled, peers })
+}
@@ -1,11 +1,34 @@
 use crate::db::Database;
+use crate::error::ApiError;
 use crate::http_client::{make_http_request, make_http_request_with_form_data};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use tauri::State;
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WolHost {
+    uuid: String,
+    interface: String,
+    mac: String,
+    description: String,
+}
+
+// Accepts colon or dash separated MAC addresses, e.g. 00:11:22:aa:bb:cc
+pub(crate) fn is_valid_mac(mac: &str) -> bool {
+    let octets: Vec<&str> = mac.split([':', '-']).collect();
+    octets.len() == 6
+        && octets
+            .iter()
+            .all(|octet| octet.len() == 2 && octet.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn wol_plugin_error(e: ApiError) -> ApiError {
+    e.plugin_missing("Wake-on-LAN plugin not available: install os-wol on the firewall")
+}
+
 // Check if WoL plugin is installed and API has required permissions
 #[tauri::command]
-pub async fn check_wol_plugin_installed(database: State<'_, Database>) -> Result<Value, String> {
+pub async fn check_wol_plugin_installed(database: State<'_, Database>) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -25,6 +48,7 @@ pub async fn check_wol_plugin_installed(database: State<'_, Database>) -> Result
         Some(10), // Short timeout
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await
     {
@@ -42,20 +66,20 @@ pub async fn check_wol_plugin_installed(database: State<'_, Database>) -> Result
             // 2. 403 - Plugin is installed but permissions are wrong
             // 3. Other errors - Network or server issues
 
-            if e.contains("404") || e.contains("API endpoint not found") {
+            if matches!(e, ApiError::NotFound(_)) {
                 log::info!("WoL plugin is not installed - API endpoint returned 404");
                 Ok(json!({
                     "installed": false,
                     "permission_error": false,
                     "error": null
                 }))
-            } else if e.contains("403") || e.contains("Permission denied") {
+            } else if matches!(e, ApiError::Forbidden(_)) {
                 // This likely means the plugin is installed, but API key doesn't have permission
                 log::warn!("WoL plugin permission error: {}", e);
                 Ok(json!({
                     "installed": true,
                     "permission_error": true,
-                    "error": e
+                    "error": e.to_string()
                 }))
             } else {
                 // For other errors, we can't be sure if the plugin is installed or not
@@ -63,7 +87,7 @@ pub async fn check_wol_plugin_installed(database: State<'_, Database>) -> Result
                 Ok(json!({
                     "installed": false,
                     "permission_error": false,
-                    "error": e
+                    "error": e.to_string()
                 }))
             }
         }
@@ -72,7 +96,7 @@ pub async fn check_wol_plugin_installed(database: State<'_, Database>) -> Result
 
 // Get available interfaces for WoL
 #[tauri::command]
-pub async fn get_wol_interfaces(database: State<'_, Database>) -> Result<Value, String> {
+pub async fn get_wol_interfaces(database: State<'_, Database>) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -85,9 +109,10 @@ pub async fn get_wol_interfaces(database: State<'_, Database>) -> Result<Value,
         &url,
         None,
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -103,12 +128,12 @@ pub async fn get_wol_interfaces(database: State<'_, Database>) -> Result<Value,
         }
     }
 
-    Err("Interface list not found".to_string())
+    Err("Interface list not found".to_string().into())
 }
 
 // Get configured WoL hosts
 #[tauri::command]
-pub async fn search_wol_hosts(database: State<'_, Database>) -> Result<Value, String> {
+pub async fn search_wol_hosts(database: State<'_, Database>) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -131,21 +156,22 @@ pub async fn search_wol_hosts(database: State<'_, Database>) -> Result<Value, St
         &url,
         Some(payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
 
 // Get ARP table devices for dropdown selection
 #[tauri::command]
-pub async fn get_arp_devices(database: State<'_, Database>) -> Result<Value, String> {
+pub async fn get_arp_devices(database: State<'_, Database>) -> Result<Value, ApiError> {
     // We'll use the existing devices API endpoint to get the ARP table
     let api_info = database
         .get_default_api_info()
@@ -162,21 +188,36 @@ pub async fn get_arp_devices(database: State<'_, Database>) -> Result<Value, Str
         &url,
         None,
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
 
 // Wake a device by UUID from saved devices
 #[tauri::command]
-pub async fn wake_device(database: State<'_, Database>, uuid: String) -> Result<Value, String> {
+pub async fn wake_device(database: State<'_, Database>, uuid: String) -> Result<Value, ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "wake_device",
+        &target,
+        wake_device_request(database.clone(), uuid),
+    )
+    .await
+}
+
+async fn wake_device_request(
+    database: State<'_, Database>,
+    uuid: String,
+) -> Result<Value, ApiError> {
     // Add debug logs for troubleshooting
     log::info!("wake_device called with UUID: {}", uuid);
     
@@ -199,9 +240,10 @@ pub async fn wake_device(database: State<'_, Database>, uuid: String) -> Result<
         &url,
         form_data,
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
     
@@ -229,7 +271,23 @@ pub async fn wake_mac_address(
     interface: String,
     mac: String,
     description: String,
-) -> Result<Value, String> {
+) -> Result<Value, ApiError> {
+    let target = mac.clone();
+    crate::audit::audited(
+        &database,
+        "wake_mac_address",
+        &target,
+        wake_mac_address_request(database.clone(), interface, mac, description),
+    )
+    .await
+}
+
+async fn wake_mac_address_request(
+    database: State<'_, Database>,
+    interface: String,
+    mac: String,
+    description: String,
+) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -251,16 +309,132 @@ pub async fn wake_mac_address(
         &url,
         Some(payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
+}
+
+// Wake a device by MAC address on the given interface
+#[tauri::command]
+pub async fn wake_on_lan(
+    database: State<'_, Database>,
+    mac: String,
+    interface: String,
+) -> Result<Value, ApiError> {
+    let target = mac.clone();
+    crate::audit::audited(
+        &database,
+        "wake_on_lan",
+        &target,
+        wake_on_lan_request(database.clone(), mac, interface),
+    )
+    .await
+}
+
+async fn wake_on_lan_request(
+    database: State<'_, Database>,
+    mac: String,
+    interface: String,
+) -> Result<Value, ApiError> {
+    let mac = mac.trim().to_lowercase().replace('-', ":");
+    if !is_valid_mac(&mac) {
+        return Err(format!("Invalid MAC address: {}", mac).into());
+    }
+
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = format!("{}:{}/api/wol/wol/set", api_info.api_url, api_info.port);
+
+    let payload = json!({
+        "wake": {
+            "interface": interface,
+            "mac": mac
+        }
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await
+    .map_err(wol_plugin_error)?;
+
+    response
+        .json::<Value>()
+        .await
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
+}
+
+// List saved WoL hosts so a configured machine can be woken by name
+#[tauri::command]
+pub async fn list_wol_hosts(database: State<'_, Database>) -> Result<Vec<WolHost>, ApiError> {
+    let api_info = database
+        .get_default_api_info()
+        .map_err(|e| format!("Failed to get API info: {}", e))?
+        .ok_or_else(|| "API info not found".to_string())?;
+
+    let url = format!(
+        "{}:{}/api/wol/wol/searchHost",
+        api_info.api_url, api_info.port
+    );
+
+    let payload = json!({
+        "current": 1,
+        "rowCount": -1,
+        "sort": {},
+        "searchPhrase": ""
+    });
+
+    let response = make_http_request(
+        "POST",
+        &url,
+        Some(payload),
+        None,
+        api_info.request_timeout(),
+        Some(&api_info.api_key),
+        Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
+    )
+    .await
+    .map_err(wol_plugin_error)?;
+
+    let result = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    let field = |row: &Value, key: &str| row[key].as_str().unwrap_or_default().to_string();
+
+    Ok(result["rows"]
+        .as_array()
+        .map(|rows| {
+            rows.iter()
+                .map(|row| WolHost {
+                    uuid: field(row, "uuid"),
+                    interface: field(row, "interface"),
+                    mac: field(row, "mac"),
+                    description: field(row, "descr"),
+                })
+                .collect()
+        })
+        .unwrap_or_default())
 }
 
 // Add a new WoL host
@@ -270,7 +444,23 @@ pub async fn add_wol_host(
     interface: String,
     mac: String,
     description: String,
-) -> Result<Value, String> {
+) -> Result<Value, ApiError> {
+    let target = mac.clone();
+    crate::audit::audited(
+        &database,
+        "add_wol_host",
+        &target,
+        add_wol_host_request(database.clone(), interface, mac, description),
+    )
+    .await
+}
+
+async fn add_wol_host_request(
+    database: State<'_, Database>,
+    interface: String,
+    mac: String,
+    description: String,
+) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -294,21 +484,39 @@ pub async fn add_wol_host(
         &url,
         Some(payload),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
 
 // Delete a WoL host
 #[tauri::command]
-pub async fn delete_wol_host(database: State<'_, Database>, uuid: String) -> Result<Value, String> {
+pub async fn delete_wol_host(
+    database: State<'_, Database>,
+    uuid: String,
+) -> Result<Value, ApiError> {
+    let target = uuid.clone();
+    crate::audit::audited(
+        &database,
+        "delete_wol_host",
+        &target,
+        delete_wol_host_request(database.clone(), uuid),
+    )
+    .await
+}
+
+async fn delete_wol_host_request(
+    database: State<'_, Database>,
+    uuid: String,
+) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -325,9 +533,10 @@ pub async fn delete_wol_host(database: State<'_, Database>, uuid: String) -> Res
         &url,
         Some(json!({})),
         None,
-        Some(30),
+        api_info.request_timeout(),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -337,7 +546,7 @@ pub async fn delete_wol_host(database: State<'_, Database>, uuid: String) -> Res
 
 // Start installation of WoL plugin
 #[tauri::command]
-pub async fn install_wol_plugin(database: State<'_, Database>) -> Result<Value, String> {
+pub async fn install_wol_plugin(database: State<'_, Database>) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -360,6 +569,7 @@ pub async fn install_wol_plugin(database: State<'_, Database>) -> Result<Value,
         Some(10), // Short timeout for just starting the installation
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
@@ -374,7 +584,7 @@ pub async fn install_wol_plugin(database: State<'_, Database>) -> Result<Value,
 
 // Check the status of a plugin installation
 #[tauri::command]
-pub async fn check_install_status(database: State<'_, Database>) -> Result<Value, String> {
+pub async fn check_install_status(database: State<'_, Database>) -> Result<Value, ApiError> {
     let api_info = database
         .get_default_api_info()
         .map_err(|e| format!("Failed to get API info: {}", e))?
@@ -399,11 +609,12 @@ pub async fn check_install_status(database: State<'_, Database>) -> Result<Value
         Some(10),
         Some(&api_info.api_key),
         Some(&api_info.api_secret),
+        Some(&api_info.client_settings()),
     )
     .await?;
 
     response
         .json::<Value>()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+        .map_err(|e| ApiError::Parse(format!("Failed to parse response: {}", e)))
 }
@@ -0,0 +1,64 @@
+import { invoke as tauriInvoke, type InvokeArgs } from '@tauri-apps/api/core';
+
+export type ApiErrorKind =
+  | 'Auth'
+  | 'Forbidden'
+  | 'NotFound'
+  | 'Timeout'
+  | 'Connection'
+  | 'Tls'
+  | 'Parse'
+  | 'PluginMissing'
+  | 'MissingPrivilege'
+  | 'Status'
+  | 'Other';
+
+// Commands reject with `{ kind, message }`; for `Status` the message is
+// `{ status, message }`
+interface SerializedApiError {
+  kind: ApiErrorKind;
+  message: string | { status: number; message: string };
+}
+
+/** A failed command, so callers can branch on `kind` instead of message text */
+export class ApiError extends Error {
+  kind: ApiErrorKind;
+  status?: number;
+
+  constructor(kind: ApiErrorKind, message: string, status?: number) {
+    super(message);
+    this.name = 'ApiError';
+    this.kind = kind;
+    this.status = status;
+  }
+
+  // Keeps `${error}` in existing toasts showing just the message
+  toString(): string {
+    return this.message;
+  }
+}
+
+function isSerializedApiError(value: unknown): value is SerializedApiError {
+  return (
+    typeof value === 'object' &&
+    value !== null &&
+    typeof (value as SerializedApiError).kind === 'string' &&
+    'message' in value
+  );
+}
+
+/** `invoke` from Tauri, rethrowing structured command errors as `ApiError` */
+export async function invoke<T>(cmd: string, args?: InvokeArgs): Promise<T> {
+  try {
+    return await tauriInvoke<T>(cmd, args);
+  } catch (err) {
+    if (isSerializedApiError(err)) {
+      const { kind, message } = err;
+      if (typeof message === 'object') {
+        throw new ApiError(kind, message.message, message.status);
+      }
+      throw new ApiError(kind, message);
+    }
+    throw err;
+  }
+}
@@ -52,6 +52,7 @@ export interface CombinedDevice {
   manufacturer: string;
   hostname: string;
   intf_description: string;
+  interfaces?: { intf: string; intf_description: string }[];
 }
 
 // Topology specific types